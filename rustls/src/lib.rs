@@ -0,0 +1,236 @@
+//! # Orb Rustls
+//!
+//! This crate provides TLS over orb streams with [rustls](https://docs.rs/rustls), independent
+//! of the async runtime.
+//!
+//! Since rustls is sans-io, [TlsStream] drives the rustls connection by reading / writing the
+//! underlying stream with orb's [AsyncRead] / [AsyncWrite] traits, so it works over
+//! `TcpStream<IO>`, `UnixStream<IO>` or any other stream.
+//!
+//! Use [connect()] / [accept()] to handshake over a stream directly, or [RustlsUpgrade], which
+//! implements [TlsUpgrade], the TLS hook of orb.
+//!
+//! See the [main Orb documentation](https://github.com/NaturalIO/orb) for more information.
+
+use orb::io::{AsyncRead, AsyncWrite};
+use orb::net::tls::{self, TlsClientConfig, TlsUpgrade};
+pub use rustls;
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, Connection, ServerConfig, ServerConnection};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+const READ_BUF_SIZE: usize = 16 * 1024;
+
+/// A TLS stream over the underlying stream `S`.
+///
+/// NOTE: Not cancel safe for write, the encrypted data might be partially written.
+pub struct TlsStream<S> {
+    io: S,
+    conn: Connection,
+    // The encrypted data read but not yet fed to rustls
+    incoming: Vec<u8>,
+    pos: usize,
+    outgoing: Vec<u8>,
+    eof: bool,
+}
+
+impl<S: AsyncRead + AsyncWrite> TlsStream<S> {
+    async fn handshake(io: S, conn: Connection) -> io::Result<Self> {
+        let mut stream =
+            Self { io, conn, incoming: Vec::new(), pos: 0, outgoing: Vec::new(), eof: false };
+        while stream.conn.is_handshaking() {
+            stream.write_tls().await?;
+            if stream.conn.is_handshaking() && stream.conn.wants_read() {
+                if stream.eof {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                stream.read_tls().await?;
+            }
+        }
+        stream.write_tls().await?;
+        Ok(stream)
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.io
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.io
+    }
+
+    /// The rustls connection state
+    #[inline]
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Send close_notify to the peer, so it reads EOF instead of error.
+    pub async fn close_notify(&mut self) -> io::Result<()> {
+        self.conn.send_close_notify();
+        self.write_tls().await
+    }
+
+    /// Write out the pending data of rustls
+    async fn write_tls(&mut self) -> io::Result<()> {
+        while self.conn.wants_write() {
+            self.outgoing.clear();
+            self.conn.write_tls(&mut self.outgoing)?;
+            self.io.write_all(&self.outgoing).await?;
+        }
+        Ok(())
+    }
+
+    /// Read from the underlying stream (if the previous data is consumed) and feed to rustls
+    async fn read_tls(&mut self) -> io::Result<()> {
+        if self.pos >= self.incoming.len() {
+            self.incoming.resize(READ_BUF_SIZE, 0);
+            let n = self.io.read(&mut self.incoming).await?;
+            self.incoming.truncate(n);
+            self.pos = 0;
+            if n == 0 {
+                self.eof = true;
+                // Signal EOF to rustls
+                self.conn.read_tls(&mut io::empty())?;
+                return Ok(());
+            }
+        }
+        let mut rd = &self.incoming[self.pos..];
+        self.pos += self.conn.read_tls(&mut rd)?;
+        if let Err(e) = self.conn.process_new_packets() {
+            // Try to send the alert
+            let _ = self.write_tls().await;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        self.write_tls().await
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite> AsyncRead for TlsStream<S> {
+    /// Returns 0 on close_notify from the peer, or UnexpectedEof error if the underlying stream is
+    /// closed without it.
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.conn.reader().read(buf) {
+                Ok(n) => return Ok(n),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            if self.eof {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            self.read_tls().await?;
+        }
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite> AsyncWrite for TlsStream<S> {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.conn.writer().write(buf)?;
+        self.write_tls().await?;
+        Ok(n)
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        self.write_tls().await?;
+        self.io.flush().await
+    }
+
+    /// Send close_notify, then shutdown the underlying stream
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.close_notify().await?;
+        self.io.shutdown().await
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite> tls::TlsStream for TlsStream<S> {
+    #[inline]
+    fn negotiated_alpn(&self) -> Option<Vec<u8>> {
+        self.conn.alpn_protocol().map(|p| p.to_vec())
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for TlsStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "tls({:?})", self.io)
+    }
+}
+
+/// Handshake as the client over `stream`, the server certificate is verified against
+/// `server_name`.
+pub async fn connect<S: AsyncRead + AsyncWrite>(
+    config: Arc<ClientConfig>, server_name: ServerName<'static>, stream: S,
+) -> io::Result<TlsStream<S>> {
+    let conn = ClientConnection::new(config, server_name).map_err(io::Error::other)?;
+    TlsStream::handshake(stream, conn.into()).await
+}
+
+/// Handshake as the server over `stream`
+pub async fn accept<S: AsyncRead + AsyncWrite>(
+    config: Arc<ServerConfig>, stream: S,
+) -> io::Result<TlsStream<S>> {
+    let conn = ServerConnection::new(config).map_err(io::Error::other)?;
+    TlsStream::handshake(stream, conn.into()).await
+}
+
+/// Implements [TlsUpgrade] with rustls configs.
+///
+/// The client side ALPN protocols are taken from [TlsClientConfig], overriding the ones in
+/// `ClientConfig`. The server side ones are from `ServerConfig::alpn_protocols`.
+#[derive(Clone, Default)]
+pub struct RustlsUpgrade {
+    client: Option<Arc<ClientConfig>>,
+    server: Option<Arc<ServerConfig>>,
+}
+
+impl RustlsUpgrade {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the config for `connect()`
+    #[inline]
+    pub fn client(mut self, config: Arc<ClientConfig>) -> Self {
+        self.client = Some(config);
+        self
+    }
+
+    /// Set the config for `accept()`
+    #[inline]
+    pub fn server(mut self, config: Arc<ServerConfig>) -> Self {
+        self.server = Some(config);
+        self
+    }
+}
+
+fn no_config(side: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("no {} config", side))
+}
+
+impl TlsUpgrade for RustlsUpgrade {
+    type Stream<S: AsyncRead + AsyncWrite + 'static> = TlsStream<S>;
+
+    async fn connect<S: AsyncRead + AsyncWrite + 'static>(
+        &self, config: &TlsClientConfig, stream: S,
+    ) -> io::Result<TlsStream<S>> {
+        let mut client = self.client.clone().ok_or_else(|| no_config("client"))?;
+        if !config.alpn.is_empty() {
+            Arc::make_mut(&mut client).alpn_protocols = config.alpn.clone();
+        }
+        let name = ServerName::try_from(config.server_name.clone())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        connect(client, name, stream).await
+    }
+
+    async fn accept<S: AsyncRead + AsyncWrite + 'static>(
+        &self, stream: S,
+    ) -> io::Result<TlsStream<S>> {
+        let server = self.server.clone().ok_or_else(|| no_config("server"))?;
+        accept(server, stream).await
+    }
+}
@@ -0,0 +1,161 @@
+use orb::io::{AsyncRead, AsyncWrite};
+use orb::net::tls::{TlsClientConfig, TlsStream as _, TlsUpgrade};
+use orb::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use orb::prelude::*;
+use orb_rustls::rustls::pki_types::pem::PemObject;
+use orb_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use orb_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+use orb_rustls::{RustlsUpgrade, TlsStream};
+use orb_test_utils::*;
+use orb_tokio::TokioRT;
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn setup() {
+    init_logger();
+}
+
+fn cert_path(name: &str) -> String {
+    format!("{}/tests/certs/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn make_configs(server_alpn: &[&[u8]]) -> (ClientConfig, ServerConfig) {
+    let certs: Vec<CertificateDer> = CertificateDer::pem_file_iter(cert_path("server.pem"))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_file(cert_path("server.key")).unwrap();
+    let mut server =
+        ServerConfig::builder().with_no_client_auth().with_single_cert(certs, key).unwrap();
+    server.alpn_protocols = server_alpn.iter().map(|p| p.to_vec()).collect();
+
+    let mut roots = RootCertStore::empty();
+    for ca in CertificateDer::pem_file_iter(cert_path("ca.pem")).unwrap() {
+        roots.add(ca.unwrap()).unwrap();
+    }
+    let client = ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+    (client, server)
+}
+
+fn make_upgrade(server_alpn: &[&[u8]]) -> RustlsUpgrade {
+    let (client, server) = make_configs(server_alpn);
+    RustlsUpgrade::new().client(Arc::new(client)).server(Arc::new(server))
+}
+
+/// Handshake over loopback, returns the ALPN protocol negotiated by (client, server)
+async fn handshake_alpn(
+    upgrade: &RustlsUpgrade, config: &TlsClientConfig,
+) -> std::io::Result<(Option<Vec<u8>>, Option<Vec<u8>>)> {
+    let mut listener = TcpListener::<TokioRT>::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = async {
+        let stream = listener.accept().await?;
+        let mut tls = upgrade.accept(stream).await?;
+        let mut buf = [0u8; 5];
+        tls.read_exact(&mut buf).await?;
+        tls.write_all(&buf).await?;
+        tls.close_notify().await?;
+        Ok::<_, std::io::Error>(tls.negotiated_alpn())
+    };
+    let client = async {
+        let stream = TcpStream::<TokioRT>::connect(&addr).await?;
+        let mut tls = upgrade.connect(config, stream).await?;
+        tls.write_all(b"hello").await?;
+        let mut buf = [0u8; 5];
+        tls.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"hello");
+        // close_notify from the server
+        assert_eq!(tls.read(&mut buf).await?, 0);
+        Ok::<_, std::io::Error>(tls.negotiated_alpn())
+    };
+    let (server, client) = futures_lite::future::zip(server, client).await;
+    Ok((client?, server?))
+}
+
+#[rstest]
+fn test_tls_alpn(setup: ()) {
+    let _ = setup; // Explicitly ignore the fixture value
+    let rt = TokioRT::new_current_thread();
+    rt.block_on(async {
+        let upgrade = make_upgrade(&[b"h2", b"http/1.1"]);
+        let config = TlsClientConfig::new("localhost").alpn("http/1.1");
+        let (client, server) = handshake_alpn(&upgrade, &config).await.expect("handshake");
+        assert_eq!(client.as_deref(), Some(&b"http/1.1"[..]));
+        assert_eq!(client, server);
+
+        // Without ALPN
+        let config = TlsClientConfig::new("localhost");
+        let (client, server) = handshake_alpn(&upgrade, &config).await.expect("handshake");
+        assert_eq!(client, None);
+        assert_eq!(server, None);
+
+        // Name mismatch
+        let config = TlsClientConfig::new("example.com");
+        assert!(handshake_alpn(&upgrade, &config).await.is_err());
+    });
+}
+
+/// Echo over the TLS stream with connect() / accept(), on both tcp and unix socket
+#[rstest]
+fn test_tls_connect_accept_echo(setup: ()) {
+    let _ = setup;
+    let rt = TokioRT::new_current_thread();
+    rt.block_on(async {
+        let (client, server) = make_configs(&[]);
+        let (client, server) = (Arc::new(client), Arc::new(server));
+        let name = ServerName::try_from("localhost").unwrap();
+
+        let mut listener = TcpListener::<TokioRT>::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let server_side = async {
+            let stream = listener.accept().await?;
+            echo(orb_rustls::accept(server.clone(), stream).await?).await
+        };
+        let client_side = async {
+            let stream = TcpStream::<TokioRT>::connect(&addr).await?;
+            ping(orb_rustls::connect(client.clone(), name.clone(), stream).await?).await
+        };
+        let (r1, r2) = futures_lite::future::zip(server_side, client_side).await;
+        r1.expect("server");
+        r2.expect("client");
+
+        let path = format!("/tmp/orb_rustls_test_echo_{}", std::process::id());
+        let _ = std::fs::remove_file(&path);
+        let mut listener = UnixListener::<TokioRT>::bind(&path).expect("bind");
+        let server_side = async {
+            let stream = listener.accept().await?;
+            echo(orb_rustls::accept(server.clone(), stream).await?).await
+        };
+        let client_side = async {
+            let stream = UnixStream::<TokioRT>::connect(&path).await?;
+            ping(orb_rustls::connect(client.clone(), name.clone(), stream).await?).await
+        };
+        let (r1, r2) = futures_lite::future::zip(server_side, client_side).await;
+        let _ = std::fs::remove_file(&path);
+        r1.expect("server");
+        r2.expect("client");
+    });
+}
+
+async fn echo<S: AsyncRead + AsyncWrite>(mut tls: TlsStream<S>) -> std::io::Result<()> {
+    let mut buf = [0u8; 1024];
+    loop {
+        let n = tls.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        tls.write_all(&buf[..n]).await?;
+    }
+}
+
+async fn ping<S: AsyncRead + AsyncWrite>(mut tls: TlsStream<S>) -> std::io::Result<()> {
+    for i in 0..3 {
+        let msg = format!("ping {}", i);
+        tls.write_all(msg.as_bytes()).await?;
+        let mut buf = vec![0u8; msg.len()];
+        tls.read_exact(&mut buf).await?;
+        assert_eq!(buf, msg.as_bytes());
+    }
+    tls.close_notify().await
+}
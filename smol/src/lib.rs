@@ -29,6 +29,15 @@
 //! let rt = SmolRT::new(executor);
 //! ```
 //!
+//! With an executor driven by the worker threads:
+//!
+//! ```rust
+//! use orb_smol::SmolRT;
+//!
+//! let rt = SmolRT::new_multi_thread(4);
+//! rt.shutdown();
+//! ```
+//!
 //! With the global executor (requires the `global` feature):
 //!
 //! ```rust
@@ -42,8 +51,9 @@ use async_executor::Executor;
 use async_io::{Async, Timer};
 use futures_lite::{future::block_on, stream::StreamExt};
 use orb::io::{AsyncFd, AsyncIO};
-use orb::runtime::{AsyncExec, AsyncHandle, ThreadHandle};
-use orb::time::{AsyncTime, TimeInterval};
+use orb::runtime::{AsyncExec, AsyncHandle, BlockingError, RuntimeFlavor, ThreadHandle};
+use orb::time::{AsyncTime, TimeDelay, TimeInterval, count_missed_ticks};
+use orb::utils::{ShutdownToken, ShutdownTrigger};
 use std::fmt;
 use std::future::Future;
 use std::io;
@@ -54,18 +64,69 @@ use std::os::fd::{AsFd, AsRawFd};
 use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::task::*;
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
 /// The SmolRT implements AsyncRuntime trait
 #[derive(Clone)]
-pub struct SmolRT(Option<Arc<Executor<'static>>>);
+pub struct SmolRT {
+    exec: Option<Arc<Executor<'static>>>,
+    // The runner threads from new_multi_thread()
+    runners: Option<Arc<Runners>>,
+}
 
 impl fmt::Debug for SmolRT {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.0.is_some() { write!(f, "smol") } else { write!(f, "smol(global)") }
+        match (&self.exec, &self.runners) {
+            (Some(_), Some(runners)) => write!(f, "smol(threads={})", runners.workers),
+            (Some(_), None) => write!(f, "smol"),
+            (None, _) => write!(f, "smol(global)"),
+        }
+    }
+}
+
+/// The threads driving the executor
+struct Runners {
+    workers: usize,
+    trigger: ShutdownTrigger,
+    threads: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Runners {
+    fn new(exec: &Arc<Executor<'static>>, workers: usize) -> Self {
+        let (trigger, token) = ShutdownToken::new();
+        let threads = (0..workers)
+            .map(|i| {
+                let exec = exec.clone();
+                let token = token.clone();
+                std::thread::Builder::new()
+                    .name(format!("smol-{}", i))
+                    .spawn(move || block_on(exec.run(token.cancelled())))
+                    .expect("spawn thread")
+            })
+            .collect();
+        Self { workers, trigger, threads: Mutex::new(threads) }
+    }
+
+    fn shutdown(&self) {
+        self.trigger.shutdown();
+        let threads = std::mem::take(&mut *self.threads.lock().unwrap());
+        let current = std::thread::current().id();
+        for thread in threads {
+            // The last SmolRT might be dropped by a task on the runner, it will exit by itself.
+            if thread.thread().id() != current {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+impl Drop for Runners {
+    fn drop(&mut self) {
+        self.shutdown();
     }
 }
 
@@ -73,13 +134,41 @@ impl SmolRT {
     #[cfg(feature = "global")]
     #[inline]
     pub fn new_global() -> Self {
-        Self(None)
+        Self { exec: None, runners: None }
     }
 
     /// spawn coroutine with specified Executor
+    ///
+    /// The threads running the executor are owned by the user, they are not stopped on drop.
     #[inline]
     pub fn new(executor: Arc<Executor<'static>>) -> Self {
-        Self(Some(executor))
+        Self { exec: Some(executor), runners: None }
+    }
+
+    /// Create an executor driven by `workers` threads (0 for the number of CPU cores), which are
+    /// named as `smol-{n}`.
+    ///
+    /// The threads run until [shutdown()](Self::shutdown), or the last clone of the SmolRT is
+    /// dropped.
+    pub fn new_multi_thread(workers: usize) -> Self {
+        let workers = if workers > 0 {
+            workers
+        } else {
+            std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+        };
+        let exec = Arc::new(Executor::new());
+        let runners = Runners::new(&exec, workers);
+        Self { exec: Some(exec), runners: Some(Arc::new(runners)) }
+    }
+
+    /// Stop the runner threads of [new_multi_thread()](Self::new_multi_thread) and wait for them
+    /// to exit. The tasks left in the executor will not run anymore.
+    ///
+    /// No-op for the other kinds of runtime.
+    pub fn shutdown(&self) {
+        if let Some(runners) = &self.runners {
+            runners.shutdown();
+        }
     }
 }
 
@@ -108,12 +197,21 @@ impl AsyncIO for SmolRT {
     fn to_async_fd_rd<T: AsRawFd + AsFd + Send + Sync + 'static>(
         fd: T,
     ) -> io::Result<Self::AsyncFd<T>> {
-        Ok(SmolFD(Async::new(fd)?))
+        Self::to_async_fd(fd, orb::io::Interest::READ)
     }
 
     #[inline(always)]
     fn to_async_fd_rw<T: AsRawFd + AsFd + Send + Sync + 'static>(
         fd: T,
+    ) -> io::Result<Self::AsyncFd<T>> {
+        Self::to_async_fd(fd, orb::io::Interest::READ_WRITE)
+    }
+
+    /// NOTE: `Async::new()` always registers for both read and write, the narrowing of
+    /// `interest` is ignored.
+    #[inline(always)]
+    fn to_async_fd<T: AsRawFd + AsFd + Send + Sync + 'static>(
+        fd: T, _interest: orb::io::Interest,
     ) -> io::Result<Self::AsyncFd<T>> {
         Ok(SmolFD(Async::new(fd)?))
     }
@@ -122,15 +220,22 @@ impl AsyncIO for SmolRT {
 impl AsyncTime for SmolRT {
     type Interval = SmolInterval;
 
+    type Delay = SmolDelay;
+
     #[inline(always)]
     fn sleep(d: Duration) -> impl Future + Send {
         Timer::after(d)
     }
 
+    #[inline(always)]
+    fn delay(d: Duration) -> Self::Delay {
+        SmolDelay(Timer::after(d))
+    }
+
     #[inline(always)]
     fn tick(d: Duration) -> Self::Interval {
         let later = std::time::Instant::now() + d;
-        SmolInterval(Timer::interval_at(later, d))
+        SmolInterval { timer: Timer::interval_at(later, d), period: d, missed: 0 }
     }
 }
 
@@ -156,8 +261,9 @@ pub struct SmolJoinHandle<T>(Option<async_executor::Task<T>>);
 
 impl<T: Send> AsyncHandle<T> for SmolJoinHandle<T> {
     #[inline(always)]
-    fn abort(self) {
-        // do nothing, the inner task will be dropped
+    fn abort(mut self) {
+        // Dropping the task cancels it, while dropping the handle detaches
+        drop(self.0.take());
     }
 
     #[inline]
@@ -203,7 +309,8 @@ impl<T> Drop for SmolJoinHandle<T> {
     }
 }
 
-pub struct BlockingJoinHandle<T>(async_executor::Task<T>);
+/// ThreadHandle implementation for smol
+pub struct BlockingJoinHandle<T>(async_executor::Task<std::thread::Result<T>>);
 
 impl<T> ThreadHandle<T> for BlockingJoinHandle<T> {
     #[inline]
@@ -213,13 +320,13 @@ impl<T> ThreadHandle<T> for BlockingJoinHandle<T> {
 }
 
 impl<T> Future for BlockingJoinHandle<T> {
-    type Output = Result<T, ()>;
+    type Output = Result<T, BlockingError>;
 
     #[inline]
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let _self = unsafe { self.get_unchecked_mut() };
         if let Poll::Ready(r) = Pin::new(&mut _self.0).poll(cx) {
-            return Poll::Ready(Ok(r));
+            return Poll::Ready(r.map_err(BlockingError::Panic));
         }
         Poll::Pending
     }
@@ -238,7 +345,7 @@ impl AsyncExec for SmolRT {
     {
         // Although SmolJoinHandle don't need Send marker, but here in the spawn()
         // need to restrict the requirements
-        let handle = match &self.0 {
+        let handle = match &self.exec {
             Some(exec) => exec.spawn(unwind_wrap!(f)),
             None => {
                 #[cfg(feature = "global")]
@@ -268,7 +375,10 @@ impl AsyncExec for SmolRT {
         F: FnOnce() -> R + Send + 'static,
         R: Send + 'static,
     {
-        BlockingJoinHandle(blocking::unblock(f))
+        // blocking will propagate the panic on await, capture it inside the thread
+        BlockingJoinHandle(blocking::unblock(move || {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+        }))
     }
 
     /// Run a future to completion on the runtime
@@ -281,7 +391,15 @@ impl AsyncExec for SmolRT {
         F: Future<Output = R> + Send,
         R: Send + 'static,
     {
-        if let Some(exec) = &self.0 {
+        self.block_on_local(f)
+    }
+
+    #[inline]
+    fn block_on_local<F, R>(&self, f: F) -> R
+    where
+        F: Future<Output = R>,
+    {
+        if let Some(exec) = &self.exec {
             block_on(exec.run(f))
         } else {
             #[cfg(feature = "global")]
@@ -292,21 +410,77 @@ impl AsyncExec for SmolRT {
             unreachable!();
         }
     }
+
+    /// Only known for [SmolRT::new_multi_thread()]. Otherwise the threads running the executor
+    /// are up to the user.
+    #[inline]
+    fn worker_count(&self) -> Option<usize> {
+        self.runners.as_ref().map(|runners| runners.workers)
+    }
+
+    #[inline]
+    fn flavor(&self) -> RuntimeFlavor {
+        match (&self.exec, &self.runners) {
+            (Some(_), Some(_)) => RuntimeFlavor::MultiThread,
+            (Some(_), None) => RuntimeFlavor::Unknown,
+            (None, _) => RuntimeFlavor::Global,
+        }
+    }
 }
 
 /// Associate type for SmolRT
-pub struct SmolInterval(Timer);
+pub struct SmolInterval {
+    timer: Timer,
+    // Timer does not expose the period
+    period: Duration,
+    missed: u32,
+}
 
 impl TimeInterval for SmolInterval {
     #[inline]
     fn poll_tick(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Instant> {
         let _self = self.get_mut();
-        match _self.0.poll_next(ctx) {
-            Poll::Ready(Some(i)) => Poll::Ready(i),
+        match _self.timer.poll_next(ctx) {
+            Poll::Ready(Some(i)) => {
+                _self.missed = count_missed_ticks(i, SmolRT::now(), _self.period);
+                Poll::Ready(i)
+            }
             Poll::Ready(None) => unreachable!(),
             Poll::Pending => Poll::Pending,
         }
     }
+
+    #[inline]
+    fn missed_ticks(&self) -> u32 {
+        self.missed
+    }
+}
+
+/// Associate type for SmolRT
+pub struct SmolDelay(Timer);
+
+impl Future for SmolDelay {
+    type Output = ();
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<()> {
+        match Pin::new(&mut self.get_mut().0).poll(ctx) {
+            Poll::Ready(_) => Poll::Ready(()),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl TimeDelay for SmolDelay {
+    #[inline]
+    fn reset(&mut self, d: Duration) {
+        self.0.set_after(d);
+    }
+
+    #[inline]
+    fn cancel(&mut self) {
+        self.0 = Timer::never();
+    }
 }
 
 /// Associate type for SmolRT
@@ -1,7 +1,7 @@
 use async_executor::Executor;
 use orb::prelude::*;
 use orb_smol::SmolRT;
-use orb_test_utils::{runtime::*, time::*, *};
+use orb_test_utils::*;
 use rstest::*;
 use std::sync::Arc;
 use std::time::Duration;
@@ -16,22 +16,18 @@ fn setup() {
 fn test_smol_global(setup: ()) {
     let _ = setup; // Explicitly ignore the fixture value
     let rt = SmolRT::new_global();
-    test_spawn_async(&rt);
-    test_spawn_blocking::<SmolRT>(&rt);
-    test_sleep(&rt);
-    test_tick(&rt);
-    test_tick_stream(&rt);
+    conformance::run_exec(&rt);
+    conformance::run_time(&rt);
+    conformance::run_io(&rt);
 }
 
 #[rstest]
 fn test_smol_rt_with_executor(setup: ()) {
     let _ = setup; // Explicitly ignore the fixture value
     let rt = SmolRT::new(Arc::new(Executor::new()));
-    test_spawn_async(&rt);
-    test_spawn_blocking::<SmolRT>(&rt);
-    test_sleep(&rt);
-    test_tick(&rt);
-    test_tick_stream(&rt);
+    conformance::run_exec(&rt);
+    conformance::run_time(&rt);
+    conformance::run_io(&rt);
 }
 
 #[cfg(not(feature = "unwind"))]
@@ -67,3 +63,103 @@ fn test_smol_rt_panic(setup: ()) {
         println!("panic captured");
     });
 }
+
+#[rstest]
+fn test_smol_flavor(setup: ()) {
+    let _ = setup;
+    use orb::runtime::RuntimeFlavor;
+    let rt = SmolRT::new(Arc::new(Executor::new()));
+    assert_eq!(rt.worker_count(), None);
+    assert_eq!(rt.flavor(), RuntimeFlavor::Unknown);
+    #[cfg(feature = "global")]
+    assert_eq!(SmolRT::new_global().flavor(), RuntimeFlavor::Global);
+}
+
+#[rstest]
+fn test_smol_multi_thread(setup: ()) {
+    let _ = setup;
+    use orb::runtime::RuntimeFlavor;
+    use std::collections::HashSet;
+    let rt = SmolRT::new_multi_thread(2);
+    assert_eq!(rt.worker_count(), Some(2));
+    assert_eq!(rt.flavor(), RuntimeFlavor::MultiThread);
+    let threads = rt.block_on(async {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                rt.spawn(async {
+                    // Blocking the thread, so the other tasks go to the other runners
+                    std::thread::sleep(Duration::from_millis(100));
+                    std::thread::current().name().map(String::from)
+                })
+            })
+            .collect();
+        let mut threads = HashSet::new();
+        for h in handles {
+            threads.insert(h.await.unwrap());
+        }
+        threads
+    });
+    assert!(threads.contains(&Some("smol-0".to_string())), "{:?}", threads);
+    assert!(threads.contains(&Some("smol-1".to_string())), "{:?}", threads);
+    rt.shutdown();
+}
+
+#[rstest]
+fn test_smol_multi_thread_drop(setup: ()) {
+    let _ = setup;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    struct DropCount(Arc<AtomicUsize>);
+    impl Drop for DropCount {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let rt = SmolRT::new_multi_thread(2);
+    let _rt = rt.clone();
+    for _ in 0..2 {
+        let guard = DropCount(dropped.clone());
+        rt.spawn_detach(async move {
+            let _guard = guard;
+            std::future::pending::<()>().await
+        });
+    }
+    std::thread::sleep(Duration::from_millis(50));
+    drop(rt);
+    // The clone keeps the runners
+    assert_eq!(dropped.load(Ordering::SeqCst), 0);
+    // The runner threads are joined, then the executor with the pending tasks is dropped
+    drop(_rt);
+    assert_eq!(dropped.load(Ordering::SeqCst), 2);
+
+    // Dropping the last clone inside the task should not deadlock
+    let rt = SmolRT::new_multi_thread(1);
+    let (tx, rx) = std::sync::mpsc::channel();
+    let _rt = rt.clone();
+    rt.spawn_detach(async move {
+        drop(_rt);
+        tx.send(()).unwrap();
+    });
+    drop(rt);
+    rx.recv_timeout(Duration::from_secs(1)).expect("task finished");
+
+    // The executor shared by user is not affected
+    let exec = Arc::new(Executor::new());
+    drop(SmolRT::new(exec.clone()));
+    assert_eq!(Arc::strong_count(&exec), 1);
+}
+
+#[rstest]
+fn test_smol_is_finished(setup: ()) {
+    let _ = setup;
+    let rt = SmolRT::new_multi_thread(1);
+    let handle = rt.spawn(async {
+        SmolRT::sleep(Duration::from_millis(100)).await;
+        1
+    });
+    assert!(!handle.is_finished());
+    std::thread::sleep(Duration::from_millis(500));
+    // Reflects the completion before joined
+    assert!(handle.is_finished());
+    assert_eq!(rt.block_on(handle).unwrap(), 1);
+}
@@ -1,6 +1,6 @@
 use async_executor::Executor;
 use orb_smol::SmolRT;
-use orb_test_utils::{net::*, *};
+use orb_test_utils::*;
 use rstest::*;
 use std::sync::Arc;
 
@@ -10,27 +10,14 @@ fn setup() {
 }
 
 #[rstest]
-fn test_addr_resolve(setup: ()) {
+fn test_net_with_executor(setup: ()) {
     let _ = setup; // Explicitly ignore the fixture value
-    let rt = SmolRT::new(Arc::new(Executor::new()));
-    test_unify_addr_resolve::<SmolRT>(&rt);
-}
-#[rstest]
-#[case(SmolRT::new(std::sync::Arc::new(async_executor::Executor::new())))]
-#[cfg(feature = "global")]
-#[case(SmolRT::new_global())]
-fn test_tcp(setup: (), #[case] rt: SmolRT) {
-    let _ = setup; // Explicitly ignore the fixture value
-    test_tcp_client_server(&rt);
-    test_unify_tcp_client_server(&rt);
+    conformance::run_net(&SmolRT::new(Arc::new(Executor::new())));
 }
 
-#[rstest]
-#[case(SmolRT::new(std::sync::Arc::new(async_executor::Executor::new())))]
 #[cfg(feature = "global")]
-#[case(SmolRT::new_global())]
-fn test_unix(setup: (), #[case] rt: SmolRT) {
+#[rstest]
+fn test_net_global(setup: ()) {
     let _ = setup; // Explicitly ignore the fixture value
-    test_unix_client_server(&rt);
-    test_unify_unix_client_server(&rt);
+    conformance::run_net(&SmolRT::new_global());
 }
@@ -0,0 +1,167 @@
+use super::AsyncWrite;
+use crate::runtime::{AsyncExec, AsyncHandle};
+use crate::utils::sync::{WaitList, poll_once};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct FlushState {
+    buf: Vec<u8>,
+    // The companion task is writing
+    busy: bool,
+    closed: bool,
+    err: Option<io::ErrorKind>,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<FlushState>,
+    // Notify the companion task there's data
+    data: WaitList,
+    // Notify the writer the buffer is flushed
+    flushed: WaitList,
+}
+
+impl Shared {
+    async fn wait(&self, f: impl Fn(&FlushState) -> bool) -> io::Result<()> {
+        loop {
+            let mut listen = self.flushed.listen();
+            poll_once(&mut listen).await;
+            {
+                let mut state = self.state.lock().unwrap();
+                if let Some(e) = state.err.take() {
+                    return Err(e.into());
+                }
+                if f(&state) {
+                    return Ok(());
+                }
+                if state.closed {
+                    return Err(io::ErrorKind::BrokenPipe.into());
+                }
+            }
+            listen.await;
+        }
+    }
+}
+
+/// A buffered writer which flushes automatically, without explicit `flush()` calls.
+///
+/// The data is written by a companion task, woken up after each write, and it yields before
+/// flushing until the buffer stops growing. So the writes within one turn of the current task
+/// (until an await pends) are coalesced into one write of the underlying writer.
+///
+/// When the buffer reaches `capacity`, `write()` waits for the flush. Each write of the
+/// underlying writer is followed by its `flush()`, so the data buffered by `W` goes out too.
+/// The error of underlying writer is returned on the subsequent `write()` / `flush()`.
+///
+/// # Type Parameters
+///
+/// * `W` - The underlying writer, owned by the companion task
+/// * `IO` - The runtime to spawn the companion task
+pub struct AutoFlushWriter<W: AsyncWrite + 'static, IO: AsyncExec> {
+    shared: Arc<Shared>,
+    capacity: usize,
+    handle: Option<IO::AsyncHandle<W>>,
+}
+
+impl<W: AsyncWrite + 'static, IO: AsyncExec> AutoFlushWriter<W, IO> {
+    pub fn new(rt: &IO, writer: W, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity {} must > 0", capacity);
+        let shared = Arc::new(Shared::default());
+        let handle = rt.spawn(Self::flush_loop(shared.clone(), writer, capacity));
+        Self { shared, capacity, handle: Some(handle) }
+    }
+
+    async fn flush_loop(shared: Arc<Shared>, mut writer: W, capacity: usize) -> W {
+        let mut data = Vec::new();
+        loop {
+            let mut listen = shared.data.listen();
+            poll_once(&mut listen).await;
+            let idle = {
+                let state = shared.state.lock().unwrap();
+                if state.buf.is_empty() && state.closed {
+                    return writer;
+                }
+                state.buf.is_empty()
+            };
+            if idle {
+                listen.await;
+                // Debounce to the next poll, until the writer stops appending (when it pends or
+                // turns to other work), so the writes within the same turn are coalesced.
+                loop {
+                    let len = shared.state.lock().unwrap().buf.len();
+                    futures_lite::future::yield_now().await;
+                    let state = shared.state.lock().unwrap();
+                    if state.buf.len() == len || state.buf.len() >= capacity || state.closed {
+                        break;
+                    }
+                }
+            }
+            {
+                let mut state = shared.state.lock().unwrap();
+                std::mem::swap(&mut state.buf, &mut data);
+                state.busy = !data.is_empty();
+            }
+            if !data.is_empty() {
+                let r = match writer.write_all(&data).await {
+                    Ok(()) => writer.flush().await,
+                    Err(e) => Err(e),
+                };
+                data.clear();
+                let mut state = shared.state.lock().unwrap();
+                state.busy = false;
+                if let Err(e) = r {
+                    state.err = Some(e.kind());
+                    state.buf.clear();
+                    state.closed = true;
+                }
+            }
+            shared.flushed.notify_all();
+        }
+    }
+
+    /// Wait until the buffer is written to the underlying writer.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.shared.wait(|state| state.buf.is_empty() && !state.busy).await
+    }
+
+    /// Flush the buffer, stop the companion task and return the underlying writer.
+    pub async fn close(mut self) -> io::Result<W> {
+        self.flush().await?;
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.data.notify_one();
+        let handle = self.handle.take().unwrap();
+        handle.await.map_err(|_| io::Error::other("flush task failed"))
+    }
+}
+
+impl<W: AsyncWrite + 'static, IO: AsyncExec> AsyncWrite for AutoFlushWriter<W, IO> {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let capacity = self.capacity;
+        self.shared.wait(|state| state.buf.len() < capacity).await?;
+        let n = {
+            let mut state = self.shared.state.lock().unwrap();
+            let n = std::cmp::min(buf.len(), capacity - state.buf.len());
+            state.buf.extend_from_slice(&buf[..n]);
+            n
+        };
+        self.shared.data.notify_one();
+        Ok(n)
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> io::Result<()> {
+        AutoFlushWriter::flush(self).await
+    }
+}
+
+impl<W: AsyncWrite + 'static, IO: AsyncExec> Drop for AutoFlushWriter<W, IO> {
+    /// The companion task flushes the remaining data in the background, then exits.
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.shared.state.lock().unwrap().closed = true;
+            self.shared.data.notify_one();
+            handle.detach();
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{AsyncRead, AsyncWrite};
+use super::{AsyncRead, AsyncSeek, AsyncWrite};
 use std::future::Future;
 use std::{fmt, io};
 
@@ -43,12 +43,102 @@ impl AsyncBufRead {
         self.pos += n;
         Ok(n)
     }
+
+    /// Returns the unread data in the internal buffer, refill from the reader if empty.
+    ///
+    /// Similar to std `BufRead::fill_buf()`, an empty slice returned means EOF.
+    /// Call [consume](Self::consume) to mark the data as read.
+    #[inline]
+    pub async fn fill_buf<T: AsyncRead>(&mut self, reader: &mut T) -> io::Result<&[u8]> {
+        if self.pos >= self.cap {
+            self.cap = reader.read(&mut self.buf).await?;
+            self.pos = 0;
+        }
+        Ok(&self.buf[self.pos..self.cap])
+    }
+
+    /// Mark `amt` bytes of the buffer returned by [fill_buf](Self::fill_buf) as read.
+    ///
+    /// `amt` exceeds the unread data will be clamped.
+    #[inline]
+    pub fn consume(&mut self, amt: usize) {
+        self.pos = std::cmp::min(self.pos + amt, self.cap);
+    }
+
+    /// Read fresh data from the reader, return the whole unread region of the internal buffer
+    /// (the unread data left by previous calls, followed by the fresh data).
+    ///
+    /// Unlike [fill_buf](Self::fill_buf), it reads even if there's unread data, so that a parser
+    /// can work in place on the borrowed slice, and ask for more when the data is incomplete.
+    /// The unread data is moved to the front to make room. Call [consume](Self::consume) to mark
+    /// the data as read.
+    ///
+    /// The reader is not called when the buffer is full of unread data. The returned slice does not
+    /// grow on EOF (empty slice if nothing unread).
+    pub async fn read_chunk<T: AsyncRead>(&mut self, reader: &mut T) -> io::Result<&[u8]> {
+        if self.pos > 0 {
+            self.buf.copy_within(self.pos..self.cap, 0);
+            self.cap -= self.pos;
+            self.pos = 0;
+        }
+        if self.cap < self.buf.len() {
+            self.cap += reader.read(&mut self.buf[self.cap..]).await?;
+        }
+        Ok(&self.buf[..self.cap])
+    }
+
+    /// Read a line in chunks of at most `chunk_max` bytes, so that an overlong line does not
+    /// take unbounded memory.
+    ///
+    /// The chunk ending with the newline (included in the data) is flagged with `is_final`, the
+    /// chunks before it are partial. On EOF, the remaining data is returned as the final chunk,
+    /// an empty final chunk means EOF.
+    pub async fn read_line_chunked<T: AsyncRead>(
+        &mut self, reader: &mut T, chunk_max: usize,
+    ) -> io::Result<LineChunk> {
+        assert!(chunk_max > 0, "chunk_max {} must > 0", chunk_max);
+        let mut data = Vec::new();
+        while data.len() < chunk_max {
+            let buf = self.fill_buf(reader).await?;
+            if buf.is_empty() {
+                return Ok(LineChunk { data, is_final: true });
+            }
+            let limit = std::cmp::min(buf.len(), chunk_max - data.len());
+            if let Some(i) = buf[..limit].iter().position(|&c| c == b'\n') {
+                data.extend_from_slice(&buf[..=i]);
+                self.consume(i + 1);
+                return Ok(LineChunk { data, is_final: true });
+            }
+            data.extend_from_slice(&buf[..limit]);
+            self.consume(limit);
+        }
+        Ok(LineChunk { data, is_final: false })
+    }
+
+    /// Discard the buffered data, return the bytes unread
+    #[inline]
+    fn discard(&mut self) -> usize {
+        let unread = self.cap - self.pos;
+        self.pos = 0;
+        self.cap = 0;
+        unread
+    }
+}
+
+/// A piece of line returned by [AsyncBufRead::read_line_chunked()]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineChunk {
+    pub data: Vec<u8>,
+    /// True if the chunk ends the line (with the newline, or on EOF)
+    pub is_final: bool,
 }
 
 /// A buffered writer that wraps an `AsyncWrite` trait object and a buffer.
 pub struct AsyncBufWrite {
     buf: Vec<u8>,
     pos: usize,
+    // 0 to flush only when full
+    flush_threshold: usize,
 }
 
 impl AsyncBufWrite {
@@ -56,7 +146,24 @@ impl AsyncBufWrite {
     #[inline]
     pub fn new(capacity: usize) -> Self {
         assert!(capacity > 0, "capacity {} must > 0", capacity);
-        AsyncBufWrite { buf: vec![0; capacity], pos: 0 }
+        AsyncBufWrite { buf: vec![0; capacity], pos: 0, flush_threshold: 0 }
+    }
+
+    /// Flush once the buffered data reaches `threshold` bytes, even if there's space remains, so
+    /// the data is not held too long. 0 (the default) to flush only when the buffer is full.
+    #[inline]
+    pub fn set_flush_threshold(&mut self, threshold: usize) {
+        self.flush_threshold = threshold;
+    }
+
+    /// Flush if the buffered data reaches the threshold set by
+    /// [set_flush_threshold()](Self::set_flush_threshold).
+    #[inline]
+    pub async fn flush_if_needed<W: AsyncWrite>(&mut self, writer: &mut W) -> io::Result<()> {
+        if self.flush_threshold > 0 && self.pos >= self.flush_threshold {
+            self.flush(writer).await?;
+        }
+        Ok(())
     }
 
     /// Flushes the buffered data to the underlying writer.
@@ -89,6 +196,7 @@ impl AsyncBufWrite {
         let n = buf.len();
         self.buf[self.pos..self.pos + n].copy_from_slice(buf);
         self.pos += n;
+        self.flush_if_needed(writer).await?;
         Ok(n)
     }
 }
@@ -109,15 +217,92 @@ impl<T: AsyncRead + AsyncWrite> AsyncBufStream<T> {
         }
     }
 
+    /// Write out the buffered data, then flush the underlying stream
     #[inline(always)]
     pub async fn flush(&mut self) -> io::Result<()> {
-        self.write_buf.flush(&mut self.inner).await
+        self.write_buf.flush(&mut self.inner).await?;
+        AsyncWrite::flush(&mut self.inner).await
+    }
+
+    /// Refer to [AsyncBufWrite::set_flush_threshold()]
+    #[inline(always)]
+    pub fn set_flush_threshold(&mut self, threshold: usize) {
+        self.write_buf.set_flush_threshold(threshold)
+    }
+
+    /// Refer to [AsyncBufWrite::flush_if_needed()]
+    #[inline(always)]
+    pub async fn flush_if_needed(&mut self) -> io::Result<()> {
+        self.write_buf.flush_if_needed(&mut self.inner).await
+    }
+
+    /// Refer to [AsyncBufRead::fill_buf()]
+    #[inline(always)]
+    pub async fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.read_buf.fill_buf(&mut self.inner).await
+    }
+
+    /// Refer to [AsyncBufRead::read_chunk()]
+    #[inline(always)]
+    pub async fn read_chunk(&mut self) -> io::Result<&[u8]> {
+        self.read_buf.read_chunk(&mut self.inner).await
+    }
+
+    /// Refer to [AsyncBufRead::read_line_chunked()]
+    #[inline(always)]
+    pub async fn read_line_chunked(&mut self, chunk_max: usize) -> io::Result<LineChunk> {
+        self.read_buf.read_line_chunked(&mut self.inner, chunk_max).await
+    }
+
+    /// Refer to [AsyncBufRead::consume()]
+    #[inline(always)]
+    pub fn consume(&mut self, amt: usize) {
+        self.read_buf.consume(amt)
     }
 
     #[inline(always)]
     pub fn get_inner(&mut self) -> &mut T {
         &mut self.inner
     }
+
+    #[inline(always)]
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Replace the underlying stream with `new`, and return the old one, e.g. for reconnection.
+    ///
+    /// The buffers are reused without reallocation:
+    ///
+    /// - Pending writes in the buffer are kept, and will be written to the new stream. The caller
+    ///   should [flush](Self::flush) before, if the data belongs to the old stream.
+    /// - Unread data in the read buffer is discarded, because it comes from the old stream.
+    #[inline]
+    pub fn replace_inner(&mut self, new: T) -> T {
+        self.read_buf.discard();
+        std::mem::replace(&mut self.inner, new)
+    }
+
+    /// Consume the wrapper and return the underlying stream.
+    ///
+    /// # NOTE:
+    ///
+    /// Nothing is flushed, pending writes in the buffer are lost unless [flush](Self::flush) is called
+    /// before, or use [into_inner_flushed](Self::into_inner_flushed) instead.
+    /// Unread data in the read buffer is discarded.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Flush the pending writes, then return the underlying stream.
+    ///
+    /// Unread data in the read buffer is discarded.
+    #[inline]
+    pub async fn into_inner_flushed(mut self) -> io::Result<T> {
+        self.flush().await?;
+        Ok(self.inner)
+    }
 }
 
 impl<T: AsyncRead + AsyncWrite + fmt::Debug> fmt::Debug for AsyncBufStream<T> {
@@ -150,4 +335,32 @@ impl<T: AsyncRead + AsyncWrite> AsyncWrite for AsyncBufStream<T> {
     fn write(&mut self, buf: &[u8]) -> impl Future<Output = io::Result<usize>> + Send {
         async move { self.write_buf.write_buffered(&mut self.inner, buf).await }
     }
+
+    #[inline(always)]
+    fn flush(&mut self) -> impl Future<Output = io::Result<()>> + Send {
+        AsyncBufStream::flush(self)
+    }
+
+    /// Write out the buffered data, then shutdown the underlying stream
+    #[inline(always)]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.write_buf.flush(&mut self.inner).await?;
+        self.inner.shutdown().await
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + AsyncSeek> AsyncSeek for AsyncBufStream<T> {
+    /// Flush the pending writes, and discard the read buffer before seeking the underlying stream.
+    ///
+    /// `SeekFrom::Current` is relative to the position of the data consumed, same as std
+    /// `BufReader`.
+    async fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.flush().await?;
+        let unread = self.read_buf.discard() as i64;
+        let pos = match pos {
+            io::SeekFrom::Current(n) => io::SeekFrom::Current(n - unread),
+            pos => pos,
+        };
+        self.inner.seek(pos).await
+    }
 }
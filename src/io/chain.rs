@@ -0,0 +1,69 @@
+use super::AsyncRead;
+use std::fmt;
+use std::io;
+
+/// Concatenate two readers, read from `a` until EOF, then from `b`.
+///
+/// # Example
+///
+/// ```no_run
+/// use orb::io::{AsyncRead, AsyncWrite, chain, copy};
+///
+/// async fn send<H, B, W>(header: H, body: B, w: &mut W) -> std::io::Result<u64>
+/// where
+///     H: AsyncRead,
+///     B: AsyncRead,
+///     W: AsyncWrite,
+/// {
+///     copy(&mut chain(header, body), w).await
+/// }
+/// ```
+#[inline]
+pub fn chain<A: AsyncRead, B: AsyncRead>(a: A, b: B) -> Chain<A, B> {
+    Chain { a, b, a_done: false }
+}
+
+/// The reader returned by [chain()]
+pub struct Chain<A, B> {
+    a: A,
+    b: B,
+    // `a` reached EOF, reading from `b`
+    a_done: bool,
+}
+
+impl<A, B> Chain<A, B> {
+    #[inline]
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.a, &self.b)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.a, &mut self.b)
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A: AsyncRead, B: AsyncRead> AsyncRead for Chain<A, B> {
+    /// Returns 0 only when both readers reach EOF
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.a_done {
+            let n = self.a.read(buf).await?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            self.a_done = true;
+        }
+        self.b.read(buf).await
+    }
+}
+
+impl<A, B> fmt::Debug for Chain<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Chain(a_done={})", self.a_done)
+    }
+}
@@ -0,0 +1,155 @@
+use super::{AsyncRead, AsyncWrite};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+type IoFuture = Pin<Box<dyn Future<Output = io::Result<usize>> + Send>>;
+
+struct SendPtr<T: ?Sized>(*mut T);
+
+unsafe impl<T: ?Sized> Send for SendPtr<T> {}
+
+/// An adapter which implements [futures_io::AsyncRead] and [futures_io::AsyncWrite] for orb
+/// streams, to work with the ecosystem crates (futures-rustls, async-compression etc).
+///
+/// The `async fn` of the inner stream is stored as an in-flight future for each direction, and
+/// driven on each poll. The read and write go on concurrently through `&S`, so it works
+/// full-duplex (e.g. split into halves with `futures::io::split()`). The streams in orb
+/// (TcpStream, UnixStream, UnifyStream) support the I/O by reference.
+///
+/// # NOTE:
+///
+/// Similar to other poll-based adapters, when `poll_write()` returns Pending, the caller should
+/// retry with the same data.
+pub struct FuturesCompat<S: Sync + 'static> {
+    // The futures borrow the fields below, must be dropped first
+    read_fut: Option<IoFuture>,
+    write_fut: Option<IoFuture>,
+    inner: Box<S>,
+    read_buf: Vec<u8>,
+    read_pos: usize,
+    read_cap: usize,
+    write_buf: Vec<u8>,
+}
+
+impl<S: Sync + 'static> FuturesCompat<S>
+where
+    for<'a> &'a S: AsyncRead + AsyncWrite,
+{
+    #[inline]
+    pub fn new(inner: S) -> Self {
+        Self {
+            read_fut: None,
+            write_fut: None,
+            inner: Box::new(inner),
+            read_buf: Vec::new(),
+            read_pos: 0,
+            read_cap: 0,
+            write_buf: Vec::new(),
+        }
+    }
+
+    /// Cancel the in-flight operations, and return the inner stream.
+    ///
+    /// NOTE: Unread data in the buffer is discarded.
+    #[inline]
+    pub fn into_inner(mut self) -> S {
+        self.read_fut.take();
+        self.write_fut.take();
+        *self.inner
+    }
+
+    fn start_read(&mut self, len: usize) {
+        if self.read_buf.len() < len {
+            self.read_buf.resize(len, 0);
+        }
+        let stream: &S = &self.inner;
+        let stream = SendPtr(stream as *const S as *mut S);
+        let buf = SendPtr(&mut self.read_buf[..len] as *mut [u8]);
+        // SAFETY: the inner and buffer have stable address, and outlive the future. The write
+        // future only shares the inner, and has its own buffer.
+        let fut = async move {
+            let (stream, buf) = (stream, buf);
+            let mut stream: &S = unsafe { &*stream.0 };
+            stream.read(unsafe { &mut *buf.0 }).await
+        };
+        self.read_fut = Some(Box::pin(fut));
+    }
+
+    fn start_write(&mut self, data: &[u8]) {
+        self.write_buf.clear();
+        self.write_buf.extend_from_slice(data);
+        let stream: &S = &self.inner;
+        let stream = SendPtr(stream as *const S as *mut S);
+        let buf = SendPtr(&self.write_buf[..] as *const [u8] as *mut [u8]);
+        // SAFETY: the inner and buffer have stable address, and outlive the future. The read
+        // future only shares the inner, and has its own buffer.
+        let fut = async move {
+            let (stream, buf) = (stream, buf);
+            let mut stream: &S = unsafe { &*stream.0 };
+            stream.write(unsafe { &*buf.0 }).await
+        };
+        self.write_fut = Some(Box::pin(fut));
+    }
+}
+
+#[inline]
+fn poll_fut(fut: &mut Option<IoFuture>, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+    let r = ready!(fut.as_mut().expect("no in-flight future").as_mut().poll(cx));
+    *fut = None;
+    Poll::Ready(r)
+}
+
+impl<S: Sync + 'static> futures_io::AsyncRead for FuturesCompat<S>
+where
+    for<'a> &'a S: AsyncRead + AsyncWrite,
+{
+    fn poll_read(
+        self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let _self = self.get_mut();
+        if _self.read_pos >= _self.read_cap {
+            if _self.read_fut.is_none() {
+                if buf.is_empty() {
+                    return Poll::Ready(Ok(0));
+                }
+                _self.start_read(buf.len());
+            }
+            _self.read_cap = ready!(poll_fut(&mut _self.read_fut, cx))?;
+            _self.read_pos = 0;
+        }
+        let n = std::cmp::min(buf.len(), _self.read_cap - _self.read_pos);
+        buf[..n].copy_from_slice(&_self.read_buf[_self.read_pos.._self.read_pos + n]);
+        _self.read_pos += n;
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl<S: Sync + 'static> futures_io::AsyncWrite for FuturesCompat<S>
+where
+    for<'a> &'a S: AsyncRead + AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let _self = self.get_mut();
+        if _self.write_fut.is_none() {
+            _self.start_write(buf);
+        }
+        poll_fut(&mut _self.write_fut, cx)
+    }
+
+    /// There's no buffer inside, the data is written through on each `poll_write()`.
+    ///
+    /// A pending write is not accepted (by the contract of Pending), so it is cancelled.
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().write_fut = None;
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
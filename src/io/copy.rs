@@ -0,0 +1,58 @@
+use super::{AsyncRead, AsyncWrite};
+use futures_lite::future::try_zip;
+use std::io;
+
+const COPY_BUF_SIZE: usize = 16 * 1024;
+
+/// Copy all the data from `reader` to `writer` until EOF, then flush the writer.
+///
+/// Returns the bytes copied.
+pub async fn copy<R, W>(reader: &mut R, writer: &mut W) -> io::Result<u64>
+where
+    R: AsyncRead + ?Sized,
+    W: AsyncWrite + ?Sized,
+{
+    let mut buf = vec![0u8; COPY_BUF_SIZE];
+    let mut total = 0u64;
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..n]).await?;
+        total += n as u64;
+    }
+    writer.flush().await?;
+    Ok(total)
+}
+
+/// Copy data between `a` and `b` in both directions, until both reach EOF.
+///
+/// When one direction reaches EOF, the write side of the opposite stream is
+/// [shutdown](AsyncWrite::shutdown), so the half-close passes through, while the other direction
+/// goes on.
+///
+/// Returns the bytes copied from `a` to `b`, and from `b` to `a`.
+///
+/// The streams are read and written through shared references (like `impl Read for &TcpStream`
+/// in std), so each direction is driven on its own with its own buffer, and a write blocked in
+/// one direction never stops the reading of the other. Otherwise, when both peers write more than
+/// the socket buffers before reading, the proxy would deadlock.
+pub async fn copy_bidirectional<A, B>(a: &A, b: &B) -> io::Result<(u64, u64)>
+where
+    A: Sync,
+    B: Sync,
+    for<'a> &'a A: AsyncRead + AsyncWrite,
+    for<'b> &'b B: AsyncRead + AsyncWrite,
+{
+    try_zip(copy_half(a, b), copy_half(b, a)).await
+}
+
+/// Copy one direction, then pass the EOF through
+async fn copy_half<R: AsyncRead, W: AsyncWrite>(mut reader: R, mut writer: W) -> io::Result<u64> {
+    let n = copy(&mut reader, &mut writer).await?;
+    writer.shutdown().await?;
+    Ok(n)
+}
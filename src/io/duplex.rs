@@ -0,0 +1,152 @@
+use super::{AsyncRead, AsyncWrite};
+use crate::net::{StreamStats, StreamStatsSnapshot};
+use futures_lite::future::poll_fn;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+/// Create a pair of connected in-memory streams, without fd, for testing.
+///
+/// Each direction buffers at most `capacity` bytes, the write waits when it's full.
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0.
+pub fn duplex(capacity: usize) -> (DuplexStream, DuplexStream) {
+    assert!(capacity > 0, "capacity must be positive");
+    let a = Arc::new(Mutex::new(Pipe::new(capacity)));
+    let b = Arc::new(Mutex::new(Pipe::new(capacity)));
+    (DuplexStream { rx: a.clone(), tx: b.clone() }, DuplexStream { rx: b, tx: a })
+}
+
+struct Pipe {
+    buf: VecDeque<u8>,
+    capacity: usize,
+    // The write side is shutdown or dropped
+    write_closed: bool,
+    // The read side is dropped
+    read_closed: bool,
+    read_waker: Option<Waker>,
+    write_waker: Option<Waker>,
+}
+
+impl Pipe {
+    fn new(capacity: usize) -> Self {
+        Self {
+            buf: VecDeque::with_capacity(capacity),
+            capacity,
+            write_closed: false,
+            read_closed: false,
+            read_waker: None,
+            write_waker: None,
+        }
+    }
+
+    #[inline]
+    fn wake_reader(&mut self) {
+        if let Some(waker) = self.read_waker.take() {
+            waker.wake();
+        }
+    }
+
+    #[inline]
+    fn wake_writer(&mut self) {
+        if let Some(waker) = self.write_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// One end of [duplex()].
+///
+/// Dropping it closes both directions, the peer reads EOF, and writes `BrokenPipe`.
+pub struct DuplexStream {
+    rx: Arc<Mutex<Pipe>>,
+    tx: Arc<Mutex<Pipe>>,
+}
+
+impl AsyncRead for DuplexStream {
+    /// Returns 0 when the peer shutdown or dropped, and the data is drained
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        poll_fn(|cx| {
+            let mut pipe = self.rx.lock().unwrap();
+            if !pipe.buf.is_empty() {
+                let n = std::cmp::min(buf.len(), pipe.buf.len());
+                for (dst, src) in buf.iter_mut().zip(pipe.buf.drain(..n)) {
+                    *dst = src;
+                }
+                pipe.wake_writer();
+                Poll::Ready(Ok(n))
+            } else if pipe.write_closed || buf.is_empty() {
+                Poll::Ready(Ok(0))
+            } else {
+                pipe.read_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl AsyncWrite for DuplexStream {
+    /// Waits when the buffer is full, returns `BrokenPipe` when the peer is dropped
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        poll_fn(|cx| {
+            let mut pipe = self.tx.lock().unwrap();
+            if pipe.read_closed || pipe.write_closed {
+                return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+            }
+            let room = pipe.capacity - pipe.buf.len();
+            if room == 0 && !buf.is_empty() {
+                pipe.write_waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            let n = std::cmp::min(room, buf.len());
+            pipe.buf.extend(&buf[..n]);
+            pipe.wake_reader();
+            Poll::Ready(Ok(n))
+        })
+        .await
+    }
+
+    /// The peer reads EOF after the buffered data
+    async fn shutdown(&mut self) -> io::Result<()> {
+        let mut pipe = self.tx.lock().unwrap();
+        pipe.write_closed = true;
+        pipe.wake_reader();
+        Ok(())
+    }
+}
+
+impl StreamStats for DuplexStream {
+    /// Only the buffer fill is available
+    fn stats(&self) -> io::Result<StreamStatsSnapshot> {
+        Ok(StreamStatsSnapshot {
+            recv_queued: Some(self.rx.lock().unwrap().buf.len()),
+            send_queued: Some(self.tx.lock().unwrap().buf.len()),
+            ..Default::default()
+        })
+    }
+}
+
+impl Drop for DuplexStream {
+    fn drop(&mut self) {
+        {
+            let mut pipe = self.tx.lock().unwrap();
+            pipe.write_closed = true;
+            pipe.wake_reader();
+        }
+        let mut pipe = self.rx.lock().unwrap();
+        pipe.read_closed = true;
+        pipe.buf.clear();
+        pipe.wake_writer();
+    }
+}
+
+impl fmt::Debug for DuplexStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DuplexStream")
+    }
+}
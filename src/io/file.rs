@@ -0,0 +1,151 @@
+use super::{AsyncRead, AsyncReadAt, AsyncSeek, AsyncWrite, AsyncWriteAt};
+use crate::runtime::AsyncExec;
+use std::fmt;
+use std::fs::{File as StdFile, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::marker::PhantomData;
+use std::os::fd::{AsRawFd, RawFd};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Regular file for async code.
+///
+/// Regular files are always "ready" to epoll (and not supported by it), wrapping them with
+/// [AsyncIO::to_async_fd_rw()](crate::io::AsyncIO::to_async_fd_rw) will block the worker on
+/// disk IO. So open / read / write of the file run in `IO::spawn_blocking()`, the data is copied
+/// between the caller's buffer and the blocking thread. While seeking runs inline, because it only
+/// changes the offset without waiting for the disk.
+///
+/// # NOTE:
+///
+/// The blocking operation is not interrupted when the future is cancelled, the file offset might
+/// be changed by it.
+pub struct File<IO: AsyncExec> {
+    inner: Arc<StdFile>,
+    _phan: PhantomData<fn() -> IO>,
+}
+
+impl<IO: AsyncExec> File<IO> {
+    #[inline]
+    pub fn from_std(file: StdFile) -> Self {
+        Self { inner: Arc::new(file), _phan: Default::default() }
+    }
+
+    /// Open a file in read-only mode
+    pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::open_with(path, OpenOptions::new().read(true).clone()).await
+    }
+
+    /// Open a file in write-only mode, create if not exists, truncate if exists
+    pub async fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::open_with(path, OpenOptions::new().write(true).create(true).truncate(true).clone())
+            .await
+    }
+
+    /// Open a file with the options
+    pub async fn open_with<P: AsRef<Path>>(path: P, options: OpenOptions) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        match IO::spawn_blocking(move || options.open(path)).await {
+            Ok(r) => Ok(Self::from_std(r?)),
+            Err(e) => Err(io::Error::other(e.to_string())),
+        }
+    }
+
+    async fn blocking<R, F>(&self, f: F) -> io::Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&StdFile) -> io::Result<R> + Send + 'static,
+    {
+        let inner = self.inner.clone();
+        match IO::spawn_blocking(move || f(&inner)).await {
+            Ok(r) => r,
+            Err(e) => Err(io::Error::other(e.to_string())),
+        }
+    }
+
+    /// Read from the current offset
+    pub async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len();
+        let data = self
+            .blocking(move |mut file| {
+                let mut data = vec![0u8; len];
+                let n = file.read(&mut data)?;
+                data.truncate(n);
+                Ok(data)
+            })
+            .await?;
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    /// Write at the current offset
+    pub async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let data = buf.to_vec();
+        self.blocking(move |mut file| file.write(&data)).await
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &StdFile {
+        &self.inner
+    }
+}
+
+impl<IO: AsyncExec> AsyncSeek for File<IO> {
+    #[inline]
+    async fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        (&*self.inner).seek(pos)
+    }
+}
+
+impl<IO: AsyncExec> AsyncRead for File<IO> {
+    #[inline(always)]
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        File::read(self, buf).await
+    }
+}
+
+impl<IO: AsyncExec> AsyncWrite for File<IO> {
+    #[inline(always)]
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        File::write(self, buf).await
+    }
+}
+
+/// Runs in `IO::spawn_blocking()` with `FileExt::read_at()`
+impl<IO: AsyncExec> AsyncReadAt for File<IO> {
+    async fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let len = buf.len();
+        let data = self
+            .blocking(move |file| {
+                let mut data = vec![0u8; len];
+                let n = file.read_at(&mut data, offset)?;
+                data.truncate(n);
+                Ok(data)
+            })
+            .await?;
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+}
+
+/// Runs in `IO::spawn_blocking()` with `FileExt::write_at()`
+impl<IO: AsyncExec> AsyncWriteAt for File<IO> {
+    async fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let data = buf.to_vec();
+        self.blocking(move |file| file.write_at(&data, offset)).await
+    }
+}
+
+impl<IO: AsyncExec> AsRawFd for File<IO> {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl<IO: AsyncExec> fmt::Debug for File<IO> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
@@ -0,0 +1,79 @@
+use super::{AsyncRead, AsyncWrite};
+use std::io;
+
+/// The codec of frames with big-endian u32 length prefix: `[len: u32][payload: len bytes]`.
+///
+/// # Example
+///
+/// ```no_run
+/// use orb::io::{AsyncRead, AsyncWrite, LengthDelimited};
+///
+/// async fn call<S: AsyncRead + AsyncWrite>(stream: &mut S) -> std::io::Result<Vec<u8>> {
+///     let codec = LengthDelimited::new(1024 * 1024);
+///     codec.write_frame(stream, b"ping").await?;
+///     codec.read_frame(stream).await
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthDelimited {
+    max_len: u32,
+}
+
+impl LengthDelimited {
+    /// Frames longer than `max_len` are rejected with `InvalidData`, to guard against the
+    /// malicious length from the peer.
+    #[inline]
+    pub fn new(max_len: u32) -> Self {
+        Self { max_len }
+    }
+
+    #[inline]
+    pub fn max_len(&self) -> u32 {
+        self.max_len
+    }
+
+    /// Read a frame, returns the payload.
+    ///
+    /// EOF before or inside the frame returns `UnexpectedEof`.
+    pub async fn read_frame<R: AsyncRead + ?Sized>(&self, reader: &mut R) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.read_frame_into(reader, &mut buf).await?;
+        Ok(buf)
+    }
+
+    /// Read a frame into `buf`, replacing the content, so that the allocation is reused across
+    /// the frames.
+    pub async fn read_frame_into<R: AsyncRead + ?Sized>(
+        &self, reader: &mut R, buf: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        let mut header = [0u8; 4];
+        reader.read_exact(&mut header).await?;
+        let len = u32::from_be_bytes(header);
+        if len > self.max_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("frame length {} exceeds {}", len, self.max_len),
+            ));
+        }
+        buf.clear();
+        buf.resize(len as usize, 0);
+        reader.read_exact(buf).await
+    }
+
+    /// Write a frame, does not flush.
+    ///
+    /// Returns `InvalidInput` if the payload is longer than `max_len`.
+    pub async fn write_frame<W: AsyncWrite + ?Sized>(
+        &self, writer: &mut W, payload: &[u8],
+    ) -> io::Result<()> {
+        if payload.len() > self.max_len as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("frame length {} exceeds {}", payload.len(), self.max_len),
+            ));
+        }
+        let header = (payload.len() as u32).to_be_bytes();
+        writer.write_all(&header).await?;
+        writer.write_all(payload).await
+    }
+}
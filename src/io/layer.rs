@@ -0,0 +1,182 @@
+use super::{AsyncRead, AsyncWrite};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Object safe form of AsyncRead + AsyncWrite, to erase the type between layers
+trait DynStream: Send {
+    fn dyn_read<'a>(&'a mut self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<usize>>;
+
+    fn dyn_read_vectored<'a>(
+        &'a mut self, bufs: &'a mut [io::IoSliceMut<'_>],
+    ) -> BoxFuture<'a, io::Result<usize>>;
+
+    fn dyn_write<'a>(&'a mut self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<usize>>;
+
+    fn dyn_flush(&mut self) -> BoxFuture<'_, io::Result<()>>;
+
+    fn dyn_shutdown(&mut self) -> BoxFuture<'_, io::Result<()>>;
+}
+
+impl<S: AsyncRead + AsyncWrite> DynStream for S {
+    #[inline]
+    fn dyn_read<'a>(&'a mut self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(self.read(buf))
+    }
+
+    #[inline]
+    fn dyn_read_vectored<'a>(
+        &'a mut self, bufs: &'a mut [io::IoSliceMut<'_>],
+    ) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(self.read_vectored(bufs))
+    }
+
+    #[inline]
+    fn dyn_write<'a>(&'a mut self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(self.write(buf))
+    }
+
+    #[inline]
+    fn dyn_flush(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(self.flush())
+    }
+
+    #[inline]
+    fn dyn_shutdown(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(self.shutdown())
+    }
+}
+
+/// A type erased stream, the output of [StreamStack].
+///
+/// Each read / write allocates a boxed future.
+pub struct BoxStream(Box<dyn DynStream>);
+
+impl BoxStream {
+    #[inline]
+    pub fn new<S: AsyncRead + AsyncWrite + 'static>(stream: S) -> Self {
+        Self(Box::new(stream))
+    }
+}
+
+impl AsyncRead for BoxStream {
+    #[inline(always)]
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.dyn_read(buf).await
+    }
+
+    #[inline(always)]
+    async fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.0.dyn_read_vectored(bufs).await
+    }
+}
+
+impl AsyncWrite for BoxStream {
+    #[inline(always)]
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.dyn_write(buf).await
+    }
+
+    #[inline(always)]
+    async fn flush(&mut self) -> io::Result<()> {
+        self.0.dyn_flush().await
+    }
+
+    #[inline(always)]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.0.dyn_shutdown().await
+    }
+}
+
+/// A behavior to stack on a stream (metering, rate limiting, timeout etc), by wrapping it.
+///
+/// # Example
+///
+/// ```rust
+/// use orb::io::{AsyncRead, AsyncWrite, StreamLayer};
+/// use std::io;
+///
+/// struct Logged<S>(S);
+///
+/// impl<S: AsyncRead> AsyncRead for Logged<S> {
+///     async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+///         let r = self.0.read(buf).await;
+///         println!("read {:?}", r);
+///         r
+///     }
+/// }
+///
+/// impl<S: AsyncWrite> AsyncWrite for Logged<S> {
+///     async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+///         self.0.write(buf).await
+///     }
+/// }
+///
+/// struct LogLayer;
+///
+/// impl StreamLayer for LogLayer {
+///     fn wrap<S: AsyncRead + AsyncWrite + 'static>(
+///         &self, inner: S,
+///     ) -> impl AsyncRead + AsyncWrite + 'static {
+///         Logged(inner)
+///     }
+/// }
+/// ```
+pub trait StreamLayer: Send + Sync + 'static {
+    fn wrap<S: AsyncRead + AsyncWrite + 'static>(
+        &self, inner: S,
+    ) -> impl AsyncRead + AsyncWrite + 'static;
+}
+
+type WrapFn = Box<dyn Fn(BoxStream) -> BoxStream + Send + Sync>;
+
+/// A builder to compose [StreamLayer]s into one stream.
+///
+/// The layers are applied in the order added, the first layer is the innermost (closest to the
+/// underlying stream), and the last one is the outermost.
+///
+/// ```rust,ignore
+/// let stack = StreamStack::new().layer(Metered).layer(RateLimited).layer(Timeout);
+/// // Timeout(RateLimited(Metered(tcp)))
+/// let stream: BoxStream = stack.wrap(tcp);
+/// ```
+///
+/// The stack can be shared for the accepted connections, and can be a layer of another stack.
+#[derive(Default)]
+pub struct StreamStack {
+    layers: Vec<WrapFn>,
+}
+
+impl StreamStack {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer outside of the existing ones
+    #[inline]
+    pub fn layer<L: StreamLayer>(mut self, layer: L) -> Self {
+        self.layers.push(Box::new(move |stream| BoxStream::new(layer.wrap(stream))));
+        self
+    }
+
+    /// Apply the layers to `inner`
+    pub fn wrap<S: AsyncRead + AsyncWrite + 'static>(&self, inner: S) -> BoxStream {
+        let mut stream = BoxStream::new(inner);
+        for layer in self.layers.iter() {
+            stream = layer(stream);
+        }
+        stream
+    }
+}
+
+impl StreamLayer for StreamStack {
+    #[inline]
+    fn wrap<S: AsyncRead + AsyncWrite + 'static>(
+        &self, inner: S,
+    ) -> impl AsyncRead + AsyncWrite + 'static {
+        StreamStack::wrap(self, inner)
+    }
+}
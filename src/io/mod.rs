@@ -5,7 +5,8 @@
 //! network services, working with file descriptors, and performing async
 //! read/write operations.
 //!
-//! Further more, we have abstract buffered I/O  with [AsyncBufRead], [AsyncBufWrite], and [AsyncBufStream]
+//! Further more, we have abstract buffered I/O  with [AsyncBufRead], [AsyncBufWrite], and [AsyncBufStream],
+//! read-ahead in the background with [PrefetchReader], and coalesced write-behind with [AutoFlushWriter]
 //!
 //! # Design Notes
 //!
@@ -32,7 +33,31 @@ use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
 
 mod buf_io;
-pub use buf_io::{AsyncBufRead, AsyncBufStream, AsyncBufWrite};
+pub use buf_io::{AsyncBufRead, AsyncBufStream, AsyncBufWrite, LineChunk};
+mod prefetch;
+pub use prefetch::PrefetchReader;
+mod compat;
+pub use compat::FuturesCompat;
+mod file;
+pub use file::File;
+mod auto_flush;
+pub use auto_flush::AutoFlushWriter;
+mod layer;
+pub use layer::{BoxStream, StreamLayer, StreamStack};
+mod read_buf;
+pub use read_buf::ReadBuf;
+mod copy;
+pub use copy::{copy, copy_bidirectional};
+mod chain;
+pub use chain::{Chain, chain};
+mod take;
+pub use take::{Take, take};
+mod frame;
+pub use frame::LengthDelimited;
+mod throttle;
+pub use throttle::Throttle;
+mod duplex;
+pub use duplex::{DuplexStream, duplex};
 
 /// Helper macro to convert timeout errors to IO errors.
 ///
@@ -157,6 +182,56 @@ pub trait AsyncIO {
     fn to_async_fd_rw<T: AsRawFd + AsFd + Send + Sync + 'static>(
         fd: T,
     ) -> io::Result<Self::AsyncFd<T>>;
+
+    /// Wrap a file object as an async handle, subscribing for the `interest` to the runtime
+    /// poller, e.g. write-only for a pipe writer.
+    ///
+    /// The default implementation subscribes with [to_async_fd_rw()](Self::to_async_fd_rw) if
+    /// writable is required, otherwise [to_async_fd_rd()](Self::to_async_fd_rd). The runtime
+    /// adapters override it to subscribe exactly the `interest`.
+    ///
+    /// NOTE: Some runtime (smol) always subscribes for both read and write, ignoring the
+    /// narrowing of `interest`.
+    ///
+    /// # Safety
+    ///
+    /// The file descriptor must be set to non-blocking mode before calling
+    /// this method.
+    #[inline]
+    fn to_async_fd<T: AsRawFd + AsFd + Send + Sync + 'static>(
+        fd: T, interest: Interest,
+    ) -> io::Result<Self::AsyncFd<T>> {
+        if interest.is_writable() { Self::to_async_fd_rw(fd) } else { Self::to_async_fd_rd(fd) }
+    }
+}
+
+/// The readiness events to subscribe for a file descriptor, combined with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interest(u8);
+
+impl Interest {
+    pub const READ: Self = Self(0b01);
+    pub const WRITE: Self = Self(0b10);
+    pub const READ_WRITE: Self = Self(0b11);
+
+    #[inline]
+    pub fn is_readable(self) -> bool {
+        self.0 & Self::READ.0 != 0
+    }
+
+    #[inline]
+    pub fn is_writable(self) -> bool {
+        self.0 & Self::WRITE.0 != 0
+    }
+}
+
+impl std::ops::BitOr for Interest {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
 }
 
 /// Trait for async file descriptor operations.
@@ -201,6 +276,15 @@ pub trait AsyncFd<T: AsRawFd + AsFd + Send + Sync + 'static>:
     fn async_write<R>(
         &self, f: impl FnMut(&T) -> io::Result<R> + Send,
     ) -> impl Future<Output = io::Result<R>> + Send;
+
+    /// Access the underlying fd as a socket, for the options not wrapped by this crate
+    /// (e.g. TCP_USER_TIMEOUT, IP_TOS, SO_MARK).
+    ///
+    /// The closure runs inline, it should not block.
+    #[inline]
+    fn with_socket<R>(&self, f: impl FnOnce(&socket2::SockRef) -> io::Result<R>) -> io::Result<R> {
+        f(&socket2::SockRef::from(&**self))
+    }
 }
 
 impl<F: std::ops::Deref<Target = IO>, IO: AsyncIO> AsyncIO for F {
@@ -229,6 +313,12 @@ impl<F: std::ops::Deref<Target = IO>, IO: AsyncIO> AsyncIO for F {
     ) -> io::Result<Self::AsyncFd<T>> {
         IO::to_async_fd_rw(fd)
     }
+
+    fn to_async_fd<T: AsRawFd + AsFd + Send + Sync + 'static>(
+        fd: T, interest: Interest,
+    ) -> io::Result<Self::AsyncFd<T>> {
+        IO::to_async_fd(fd, interest)
+    }
 }
 
 /// AsyncRead trait for runtime adapter
@@ -238,6 +328,42 @@ pub trait AsyncRead: Send {
     /// On ok, return the bytes read
     fn read(&mut self, buf: &mut [u8]) -> impl Future<Output = io::Result<usize>> + Send;
 
+    /// Like `read`, except that it reads into a slice of buffers.
+    ///
+    /// The default implementation reads into the first non-empty buffer, override this when the
+    /// underlying I/O object supports scatter read in one syscall.
+    ///
+    /// On ok, return the bytes read
+    fn read_vectored<'a>(
+        &'a mut self, bufs: &'a mut [io::IoSliceMut<'_>],
+    ) -> impl Future<Output = io::Result<usize>> + Send + 'a {
+        async move {
+            for buf in bufs.iter_mut() {
+                if !buf.is_empty() {
+                    return self.read(buf).await;
+                }
+            }
+            Ok(0)
+        }
+    }
+
+    /// Read into the unfilled region of `buf`, which might be uninitialized, and advance the
+    /// filled region.
+    ///
+    /// The default implementation zeroes the uninitialized region and calls `read`, override this
+    /// when the underlying I/O object can read into uninitialized memory directly.
+    ///
+    /// On ok, return the bytes read
+    fn read_buf<'a>(
+        &'a mut self, buf: &'a mut ReadBuf<'_>,
+    ) -> impl Future<Output = io::Result<usize>> + Send + 'a {
+        async move {
+            let n = self.read(buf.initialize_unfilled()).await?;
+            buf.advance(n);
+            Ok(n)
+        }
+    }
+
     /// Read the exact number of bytes required to fill `buf`.
     ///
     /// This function repeatedly calls `read` until the buffer is completely filled.
@@ -343,4 +469,104 @@ pub trait AsyncWrite: Send {
             Ok(())
         }
     }
+
+    /// Push out the data buffered inside the writer (and the writers it wraps).
+    ///
+    /// The default is a no-op for the unbuffered ones, the buffered writers like
+    /// [AsyncBufStream] override it.
+    #[inline]
+    fn flush(&mut self) -> impl Future<Output = io::Result<()>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Flush and shutdown the write side, the peer reads EOF after the data written.
+    ///
+    /// The default only flushes, the sockets override it to shutdown the write direction.
+    #[inline]
+    fn shutdown(&mut self) -> impl Future<Output = io::Result<()>> + Send {
+        self.flush()
+    }
+}
+
+/// AsyncReadAt trait for positional read (pread), without changing the current position
+///
+/// The receiver is `&self`, so that multiple reads can run concurrently.
+pub trait AsyncReadAt: Send + Sync {
+    /// Read at `offset`, return the bytes read, 0 means EOF
+    fn read_at(
+        &self, buf: &mut [u8], offset: u64,
+    ) -> impl Future<Output = io::Result<usize>> + Send;
+
+    /// Read exactly `buf.len()` bytes at `offset`, return UnexpectedEof when reaching the end
+    #[inline]
+    fn read_exact_at(
+        &self, mut buf: &mut [u8], mut offset: u64,
+    ) -> impl Future<Output = io::Result<()>> + Send {
+        async move {
+            while !buf.is_empty() {
+                match self.read_at(buf, offset).await {
+                    Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                    Ok(n) => {
+                        buf = &mut buf[n..];
+                        offset += n as u64;
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// AsyncWriteAt trait for positional write (pwrite), without changing the current position
+///
+/// The receiver is `&self`, so that multiple writes can run concurrently.
+pub trait AsyncWriteAt: Send + Sync {
+    /// Write at `offset`, return the bytes written
+    fn write_at(&self, buf: &[u8], offset: u64) -> impl Future<Output = io::Result<usize>> + Send;
+
+    /// Write the whole `buf` at `offset`
+    #[inline]
+    fn write_all_at(
+        &self, mut buf: &[u8], mut offset: u64,
+    ) -> impl Future<Output = io::Result<()>> + Send {
+        async move {
+            while !buf.is_empty() {
+                match self.write_at(buf, offset).await {
+                    Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                    Ok(n) => {
+                        buf = &buf[n..];
+                        offset += n as u64;
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// AsyncSeek trait for seekable I/O objects
+pub trait AsyncSeek: Send {
+    /// Async version of seek function
+    ///
+    /// On ok, return the new position from the start
+    fn seek(&mut self, pos: io::SeekFrom) -> impl Future<Output = io::Result<u64>> + Send;
+
+    /// Return the current position from the start
+    #[inline]
+    fn stream_position(&mut self) -> impl Future<Output = io::Result<u64>> + Send {
+        self.seek(io::SeekFrom::Current(0))
+    }
+
+    /// Seek to the start
+    #[inline]
+    fn rewind(&mut self) -> impl Future<Output = io::Result<()>> + Send {
+        async move {
+            self.seek(io::SeekFrom::Start(0)).await?;
+            Ok(())
+        }
+    }
 }
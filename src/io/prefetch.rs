@@ -0,0 +1,121 @@
+use super::AsyncRead;
+use crate::runtime::AsyncExec;
+use std::io;
+use std::pin::Pin;
+
+type FillResult<R> = (R, Vec<u8>, io::Result<usize>);
+
+type FillHandle<R, RT> = Pin<Box<<RT as AsyncExec>::AsyncHandle<FillResult<R>>>>;
+
+/// A buffered reader which issues the next read in the background, while the consumer is
+/// processing the current buffer.
+///
+/// The reader is moved into a spawned task to fill a spare buffer, and handed back when the task is
+/// done. There's at most one read ahead in flight, so the memory is bounded by 2 * capacity.
+///
+/// Errors and EOF of the prefetch are returned after the buffered data is consumed.
+///
+/// # NOTE:
+///
+/// When dropped with a read in flight, the task is detached, the underlying reader is dropped
+/// after that read completes.
+///
+/// # Type Parameters
+///
+/// * `R` - The underlying reader
+/// * `RT` - The runtime to spawn the prefetch task
+pub struct PrefetchReader<R: AsyncRead + 'static, RT: AsyncExec> {
+    rt: RT,
+    reader: Option<R>,
+    buf: Vec<u8>,
+    spare: Option<Vec<u8>>,
+    pos: usize,
+    cap: usize,
+    eof: bool,
+    inflight: Option<FillHandle<R, RT>>,
+}
+
+impl<R: AsyncRead + 'static, RT: AsyncExec> PrefetchReader<R, RT> {
+    /// Create a prefetch reader with the buffer capacity.
+    #[inline]
+    pub fn new(rt: RT, reader: R, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity {} must > 0", capacity);
+        Self {
+            rt,
+            reader: Some(reader),
+            buf: vec![0; capacity],
+            spare: Some(vec![0; capacity]),
+            pos: 0,
+            cap: 0,
+            eof: false,
+            inflight: None,
+        }
+    }
+
+    /// Whether there's a read ahead in flight
+    #[inline]
+    pub fn is_prefetching(&self) -> bool {
+        self.inflight.is_some()
+    }
+
+    fn prefetch(&mut self) {
+        if self.eof || self.inflight.is_some() {
+            return;
+        }
+        if let (Some(mut reader), Some(mut spare)) = (self.reader.take(), self.spare.take()) {
+            let handle = self.rt.spawn(async move {
+                let r = reader.read(&mut spare).await;
+                (reader, spare, r)
+            });
+            self.inflight = Some(Box::pin(handle));
+        }
+    }
+
+    /// Wait for the in-flight read (or read directly), and swap into the buffer.
+    async fn fill(&mut self) -> io::Result<()> {
+        let r = if let Some(handle) = self.inflight.as_mut() {
+            // poll by reference, so that the read is cancel safe
+            let r = handle.await;
+            self.inflight = None;
+            match r {
+                Ok((reader, buf, r)) => {
+                    self.reader = Some(reader);
+                    self.spare = Some(std::mem::replace(&mut self.buf, buf));
+                    r
+                }
+                Err(_) => {
+                    self.eof = true;
+                    return Err(io::Error::other("prefetch task failed"));
+                }
+            }
+        } else if let Some(reader) = self.reader.as_mut() {
+            reader.read(&mut self.buf).await
+        } else {
+            return Ok(());
+        };
+        self.pos = 0;
+        self.cap = 0;
+        match r {
+            Ok(0) => self.eof = true,
+            Ok(n) => self.cap = n,
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+}
+
+impl<R: AsyncRead + 'static, RT: AsyncExec> AsyncRead for PrefetchReader<R, RT> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.cap {
+            if self.eof && self.inflight.is_none() {
+                return Ok(0);
+            }
+            self.fill().await?;
+        }
+        let n = std::cmp::min(buf.len(), self.cap - self.pos);
+        buf[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        self.prefetch();
+        Ok(n)
+    }
+}
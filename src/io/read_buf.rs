@@ -0,0 +1,156 @@
+use std::fmt;
+use std::mem::MaybeUninit;
+
+/// A buffer which may be partially uninitialized, for [read_buf()](super::AsyncRead::read_buf).
+///
+/// Similar to the one in tokio, the buffer is divided into three regions:
+///
+/// ```text
+/// [ filled | initialized but unfilled | uninitialized ]
+/// ```
+///
+/// The reader appends to the filled region, the uninitialized region is never read, so the
+/// caller can skip zeroing the memory before reading.
+pub struct ReadBuf<'a> {
+    buf: &'a mut [MaybeUninit<u8>],
+    filled: usize,
+    initialized: usize,
+}
+
+impl<'a> ReadBuf<'a> {
+    /// Create from a fully initialized buffer
+    #[inline]
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        let initialized = buf.len();
+        // SAFETY: MaybeUninit<u8> has the same layout as u8, and the bytes are initialized
+        let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+        Self { buf, filled: 0, initialized }
+    }
+
+    /// Create from an uninitialized buffer
+    #[inline]
+    pub fn uninit(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self { buf, filled: 0, initialized: 0 }
+    }
+
+    /// The total size of the buffer
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// The number of bytes can still be filled
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.filled
+    }
+
+    /// The filled region
+    #[inline]
+    pub fn filled(&self) -> &[u8] {
+        // SAFETY: filled <= initialized
+        unsafe { &*(&self.buf[..self.filled] as *const [MaybeUninit<u8>] as *const [u8]) }
+    }
+
+    /// The filled region, mutable
+    #[inline]
+    pub fn filled_mut(&mut self) -> &mut [u8] {
+        // SAFETY: filled <= initialized
+        unsafe { &mut *(&mut self.buf[..self.filled] as *mut [MaybeUninit<u8>] as *mut [u8]) }
+    }
+
+    /// The initialized region, including the filled one
+    #[inline]
+    pub fn initialized(&self) -> &[u8] {
+        // SAFETY: the bytes before initialized are written
+        unsafe { &*(&self.buf[..self.initialized] as *const [MaybeUninit<u8>] as *const [u8]) }
+    }
+
+    /// Zero the uninitialized region, and return the whole unfilled region
+    pub fn initialize_unfilled(&mut self) -> &mut [u8] {
+        for b in &mut self.buf[self.initialized..] {
+            b.write(0);
+        }
+        self.initialized = self.buf.len();
+        // SAFETY: all the bytes are initialized now
+        unsafe { &mut *(&mut self.buf[self.filled..] as *mut [MaybeUninit<u8>] as *mut [u8]) }
+    }
+
+    /// The unfilled region, which might be uninitialized.
+    ///
+    /// # Safety
+    ///
+    /// The caller must not de-initialize the bytes already initialized.
+    #[inline]
+    pub unsafe fn unfilled_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        &mut self.buf[self.filled..]
+    }
+
+    /// Mark `n` bytes after the filled region as initialized.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have written the `n` bytes after the filled region.
+    #[inline]
+    pub unsafe fn assume_init(&mut self, n: usize) {
+        let end = self.filled + n;
+        if end > self.initialized {
+            self.initialized = end;
+        }
+    }
+
+    /// Append `n` bytes to the filled region.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes are not initialized.
+    #[inline]
+    pub fn advance(&mut self, n: usize) {
+        let end = self.filled.checked_add(n).expect("filled overflow");
+        self.set_filled(end);
+    }
+
+    /// Set the size of the filled region, might shrink it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is beyond the initialized region.
+    #[inline]
+    pub fn set_filled(&mut self, n: usize) {
+        assert!(n <= self.initialized, "filled must not become larger than initialized");
+        self.filled = n;
+    }
+
+    /// Clear the filled region, the initialized bytes are kept.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.filled = 0;
+    }
+
+    /// Append the data to the filled region.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's not enough room.
+    pub fn put_slice(&mut self, src: &[u8]) {
+        assert!(src.len() <= self.remaining(), "src.len() must fit in remaining()");
+        let end = self.filled + src.len();
+        for (dst, b) in self.buf[self.filled..end].iter_mut().zip(src) {
+            dst.write(*b);
+        }
+        if end > self.initialized {
+            self.initialized = end;
+        }
+        self.filled = end;
+    }
+}
+
+impl fmt::Debug for ReadBuf<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadBuf")
+            .field("filled", &self.filled)
+            .field("initialized", &self.initialized)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
@@ -0,0 +1,64 @@
+use super::AsyncRead;
+use std::fmt;
+use std::io;
+
+/// Limit the reader to `limit` bytes, returns EOF after that even if more data is available.
+///
+/// The standard guard when reading a body of the length from the peer.
+#[inline]
+pub fn take<R: AsyncRead>(reader: R, limit: u64) -> Take<R> {
+    Take { inner: reader, limit }
+}
+
+/// The reader returned by [take()]
+pub struct Take<R> {
+    inner: R,
+    limit: u64,
+}
+
+impl<R> Take<R> {
+    /// The number of bytes can still be read
+    #[inline]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Reset the number of bytes can be read, the bytes read before are not counted
+    #[inline]
+    pub fn set_limit(&mut self, limit: u64) {
+        self.limit = limit;
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for Take<R> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.limit == 0 {
+            return Ok(0);
+        }
+        let max = std::cmp::min(buf.len() as u64, self.limit) as usize;
+        let n = self.inner.read(&mut buf[..max]).await?;
+        self.limit -= n as u64;
+        Ok(n)
+    }
+}
+
+impl<R> fmt::Debug for Take<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Take(limit={})", self.limit)
+    }
+}
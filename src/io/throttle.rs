@@ -0,0 +1,132 @@
+use super::{AsyncRead, AsyncWrite};
+use crate::time::AsyncTime;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+/// Limit the throughput of a stream to `bytes_per_sec`, for each direction separately.
+///
+/// It's a token bucket holding at most 1/20 second of the bytes, so the burst is small. When the
+/// tokens are not enough for the chunk, sleeps with `IO::sleep()` until they are.
+pub struct Throttle<S, IO> {
+    inner: S,
+    read: Bucket,
+    write: Bucket,
+    _phan: PhantomData<fn() -> IO>,
+}
+
+struct Bucket {
+    rate: u64,
+    capacity: u64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    fn new<IO: AsyncTime>(rate: u64) -> Self {
+        assert!(rate > 0, "bytes_per_sec must be positive");
+        let capacity = (rate / 20).max(1);
+        Self { rate, capacity, tokens: capacity as f64, last: IO::now() }
+    }
+
+    fn refill<IO: AsyncTime>(&mut self) {
+        let now = IO::now();
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.capacity as f64);
+        self.last = now;
+    }
+
+    /// Wait for the tokens of a chunk up to `len`, returns the size granted
+    async fn acquire<IO: AsyncTime>(&mut self, len: usize) -> usize {
+        let want = std::cmp::min(len as u64, self.capacity);
+        loop {
+            self.refill::<IO>();
+            if self.tokens >= want as f64 {
+                return want as usize;
+            }
+            let lack = want as f64 - self.tokens;
+            IO::sleep(Duration::from_secs_f64(lack / self.rate as f64)).await;
+        }
+    }
+
+    #[inline]
+    fn consume(&mut self, n: usize) {
+        self.tokens -= n as f64;
+    }
+}
+
+impl<S, IO: AsyncTime> Throttle<S, IO> {
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_sec` is 0.
+    #[inline]
+    pub fn new(inner: S, bytes_per_sec: u64) -> Self {
+        Self {
+            inner,
+            read: Bucket::new::<IO>(bytes_per_sec),
+            write: Bucket::new::<IO>(bytes_per_sec),
+            _phan: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.read.rate
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: AsyncRead, IO: AsyncTime> AsyncRead for Throttle<S, IO> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.read(buf).await;
+        }
+        let n = self.read.acquire::<IO>(buf.len()).await;
+        let n = self.inner.read(&mut buf[..n]).await?;
+        self.read.consume(n);
+        Ok(n)
+    }
+}
+
+impl<S: AsyncWrite, IO: AsyncTime> AsyncWrite for Throttle<S, IO> {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.write(buf).await;
+        }
+        let n = self.write.acquire::<IO>(buf.len()).await;
+        let n = self.inner.write(&buf[..n]).await?;
+        self.write.consume(n);
+        Ok(n)
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush().await
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.inner.shutdown().await
+    }
+}
+
+impl<S: fmt::Debug, IO> fmt::Debug for Throttle<S, IO> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Throttle({:?}, {}/s)", self.inner, self.read.rate)
+    }
+}
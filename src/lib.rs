@@ -60,7 +60,7 @@ pub mod prelude {
     pub use crate::io::{AsyncBufRead, AsyncBufWrite, AsyncFd, AsyncIO, AsyncRead, AsyncWrite};
     pub use crate::net::AsyncListener;
     pub use crate::runtime::{AsyncExec, AsyncHandle, ThreadHandle};
-    pub use crate::time::{AsyncTime, TimeInterval};
+    pub use crate::time::{AsyncTime, TimeDelay, TimeInterval};
     // Re-export the Stream trait so users can import it
     pub use futures_lite::stream::Stream;
     pub use futures_lite::stream::StreamExt;
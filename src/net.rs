@@ -6,23 +6,210 @@
 //! - [UnifyAddr] type for smart address parsing, and trait [ResolveAddr] which provides async
 //! fn resolve(), to replace std [ToSocketAddrs](https://doc.rust-lang.org/std/net/trait.ToSocketAddrs.html),
 //! - [UnifyStream] + [UnixListener] to provide consistent interface for both tcp + unix socket types.
+//! - [pool::Pool] to reuse the connections to the addresses.
+//! - [rpc::Client] to multiplex the request / response calls over one connection.
+//! - [tls::TlsUpgrade] as the hook for TLS implementations.
 
-use crate::io::{AsyncFd, AsyncIO, AsyncRead, AsyncWrite, io_with_timeout};
-use crate::runtime::AsyncExec;
+use crate::io::{AsyncFd, AsyncIO, AsyncRead, AsyncWrite, ReadBuf, io_with_timeout};
+use crate::runtime::{AsyncExec, AsyncHandle};
 use crate::time::AsyncTime;
+use crate::utils::sync::{WaitList, poll_once};
+use crate::utils::{Cancellable, ShutdownToken};
+use futures_lite::future::poll_fn;
+use futures_lite::stream::Stream;
+use std::collections::HashMap;
 use std::fmt;
+use std::future::Future;
 use std::io;
 use std::net::{
     AddrParseError, IpAddr, SocketAddr, SocketAddrV4, SocketAddrV6, TcpListener as StdTcpListener,
     TcpStream as StdTcpStream, ToSocketAddrs,
 };
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
 use std::time::Duration;
 
-use std::os::fd::{AsRawFd, FromRawFd, RawFd};
-use std::os::unix::net::{UnixListener as StdUnixListener, UnixStream as StdUnixStream};
+use socket2::{Domain, SockAddr, Socket, Type};
+use std::os::fd::{AsFd, AsRawFd, FromRawFd, RawFd};
+use std::os::unix::net::{
+    SocketAddr as UnixSocketAddr, UnixListener as StdUnixListener, UnixStream as StdUnixStream,
+};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+pub mod pool;
+pub mod rpc;
+pub mod tls;
+
+mod connect;
+pub use connect::{BackoffPolicy, ConnectOptions, connect_retry};
+mod idle;
+pub use idle::IdleTimeout;
+mod keepalive;
+pub use keepalive::AppKeepalive;
+
+/// The default listen backlog of `bind()`
+pub const DEFAULT_BACKLOG: i32 = 1024;
+
+/// Resolve the addr for tcp, unix path is not allowed.
+async fn resolve_tcp_addr<E: AsyncExec, A: ResolveAddr + ?Sized>(
+    addr: &A,
+) -> io::Result<SocketAddr> {
+    // generic params are Sized by default, while str is ?Sized
+    match addr.resolve::<E>().await {
+        Ok(UnifyAddr::Socket(_addr)) => Ok(_addr),
+        Ok(_) => Err(AddrError::WrongScheme(format!("{:?}", addr)).into()),
+        Err(e) => Err(resolve_error(addr, e)),
+    }
+}
+
+/// Convert the [ResolveError] of `addr` to io::Error, with [AddrError] as the source
+pub(crate) fn resolve_error<A: fmt::Debug + ?Sized>(addr: &A, e: ResolveError) -> io::Error {
+    match e {
+        ResolveError::Parse(_) => AddrError::InvalidAddr(format!("{:?}", addr)).into(),
+        e => AddrError::ResolveFailed(format!("{:?}", addr), Arc::new(e)).into(),
+    }
+}
+
+/// Resolve all the addresses of "host:port" for tcp.
+async fn resolve_tcp_all<E: AsyncExec>(addr: &str) -> io::Result<Vec<SocketAddr>> {
+    UnifyAddr::resolve_all::<E>(addr)
+        .await?
+        .into_iter()
+        .map(|a| match a {
+            UnifyAddr::Socket(a) => Ok(a),
+            _ => Err(AddrError::WrongScheme(format!("{:?}", addr)).into()),
+        })
+        .collect()
+}
+
+/// Reorder the addresses to alternate between the families, starting with the family of the
+/// first one, as RFC 8305 section 4.
+fn interleave_family(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let Some(first_v6) = addrs.first().map(|a| a.is_ipv6()) else { return addrs };
+    let len = addrs.len();
+    let (primary, secondary): (Vec<SocketAddr>, Vec<SocketAddr>) =
+        addrs.into_iter().partition(|a| a.is_ipv6() == first_v6);
+    let mut out = Vec::with_capacity(len);
+    let (mut p, mut s) = (primary.into_iter(), secondary.into_iter());
+    loop {
+        match (p.next(), s.next()) {
+            (None, None) => return out,
+            (a, b) => out.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+/// Race the attempts of `connect` to `addrs`, staggered by `delay`, refer to
+/// [TcpStream::connect_any()].
+async fn race_connect<IO, F, Fut>(
+    addrs: &[SocketAddr], delay: Duration, connect: F,
+) -> io::Result<TcpStream<IO>>
+where
+    IO: AsyncIO + AsyncTime,
+    F: Fn(SocketAddr) -> Fut,
+    Fut: Future<Output = io::Result<TcpStream<IO>>>,
+{
+    if addrs.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "no address to connect"));
+    }
+    let mut attempts = Vec::new();
+    let mut stagger = None;
+    let mut next = 0;
+    let mut last_err = None;
+    poll_fn(|cx| {
+        loop {
+            if next < addrs.len() && (attempts.is_empty() || stagger.is_none()) {
+                attempts.push(Box::pin(connect(addrs[next])));
+                next += 1;
+                stagger = Some(Box::pin(IO::sleep(delay)));
+            }
+            let mut i = 0;
+            while i < attempts.len() {
+                match attempts[i].as_mut().poll(cx) {
+                    Poll::Ready(Ok(stream)) => return Poll::Ready(Ok(stream)),
+                    Poll::Ready(Err(e)) => {
+                        drop(attempts.remove(i));
+                        last_err = Some(e);
+                    }
+                    Poll::Pending => i += 1,
+                }
+            }
+            if attempts.is_empty() {
+                if next < addrs.len() {
+                    // Start the next at once
+                    continue;
+                }
+                return Poll::Ready(Err(last_err.take().unwrap()));
+            }
+            if next < addrs.len()
+                && let Some(sleep) = stagger.as_mut()
+                && sleep.as_mut().poll(cx).is_ready()
+            {
+                stagger = None;
+                continue;
+            }
+            return Poll::Pending;
+        }
+    })
+    .await
+}
+
+/// Create a tcp socket, with IPPROTO_MPTCP if `multipath` (falling back to plain TCP if not
+/// supported by the kernel).
+fn tcp_socket(addr: &SocketAddr, multipath: bool) -> io::Result<Socket> {
+    let domain = Domain::for_address(*addr);
+    #[cfg(target_os = "linux")]
+    if multipath {
+        match Socket::new(domain, Type::STREAM, Some(socket2::Protocol::MPTCP)) {
+            Ok(socket) => return Ok(socket),
+            Err(e)
+                if matches!(
+                    e.raw_os_error(),
+                    Some(libc::EPROTONOSUPPORT | libc::ENOPROTOOPT | libc::EINVAL)
+                ) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = multipath;
+    Socket::new(domain, Type::STREAM, None)
+}
+
+/// Create a listening tcp socket, `f` is for setting options before bind.
+fn tcp_listen(
+    addr: &SocketAddr, backlog: i32, multipath: bool, f: impl FnOnce(&Socket) -> io::Result<()>,
+) -> io::Result<StdTcpListener> {
+    let socket = tcp_socket(addr, multipath)?;
+    // Aligned with std TcpListener::bind()
+    socket.set_reuse_address(true)?;
+    f(&socket)?;
+    socket.bind(&(*addr).into())?;
+    socket.listen(backlog)?;
+    Ok(socket.into())
+}
+
+/// Create a listening unix socket
+fn unix_listen(path: &Path, backlog: i32, mode: Option<u32>) -> io::Result<StdUnixListener> {
+    let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+    if let Some(mode) = mode {
+        // Linux creates the socket file with the mode of the socket (and umask), no window for
+        // others to connect before chmod.
+        #[cfg(target_os = "linux")]
+        if unsafe { libc::fchmod(socket.as_raw_fd(), mode as libc::mode_t) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        socket.bind(&SockAddr::unix(path)?)?;
+        // Set the exact mode regardless of umask
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    } else {
+        socket.bind(&SockAddr::unix(path)?)?;
+    }
+    socket.listen(backlog)?;
+    Ok(std::os::fd::OwnedFd::from(socket).into())
+}
+
 /// A TCP socket listener that implements AsyncListener.
 pub struct TcpListener<IO: AsyncIO> {
     inner: IO::AsyncFd<StdTcpListener>,
@@ -51,36 +238,101 @@ impl<IO: AsyncIO> TcpListener<IO> {
         Ok(TcpListener { inner })
     }
 
-    /// Bind a TcpListener to the specified address.
+    /// Bind a TcpListener to the specified address, with [DEFAULT_BACKLOG].
+    #[inline]
     pub async fn bind<A: ResolveAddr + ?Sized>(addr: &A) -> io::Result<Self>
     where
         IO: AsyncExec,
     {
-        // generic params are Sized by default, while str is ?Sized
-        match addr.resolve::<IO>().await {
-            Ok(UnifyAddr::Socket(_addr)) => {
-                let listener = StdTcpListener::bind(&_addr)?;
-                Self::from_std(listener)
-            }
-            Ok(UnifyAddr::Path(_)) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("addr {:?} invalid", addr),
-                ));
-            }
-            Err(e) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("addr {:?} invalid: {:?}", addr, e),
-                ));
-            }
+        Self::bind_with_backlog(addr, DEFAULT_BACKLOG).await
+    }
+
+    /// Bind a TcpListener to the specified address, with the specified listen backlog.
+    pub async fn bind_with_backlog<A: ResolveAddr + ?Sized>(
+        addr: &A, backlog: i32,
+    ) -> io::Result<Self>
+    where
+        IO: AsyncExec,
+    {
+        let _addr = resolve_tcp_addr::<IO, A>(addr).await?;
+        let listener = tcp_listen(&_addr, backlog, false, |_| Ok(()))?;
+        Self::from_std(listener)
+    }
+
+    /// Bind a TcpListener with SO_REUSEADDR / SO_REUSEPORT options, with [DEFAULT_BACKLOG].
+    ///
+    /// `reuse_port` allows multiple listeners (even across processes) bind to the same port, for
+    /// zero-downtime restart and multi-process accept sharing.
+    pub async fn bind_reuse<A: ResolveAddr + ?Sized>(
+        addr: &A, reuse_addr: bool, reuse_port: bool,
+    ) -> io::Result<Self>
+    where
+        IO: AsyncExec,
+    {
+        let _addr = resolve_tcp_addr::<IO, A>(addr).await?;
+        let listener = tcp_listen(&_addr, DEFAULT_BACKLOG, false, |socket| {
+            socket.set_reuse_address(reuse_addr)?;
+            socket.set_reuse_port(reuse_port)
+        })?;
+        Self::from_std(listener)
+    }
+
+    /// Bind a TcpListener to an IPv6 address with IPV6_V6ONLY, with [DEFAULT_BACKLOG].
+    ///
+    /// With `v6_only`, the listener on `[::]` only accepts IPv6, leaving the v4 port to others.
+    /// Otherwise it's dual-stack, accepting IPv4 as the v4-mapped addresses, regardless of the
+    /// system default. Returns `InvalidInput` for an IPv4 address.
+    pub async fn bind_v6_only<A: ResolveAddr + ?Sized>(addr: &A, v6_only: bool) -> io::Result<Self>
+    where
+        IO: AsyncExec,
+    {
+        let _addr = resolve_tcp_addr::<IO, A>(addr).await?;
+        if !_addr.is_ipv6() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not an IPv6 address", _addr),
+            ));
         }
+        let listener =
+            tcp_listen(&_addr, DEFAULT_BACKLOG, false, |socket| socket.set_only_v6(v6_only))?;
+        Self::from_std(listener)
+    }
+
+    /// Bind a TcpListener with Multipath TCP (IPPROTO_MPTCP), with [DEFAULT_BACKLOG].
+    ///
+    /// Falls back to plain TCP if MPTCP is not supported (non-Linux, or disabled in the kernel).
+    /// The plain TCP clients are accepted as well.
+    pub async fn bind_mptcp<A: ResolveAddr + ?Sized>(addr: &A) -> io::Result<Self>
+    where
+        IO: AsyncExec,
+    {
+        let _addr = resolve_tcp_addr::<IO, A>(addr).await?;
+        let listener = tcp_listen(&_addr, DEFAULT_BACKLOG, true, |_| Ok(()))?;
+        Self::from_std(listener)
     }
 
     /// Accept a new connection.
+    #[inline]
     pub async fn accept(&mut self) -> io::Result<TcpStream<IO>> {
+        let (stream, _) = self.accept_with_addr().await?;
+        Ok(stream)
+    }
+
+    /// Accept a new connection, fails with `TimedOut` if none arrives within `timeout`.
+    ///
+    /// `timeout` of 0 means no timeout.
+    #[inline]
+    pub async fn accept_timeout(&mut self, timeout: Duration) -> io::Result<TcpStream<IO>>
+    where
+        IO: AsyncTime,
+    {
+        io_with_timeout!(IO, timeout, self.accept())
+    }
+
+    /// Accept a new connection, along with the peer address.
+    pub async fn accept_with_addr(&mut self) -> io::Result<(TcpStream<IO>, SocketAddr)> {
         match self.inner.async_read(|listener| listener.accept()).await {
-            Ok((stream, _)) => {
+            Ok((stream, addr)) => {
                 stream.set_nonblocking(true).map_err(|e| {
                     io::Error::new(
                         io::ErrorKind::Other,
@@ -88,7 +340,7 @@ impl<IO: AsyncIO> TcpListener<IO> {
                     )
                 })?;
                 let inner = IO::to_async_fd_rw(stream)?;
-                Ok(TcpStream { inner })
+                Ok((TcpStream { inner }, addr))
             }
             Err(e) => Err(e),
         }
@@ -100,6 +352,12 @@ impl<IO: AsyncIO> TcpListener<IO> {
         Ok(addr.to_string())
     }
 
+    /// Get the local address of the listener, for example to know the port after binding to 0.
+    #[inline]
+    pub fn local_socket_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
     /// Try to recover a listener from RawFd.
     ///
     /// Will set listener to non_blocking to validate the fd.
@@ -126,16 +384,60 @@ impl<IO: AsyncIO> UnixListener<IO> {
         Ok(UnixListener { inner })
     }
 
-    /// Bind a UnixListener to the specified path.
+    /// Bind a UnixListener to the specified path, with [DEFAULT_BACKLOG].
+    #[inline]
     pub fn bind<P: AsRef<Path>>(p: P) -> io::Result<Self> {
-        let listener = StdUnixListener::bind(p)?;
+        Self::bind_with_backlog(p, DEFAULT_BACKLOG)
+    }
+
+    /// Bind a UnixListener to the specified path, with the specified listen backlog.
+    pub fn bind_with_backlog<P: AsRef<Path>>(p: P, backlog: i32) -> io::Result<Self> {
+        let listener = unix_listen(p.as_ref(), backlog, None)?;
         Self::from_std(listener)
     }
 
+    /// Bind a UnixListener to the specified path with [DEFAULT_BACKLOG], and set the permission
+    /// `mode` (e.g. 0o600) of the socket file, instead of the default from umask.
+    ///
+    /// On Linux the mode is applied before the socket file is created, so it's never
+    /// accessible with the default permission.
+    pub fn bind_with_mode<P: AsRef<Path>>(p: P, mode: u32) -> io::Result<Self> {
+        let listener = unix_listen(p.as_ref(), DEFAULT_BACKLOG, Some(mode))?;
+        Self::from_std(listener)
+    }
+
+    /// Bind a UnixListener to the `name` in abstract namespace, without filesystem entry.
+    #[cfg(target_os = "linux")]
+    pub fn bind_abstract(name: &str) -> io::Result<Self> {
+        use std::os::linux::net::SocketAddrExt;
+        let addr = UnixSocketAddr::from_abstract_name(name)?;
+        Self::from_std(StdUnixListener::bind_addr(&addr)?)
+    }
+
     /// Accept a new connection.
+    #[inline]
     pub async fn accept(&mut self) -> io::Result<UnixStream<IO>> {
+        let (stream, _) = self.accept_with_addr().await?;
+        Ok(stream)
+    }
+
+    /// Accept a new connection, fails with `TimedOut` if none arrives within `timeout`.
+    ///
+    /// `timeout` of 0 means no timeout.
+    #[inline]
+    pub async fn accept_timeout(&mut self, timeout: Duration) -> io::Result<UnixStream<IO>>
+    where
+        IO: AsyncTime,
+    {
+        io_with_timeout!(IO, timeout, self.accept())
+    }
+
+    /// Accept a new connection, along with the peer address.
+    ///
+    /// NOTE: the address is unnamed if the client did not bind to a path.
+    pub async fn accept_with_addr(&mut self) -> io::Result<(UnixStream<IO>, UnixSocketAddr)> {
         match self.inner.async_read(|listener| listener.accept()).await {
-            Ok((stream, _)) => {
+            Ok((stream, addr)) => {
                 stream.set_nonblocking(true).map_err(|e| {
                     io::Error::new(
                         io::ErrorKind::Other,
@@ -143,20 +445,35 @@ impl<IO: AsyncIO> UnixListener<IO> {
                     )
                 })?;
                 let inner = IO::to_async_fd_rw(stream)?;
-                Ok(UnixStream { inner })
+                Ok((UnixStream { inner }, addr))
             }
             Err(e) => Err(e),
         }
     }
 
     /// Get the local address of the listener.
+    ///
+    /// For the abstract namespace, the name is returned with "@" prefix.
     pub fn local_addr(&self) -> io::Result<String> {
+        Ok(self.local_unify_addr()?.to_string())
+    }
+
+    /// Get the path the listener bound to.
+    ///
+    /// Returns error for the abstract namespace, which has no path.
+    pub fn local_path(&self) -> io::Result<PathBuf> {
+        match self.local_unify_addr()? {
+            UnifyAddr::Path(path) => Ok(path),
+            addr => Err(AddrError::NoPathname(addr.to_string()).into()),
+        }
+    }
+
+    fn local_unify_addr(&self) -> io::Result<UnifyAddr> {
         let addr = self.inner.local_addr()?;
-        Ok(addr
-            .as_pathname()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "No pathname for Unix socket"))?
-            .to_string_lossy()
-            .into_owned())
+        if addr.is_unnamed() {
+            return Err(AddrError::NoPathname(format!("{:?}", addr)).into());
+        }
+        Ok(unix_to_unify_addr(&addr))
     }
 
     /// Try to recover a listener from RawFd.
@@ -177,6 +494,18 @@ impl<IO: AsyncIO> UnixListener<IO> {
     }
 }
 
+/// Convert the unix socket address, the unnamed one becomes an empty path
+fn unix_to_unify_addr(addr: &UnixSocketAddr) -> UnifyAddr {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::linux::net::SocketAddrExt;
+        if let Some(name) = addr.as_abstract_name() {
+            return UnifyAddr::Abstract(String::from_utf8_lossy(name).into_owned());
+        }
+    }
+    UnifyAddr::Path(addr.as_pathname().map(|p| p.to_path_buf()).unwrap_or_default())
+}
+
 impl<IO: AsyncIO> fmt::Debug for TcpListener<IO> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.local_addr() {
@@ -207,6 +536,18 @@ impl<IO: AsyncIO> AsRawFd for UnixListener<IO> {
     }
 }
 
+impl<IO: AsyncIO> AsRawFd for TcpStream<IO> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl<IO: AsyncIO> AsRawFd for UnixStream<IO> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
 impl<IO: AsyncIO> TcpStream<IO> {
     /// Connect to a TCP address asynchronously.
     ///
@@ -221,24 +562,13 @@ impl<IO: AsyncIO> TcpStream<IO> {
     ///
     /// A future that resolves to a `Result` containing either the connected
     /// TcpStream or an I/O error.
+    #[inline]
     pub async fn connect<A: ResolveAddr + ?Sized>(addr: &A) -> io::Result<Self>
     where
         IO: AsyncExec,
     {
-        // generic params are Sized by default, while str is ?Sized
-        match addr.resolve::<IO>().await {
-            Ok(UnifyAddr::Socket(socket_addr)) => {
-                let stream = IO::connect_tcp(&socket_addr).await?;
-                Ok(TcpStream { inner: stream })
-            }
-            Err(e) => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("addr {:?} invalid: {:?}", addr, e),
-            )),
-            Ok(UnifyAddr::Path(_)) => {
-                Err(io::Error::new(io::ErrorKind::Other, format!("addr {:?} invalid", addr)))
-            }
-        }
+        let _addr = resolve_tcp_addr::<IO, A>(addr).await?;
+        Ok(TcpStream { inner: IO::connect_tcp(&_addr).await? })
     }
 
     /// Connect to a TCP address asynchronously with a timeout.
@@ -257,19 +587,210 @@ impl<IO: AsyncIO> TcpStream<IO> {
     ///
     /// A future that returns to a `Result` containing either the connected
     /// TcpStream or an I/O error.
+    #[inline]
     pub async fn connect_timeout<A>(addr: &A, timeout: std::time::Duration) -> io::Result<Self>
     where
-        IO: AsyncTime + AsyncTime + AsyncExec,
+        IO: AsyncTime + AsyncExec,
         A: ResolveAddr + ?Sized,
     {
-        // generic params are Sized by default, while str is ?Sized
-        io_with_timeout!(IO, timeout, Self::connect::<A>(addr))
+        Self::connect_with(addr, &ConnectOptions::new().timeout(timeout)).await
+    }
+
+    /// Connect with Multipath TCP (IPPROTO_MPTCP), which uses multiple network paths when the
+    /// peer supports, for resilience of mobile / multi-homed hosts.
+    ///
+    /// Falls back to plain TCP if MPTCP is not supported (non-Linux, or disabled in the kernel).
+    /// The kernel also falls back to TCP when the peer does not support MPTCP. Check with
+    /// [is_multipath()](Self::is_multipath).
+    pub async fn connect_mptcp<A: ResolveAddr + ?Sized>(addr: &A) -> io::Result<Self>
+    where
+        IO: AsyncExec,
+    {
+        let _addr = resolve_tcp_addr::<IO, A>(addr).await?;
+        Self::connect_with_socket(tcp_socket(&_addr, true)?, &_addr).await
+    }
+
+    /// Connect with Happy Eyeballs (RFC 8305) on dual-stack hosts.
+    ///
+    /// All the addresses of `addr` ("host:port") are resolved, and tried in the order of
+    /// alternating address families. The next attempt starts when the previous fails, or after
+    /// `delay` (250ms is recommended by the RFC) in parallel. The first one connected wins, and the
+    /// rest are cancelled.
+    pub async fn connect_happy_eyeballs(addr: &str, delay: Duration) -> io::Result<Self>
+    where
+        IO: AsyncExec + AsyncTime,
+    {
+        let addrs = resolve_tcp_all::<IO>(addr).await?;
+        Self::connect_any(&interleave_family(addrs), delay).await
+    }
+
+    /// Connect to the first reachable one of `addrs`, racing the attempts staggered by `delay`.
+    ///
+    /// The attempts start in the order of `addrs`, the next one starts when the previous fails or
+    /// after `delay`. Returns the first connected, the others are cancelled. If all failed,
+    /// returns the last error.
+    pub async fn connect_any(addrs: &[SocketAddr], delay: Duration) -> io::Result<Self>
+    where
+        IO: AsyncTime,
+    {
+        race_connect::<IO, _, _>(addrs, delay, |addr| async move {
+            Ok(TcpStream { inner: IO::connect_tcp(&addr).await? })
+        })
+        .await
+    }
+
+    /// Whether the socket is created with IPPROTO_MPTCP.
+    ///
+    /// NOTE: The connection might still fallback to TCP if the peer does not support MPTCP.
+    #[cfg(target_os = "linux")]
+    pub fn is_multipath(&self) -> io::Result<bool> {
+        let protocol = socket2::SockRef::from(&*self.inner).protocol()?;
+        Ok(protocol == Some(socket2::Protocol::MPTCP))
+    }
+
+    /// Connect with a socket created and configured by the caller.
+    ///
+    /// The socket options are kept on the connected stream. The socket will be set to
+    /// non-blocking if not already.
+    pub async fn connect_with_socket(socket: Socket, addr: &SocketAddr) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        match socket.connect(&(*addr).into()) {
+            Ok(()) => {}
+            Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {}
+            Err(e) => return Err(e),
+        }
+        let inner = IO::to_async_fd_rw(StdTcpStream::from(socket))?;
+        inner
+            .async_write(|stream| {
+                if let Some(e) = stream.take_error()? {
+                    return Err(e);
+                }
+                match stream.peer_addr() {
+                    Ok(_) => Ok(()),
+                    Err(e) if e.kind() == io::ErrorKind::NotConnected => {
+                        Err(io::ErrorKind::WouldBlock.into())
+                    }
+                    Err(e) => Err(e),
+                }
+            })
+            .await?;
+        Ok(TcpStream { inner })
+    }
+
+    /// Connect from the `local` address, to select the source IP on multi-homed hosts, or a
+    /// fixed source port.
+    ///
+    /// The port of `local` can be 0 to let the OS pick one. Returns `InvalidInput` if `local` and
+    /// `addr` are of different address families.
+    pub async fn connect_from<A: ResolveAddr + ?Sized>(
+        local: SocketAddr, addr: &A,
+    ) -> io::Result<Self>
+    where
+        IO: AsyncExec,
+    {
+        let _addr = resolve_tcp_addr::<IO, A>(addr).await?;
+        if local.is_ipv4() != _addr.is_ipv4() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("local {} and remote {} are of different families", local, _addr),
+            ));
+        }
+        Self::connect_bound(local, &_addr).await
     }
 
     #[inline]
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
         self.inner.peer_addr()
     }
+
+    #[inline]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Send a byte as TCP urgent data (MSG_OOB).
+    ///
+    /// It does not wait, returns `ErrorKind::WouldBlock` when the send buffer is full.
+    pub fn send_urgent(&mut self, byte: u8) -> io::Result<()> {
+        let fd = self.inner.as_raw_fd();
+        let r =
+            unsafe { libc::send(fd, &byte as *const u8 as *const libc::c_void, 1, libc::MSG_OOB) };
+        if r < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Receive the TCP urgent byte out of band (MSG_OOB).
+    ///
+    /// It does not wait, returns `ErrorKind::WouldBlock` when there's no urgent data pending
+    /// (EINVAL by the OS), or it's not arrived yet. It fails with EINVAL as well when
+    /// [set_oob_inline](Self::set_oob_inline) is enabled, the urgent byte is in the normal stream
+    /// then.
+    ///
+    /// The normal read stops at the urgent mark, use [at_mark](Self::at_mark) to detect it.
+    pub fn recv_urgent(&mut self) -> io::Result<u8> {
+        let fd = self.inner.as_raw_fd();
+        let mut byte = 0u8;
+        let r =
+            unsafe { libc::recv(fd, &mut byte as *mut u8 as *mut libc::c_void, 1, libc::MSG_OOB) };
+        if r < 0 {
+            let e = io::Error::last_os_error();
+            if e.raw_os_error() == Some(libc::EINVAL) {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "no urgent data pending"));
+            }
+            return Err(e);
+        }
+        if r == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(byte)
+    }
+
+    /// Set SO_RCVLOWAT, the stream is not readable until at least `n` bytes are received (or
+    /// EOF, error), to avoid the wakeup for partial records.
+    ///
+    /// It's supported by Linux (since 2.6.28), macOS and the BSDs. The read returns what is
+    /// available after wakeup, which might be still less than `n` when the peer closed.
+    #[inline]
+    pub fn set_recv_lowat(&self, n: usize) -> io::Result<()> {
+        set_sockopt_int(self.inner.as_raw_fd(), libc::SO_RCVLOWAT, n as libc::c_int)
+    }
+
+    /// Get SO_RCVLOWAT
+    #[inline]
+    pub fn recv_lowat(&self) -> io::Result<usize> {
+        Ok(get_sockopt_int(self.inner.as_raw_fd(), libc::SO_RCVLOWAT)? as usize)
+    }
+
+    /// Set SO_SNDLOWAT, the stream is not writable until at least `n` bytes of send buffer are
+    /// available.
+    ///
+    /// It's supported by macOS and the BSDs, Linux returns `ENOPROTOOPT` as it is not changeable
+    /// there (fixed to 1).
+    #[inline]
+    pub fn set_send_lowat(&self, n: usize) -> io::Result<()> {
+        set_sockopt_int(self.inner.as_raw_fd(), libc::SO_SNDLOWAT, n as libc::c_int)
+    }
+
+    /// Set SO_OOBINLINE, to receive the urgent byte in the normal data stream.
+    #[inline]
+    pub fn set_oob_inline(&self, inline: bool) -> io::Result<()> {
+        socket2::SockRef::from(&*self.inner).set_out_of_band_inline(inline)
+    }
+
+    /// Whether the read position is at the urgent mark (SIOCATMARK), i.e. the data before the
+    /// urgent byte has all been read.
+    #[cfg(target_os = "linux")]
+    pub fn at_mark(&self) -> io::Result<bool> {
+        // From <asm-generic/sockios.h>
+        const SIOCATMARK: libc::c_ulong = 0x8905;
+        let mut n: libc::c_int = 0;
+        if unsafe { libc::ioctl(self.inner.as_raw_fd(), SIOCATMARK as _, &mut n) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(n != 0)
+    }
 }
 
 impl<IO: AsyncIO> AsyncRead for TcpStream<IO> {
@@ -277,12 +798,58 @@ impl<IO: AsyncIO> AsyncRead for TcpStream<IO> {
         use std::io::Read;
         self.inner.async_read(|mut stream| stream.read(buf)).await
     }
+
+    async fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        use std::io::Read;
+        self.inner.async_read(|mut stream| stream.read_vectored(bufs)).await
+    }
+
+    #[inline]
+    async fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        recv_uninit::<IO, _>(&self.inner, buf).await
+    }
 }
 
 impl<IO: AsyncIO> AsyncWrite for TcpStream<IO> {
+    #[inline]
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        write_fast::<IO, _>(&self.inner, buf).await
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.inner.shutdown(std::net::Shutdown::Write)
+    }
+}
+
+/// Read and write through the shared reference, like `impl Read for &TcpStream` in std, so that
+/// both directions can be driven concurrently, refer to [copy_bidirectional()](crate::io::copy_bidirectional).
+impl<IO: AsyncIO> AsyncRead for &TcpStream<IO> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use std::io::Read;
+        self.inner.async_read(|mut stream| stream.read(buf)).await
+    }
+
+    async fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        use std::io::Read;
+        self.inner.async_read(|mut stream| stream.read_vectored(bufs)).await
+    }
+
+    #[inline]
+    async fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        recv_uninit::<IO, _>(&self.inner, buf).await
+    }
+}
+
+impl<IO: AsyncIO> AsyncWrite for &TcpStream<IO> {
+    #[inline]
     async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        use std::io::Write;
-        self.inner.async_write(|mut stream| stream.write(buf)).await
+        write_fast::<IO, _>(&self.inner, buf).await
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.inner.shutdown(std::net::Shutdown::Write)
     }
 }
 
@@ -305,19 +872,308 @@ impl<IO: AsyncIO> UnixStream<IO> {
         let stream = IO::connect_unix(&path_buf).await?;
         Ok(UnixStream { inner: stream })
     }
+
+    /// Connect to a Unix socket address asynchronously with a timeout.
+    ///
+    /// If the connection attempt takes longer than the specified timeout, an error of
+    /// `ErrorKind::TimedOut` will be returned.
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - The path to the Unix socket
+    /// * `timeout` - The maximum time to wait for the connection
+    ///
+    /// # Returns
+    ///
+    /// A future that returns `Result` containing either the connected
+    /// UnixStream or an I/O error.
+    pub async fn connect_timeout<P: AsRef<Path>>(addr: P, timeout: Duration) -> io::Result<Self>
+    where
+        IO: AsyncTime,
+    {
+        let path_buf = addr.as_ref().to_path_buf();
+        io_with_timeout!(IO, timeout, Self::connect(path_buf))
+    }
+
+    /// Connect to the `name` in abstract namespace.
+    ///
+    /// NOTE: The connection of unix socket completes immediately, unless the backlog of the
+    /// listener is full.
+    #[cfg(target_os = "linux")]
+    pub async fn connect_abstract(name: &str) -> io::Result<Self> {
+        use std::os::linux::net::SocketAddrExt;
+        let addr = UnixSocketAddr::from_abstract_name(name)?;
+        let stream = StdUnixStream::connect_addr(&addr)?;
+        stream.set_nonblocking(true)?;
+        Ok(UnixStream { inner: IO::to_async_fd_rw(stream)? })
+    }
+
+    /// Get the credentials of the peer process, with SO_PEERCRED.
+    #[cfg(target_os = "linux")]
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        let mut cred: libc::ucred = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        let r = unsafe {
+            libc::getsockopt(
+                self.inner.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                &mut cred as *mut _ as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        if r < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(UCred { pid: cred.pid, uid: cred.uid, gid: cred.gid })
+    }
+
+    /// Get the executable path of the peer process, from `/proc/<pid>/exe`.
+    ///
+    /// Returns `NotFound` if the peer process has exited.
+    #[cfg(target_os = "linux")]
+    pub fn peer_exe(&self) -> io::Result<PathBuf> {
+        let cred = self.peer_cred()?;
+        if cred.pid <= 0 {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "peer pid not available"));
+        }
+        match std::fs::read_link(format!("/proc/{}/exe", cred.pid)) {
+            Ok(path) => Ok(path),
+            Err(e)
+                if e.kind() == io::ErrorKind::NotFound || e.raw_os_error() == Some(libc::ESRCH) =>
+            {
+                Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("peer process {} exited", cred.pid),
+                ))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Credentials of the peer process of a unix socket
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UCred {
+    pub pid: i32,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl<IO: AsyncIO> AsyncRead for UnixStream<IO> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use std::io::Read;
+        self.inner.async_read(|mut stream| stream.read(buf)).await
+    }
+
+    async fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        use std::io::Read;
+        self.inner.async_read(|mut stream| stream.read_vectored(bufs)).await
+    }
+
+    #[inline]
+    async fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        recv_uninit::<IO, _>(&self.inner, buf).await
+    }
+}
+
+impl<IO: AsyncIO> AsyncWrite for UnixStream<IO> {
+    #[inline]
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        write_fast::<IO, _>(&self.inner, buf).await
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.inner.shutdown(std::net::Shutdown::Write)
+    }
+}
+
+/// Read and write through the shared reference, like `impl Read for &TcpStream` in std, so that
+/// both directions can be driven concurrently, refer to [copy_bidirectional()](crate::io::copy_bidirectional).
+impl<IO: AsyncIO> AsyncRead for &UnixStream<IO> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use std::io::Read;
+        self.inner.async_read(|mut stream| stream.read(buf)).await
+    }
+
+    async fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        use std::io::Read;
+        self.inner.async_read(|mut stream| stream.read_vectored(bufs)).await
+    }
+
+    #[inline]
+    async fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        recv_uninit::<IO, _>(&self.inner, buf).await
+    }
+}
+
+impl<IO: AsyncIO> AsyncWrite for &UnixStream<IO> {
+    #[inline]
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        write_fast::<IO, _>(&self.inner, buf).await
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.inner.shutdown(std::net::Shutdown::Write)
+    }
+}
+
+/// Write to the non-blocking socket directly, only go through the readiness of the reactor on
+/// WouldBlock.
+///
+/// In the common case of small writes the socket buffer has room, this saves the poll of the
+/// reactor.
+async fn write_fast<IO: AsyncIO, T>(inner: &IO::AsyncFd<T>, buf: &[u8]) -> io::Result<usize>
+where
+    T: AsRawFd + AsFd + Send + Sync + 'static,
+    for<'a> &'a T: std::io::Write,
+{
+    use std::io::Write;
+    match (&**inner).write(buf) {
+        Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted) => {}
+        r => return r,
+    }
+    inner.async_write(|mut stream| stream.write(buf)).await
+}
+
+/// Receive into the unfilled region of `buf` without zeroing it first.
+async fn recv_uninit<IO: AsyncIO, T>(
+    inner: &IO::AsyncFd<T>, buf: &mut ReadBuf<'_>,
+) -> io::Result<usize>
+where
+    T: AsRawFd + AsFd + Send + Sync + 'static,
+{
+    // SAFETY: recv() only writes into the memory, never de-initialize it
+    let unfilled = unsafe { buf.unfilled_mut() };
+    let n = inner
+        .async_read(|stream| {
+            let r = unsafe {
+                libc::recv(
+                    stream.as_raw_fd(),
+                    unfilled.as_mut_ptr() as *mut libc::c_void,
+                    unfilled.len(),
+                    0,
+                )
+            };
+            if r < 0 { Err(io::Error::last_os_error()) } else { Ok(r as usize) }
+        })
+        .await?;
+    // SAFETY: recv() has written n bytes
+    unsafe { buf.assume_init(n) };
+    buf.advance(n);
+    Ok(n)
+}
+
+/// A snapshot of the statistics of a stream.
+///
+/// The fields not supported by the transport (or the OS) are None.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamStatsSnapshot {
+    /// Bytes received but not yet read
+    pub recv_queued: Option<usize>,
+    /// Bytes written but not yet sent (for tcp, including those not acknowledged by the peer)
+    pub send_queued: Option<usize>,
+    /// Smoothed round trip time
+    pub rtt: Option<Duration>,
+    /// Round trip time variance
+    pub rtt_var: Option<Duration>,
+    /// Total retransmitted segments
+    pub retransmits: Option<u32>,
+}
+
+/// Uniform API to get the statistics of a stream, regardless of transport
+pub trait StreamStats {
+    fn stats(&self) -> io::Result<StreamStatsSnapshot>;
+}
+
+/// Get the length of receive queue and send queue of a socket.
+fn set_sockopt_int(fd: RawFd, opt: libc::c_int, value: libc::c_int) -> io::Result<()> {
+    let r = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            opt,
+            &value as *const _ as *const libc::c_void,
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if r < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
 }
 
-impl<IO: AsyncIO> AsyncRead for UnixStream<IO> {
-    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        use std::io::Read;
-        self.inner.async_read(|mut stream| stream.read(buf)).await
+fn get_sockopt_int(fd: RawFd, opt: libc::c_int) -> io::Result<libc::c_int> {
+    let mut value: libc::c_int = 0;
+    let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+    let r = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            opt,
+            &mut value as *mut _ as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if r < 0 {
+        return Err(io::Error::last_os_error());
     }
+    Ok(value)
 }
 
-impl<IO: AsyncIO> AsyncWrite for UnixStream<IO> {
-    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        use std::io::Write;
-        self.inner.async_write(|mut stream| stream.write(buf)).await
+fn sock_queued(fd: RawFd, stats: &mut StreamStatsSnapshot) -> io::Result<()> {
+    let mut n: libc::c_int = 0;
+    if unsafe { libc::ioctl(fd, libc::FIONREAD, &mut n) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    stats.recv_queued = Some(n as usize);
+    #[cfg(target_os = "linux")]
+    {
+        if unsafe { libc::ioctl(fd, libc::TIOCOUTQ, &mut n) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        stats.send_queued = Some(n as usize);
+    }
+    Ok(())
+}
+
+impl<IO: AsyncIO> StreamStats for TcpStream<IO> {
+    fn stats(&self) -> io::Result<StreamStatsSnapshot> {
+        let fd = self.inner.as_raw_fd();
+        let mut stats = StreamStatsSnapshot::default();
+        sock_queued(fd, &mut stats)?;
+        #[cfg(target_os = "linux")]
+        {
+            let mut info: libc::tcp_info = unsafe { std::mem::zeroed() };
+            let mut len = std::mem::size_of::<libc::tcp_info>() as libc::socklen_t;
+            let r = unsafe {
+                libc::getsockopt(
+                    fd,
+                    libc::IPPROTO_TCP,
+                    libc::TCP_INFO,
+                    &mut info as *mut _ as *mut libc::c_void,
+                    &mut len,
+                )
+            };
+            if r < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            stats.rtt = Some(Duration::from_micros(info.tcpi_rtt as u64));
+            stats.rtt_var = Some(Duration::from_micros(info.tcpi_rttvar as u64));
+            stats.retransmits = Some(info.tcpi_total_retrans);
+        }
+        Ok(stats)
+    }
+}
+
+impl<IO: AsyncIO> StreamStats for UnixStream<IO> {
+    fn stats(&self) -> io::Result<StreamStatsSnapshot> {
+        let mut stats = StreamStatsSnapshot::default();
+        sock_queued(self.inner.as_raw_fd(), &mut stats)?;
+        Ok(stats)
     }
 }
 
@@ -343,6 +1199,18 @@ pub trait AsyncListener: Send + Sized + 'static + fmt::Debug {
 
     fn local_addr(&self) -> io::Result<String>;
 
+    /// Convert the listener into a stream of incoming connections, which calls `accept()`
+    /// repeatedly.
+    ///
+    /// The stream never ends, the error of accept is yielded as item.
+    /// The returned stream might not be Unpin, use `std::pin::pin!()` before calling `next()`.
+    fn incoming(self) -> impl Stream<Item = io::Result<Self::Conn>> + Send {
+        futures_lite::stream::unfold(self, |mut listener| async move {
+            let r = listener.accept().await;
+            Some((r, listener))
+        })
+    }
+
     /// Try to recover a listener from RawFd
     ///
     /// Will set listener to non_blocking to validate the fd
@@ -409,12 +1277,319 @@ impl<IO: AsyncIO + AsyncExec> AsyncListener for UnixListener<IO> {
     }
 }
 
-/// Unify behavior of tcp & unix addr
-#[derive(Clone, PartialEq, Eq)]
+/// Trait for stream transport, which is the connection type of an [AsyncListener].
+///
+/// Implement this trait (along with a listener) for other stream protocols (for example SCTP, QUIC
+/// stream, in-memory pipe), so it can work with the helpers which generic over the transport.
+pub trait Transport: AsyncRead + AsyncWrite + Send + Sized + 'static {
+    type Listener: AsyncListener<Conn = Self>;
+
+    fn connect(addr: &str) -> impl Future<Output = io::Result<Self>> + Send;
+
+    #[inline(always)]
+    fn bind(addr: &str) -> impl Future<Output = io::Result<Self::Listener>> + Send {
+        Self::Listener::bind(addr)
+    }
+}
+
+impl<IO: AsyncIO + AsyncExec> Transport for TcpStream<IO> {
+    type Listener = TcpListener<IO>;
+
+    #[inline]
+    async fn connect(addr: &str) -> io::Result<Self> {
+        TcpStream::<IO>::connect(addr).await
+    }
+}
+
+impl<IO: AsyncIO + AsyncExec> Transport for UnixStream<IO> {
+    type Listener = UnixListener<IO>;
+
+    #[inline]
+    async fn connect(addr: &str) -> io::Result<Self> {
+        UnixStream::<IO>::connect(addr).await
+    }
+}
+
+/// Run `f` inside the network namespace of `ns_fd`, for creating sockets with bind / connect in
+/// the target netns (Linux only).
+///
+/// Since `setns()` affects the whole thread, `f` runs on a dedicated thread which exits
+/// afterwards, so the namespace of other tasks (and the threads of the runtime) is not affected.
+/// The result is awaited without occupying a thread of the runtime. `ns_fd` should be kept open
+/// until returned.
+///
+/// The sockets created remain in the namespace after the thread exits, so create the std sockets
+/// in `f`, then convert them with `from_std()` outside.
+///
+/// # Panics
+///
+/// The panic of `f` is resumed on the caller.
+#[cfg(target_os = "linux")]
+pub async fn in_netns<F, R>(ns_fd: RawFd, f: F) -> io::Result<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let (tx, rx) = crate::utils::oneshot();
+    std::thread::Builder::new().name("orb-netns".to_string()).spawn(move || {
+        let r = if unsafe { libc::setns(ns_fd, libc::CLONE_NEWNET) } < 0 {
+            Ok(Err(io::Error::last_os_error()))
+        } else {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map(Ok)
+        };
+        let _ = tx.send(r);
+    })?;
+    match rx.await {
+        Ok(Ok(r)) => r,
+        Ok(Err(payload)) => std::panic::resume_unwind(payload),
+        Err(_) => Err(io::Error::other("in_netns thread exited")),
+    }
+}
+
+/// Accept connections from the listener in a loop, spawn `handler` for each connection.
+///
+/// Returns when accept failed.
+pub async fn serve<RT, L, H, F>(rt: &RT, mut listener: L, handler: H) -> io::Result<()>
+where
+    RT: AsyncExec,
+    L: AsyncListener,
+    H: Fn(L::Conn) -> F + Send + Sync,
+    F: Future<Output = ()> + Send + 'static,
+{
+    loop {
+        let conn = listener.accept().await?;
+        rt.spawn_detach(handler(conn));
+    }
+}
+
+/// Accept connections for the duration of `total`, pass each connection to `on_conn`.
+///
+/// Returns the count of the accepted connections when `total` elapsed, or error when accept
+/// failed.
+pub async fn accept_for<L, IO>(
+    listener: &mut L, total: Duration, mut on_conn: impl FnMut(L::Conn),
+) -> io::Result<usize>
+where
+    L: AsyncListener,
+    IO: AsyncTime,
+{
+    let mut deadline = std::pin::pin!(IO::sleep(total));
+    let mut count = 0;
+    while let Ok(r) = Cancellable::new(listener.accept(), deadline.as_mut()).await {
+        on_conn(r?);
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// The handles of the running handlers of [serve_graceful()], keyed by id
+struct InFlight<H> {
+    handles: Mutex<HashMap<u64, H>>,
+    done: WaitList,
+}
+
+/// Remove the handle of its own when the handler finishes, or aborted.
+struct InFlightGuard<H> {
+    in_flight: Arc<InFlight<H>>,
+    id: u64,
+}
+
+impl<H> Drop for InFlightGuard<H> {
+    fn drop(&mut self) {
+        let handle = {
+            let mut handles = self.in_flight.handles.lock().unwrap();
+            let handle = handles.remove(&self.id);
+            if handles.is_empty() {
+                self.in_flight.done.notify_all();
+            }
+            handle
+        };
+        // Detach outside the lock
+        drop(handle);
+    }
+}
+
+impl<H> InFlight<H> {
+    async fn wait_idle(&self) {
+        loop {
+            let mut listen = self.done.listen();
+            poll_once(&mut listen).await;
+            if self.handles.lock().unwrap().is_empty() {
+                return;
+            }
+            listen.await;
+        }
+    }
+}
+
+/// Like [serve()], but stops accepting on `shutdown`, and drains the in-flight handlers.
+///
+/// After shutdown, waits up to `grace` for the spawned handlers to finish, then aborts the
+/// stragglers. Returns the count of the handlers aborted.
+///
+/// Returns error when accept failed, the running handlers are detached.
+pub async fn serve_graceful<RT, L, H, F>(
+    rt: &RT, mut listener: L, handler: H, shutdown: ShutdownToken, grace: Duration,
+) -> io::Result<usize>
+where
+    RT: AsyncExec + AsyncTime,
+    L: AsyncListener,
+    H: Fn(L::Conn) -> F + Send + Sync,
+    F: Future<Output = ()> + Send + 'static,
+{
+    let in_flight: Arc<InFlight<RT::AsyncHandle<()>>> =
+        Arc::new(InFlight { handles: Mutex::new(HashMap::new()), done: WaitList::default() });
+    let mut next_id = 0;
+    while let Ok(r) = Cancellable::new(listener.accept(), shutdown.cancelled()).await {
+        let conn = r?;
+        let f = handler(conn);
+        let id = next_id;
+        next_id += 1;
+        let guard = InFlightGuard { in_flight: in_flight.clone(), id };
+        // Insert within the lock, so the guard of a quick handler removes the entry after it
+        let mut handles = in_flight.handles.lock().unwrap();
+        let handle = rt.spawn(async move {
+            let _guard = guard;
+            f.await
+        });
+        handles.insert(id, handle);
+    }
+    drop(listener);
+    if RT::timeout(grace, in_flight.wait_idle()).await.is_ok() {
+        return Ok(0);
+    }
+    // Abort outside the lock, as the guards of the aborted handlers take it
+    let handles = std::mem::take(&mut *in_flight.handles.lock().unwrap());
+    let mut aborted = 0;
+    for (_, handle) in handles {
+        if !handle.is_finished() {
+            handle.abort();
+            aborted += 1;
+        }
+    }
+    Ok(aborted)
+}
+
+/// The typed error of address handling in this module.
+///
+/// It's embedded as the source of the returned `io::Error`, which can be obtained with
+/// `e.get_ref().and_then(|e| e.downcast_ref::<AddrError>())`.
+#[derive(Debug, Clone)]
+pub enum AddrError {
+    /// The address string is malformed
+    InvalidAddr(String),
+    /// The address type does not fit the socket, e.g. unix path for tcp
+    WrongScheme(String),
+    /// Failed to resolve the address, the DNS lookup failed or returned nothing.
+    ///
+    /// The [ResolveError] (`Dns` with the error of the resolver, or `NoAddresses`) is kept as the
+    /// [source()](std::error::Error::source).
+    ResolveFailed(String, Arc<ResolveError>),
+    /// The unix socket has no pathname, it's unnamed or in the abstract namespace
+    NoPathname(String),
+}
+
+impl AddrError {
+    /// The `io::ErrorKind` of the converted `io::Error`
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::InvalidAddr(_) | Self::WrongScheme(_) => io::ErrorKind::InvalidInput,
+            Self::ResolveFailed(..) => io::ErrorKind::NotFound,
+            Self::NoPathname(_) => io::ErrorKind::AddrNotAvailable,
+        }
+    }
+}
+
+impl fmt::Display for AddrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidAddr(addr) => write!(f, "addr {} invalid", addr),
+            Self::WrongScheme(addr) => write!(f, "addr {} is of wrong type", addr),
+            Self::ResolveFailed(addr, e) => write!(f, "addr {} cannot be resolved: {}", addr, e),
+            Self::NoPathname(addr) => write!(f, "addr {} has no pathname", addr),
+        }
+    }
+}
+
+impl std::error::Error for AddrError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ResolveFailed(_, e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+impl From<AddrError> for io::Error {
+    #[inline]
+    fn from(e: AddrError) -> Self {
+        io::Error::new(e.kind(), e)
+    }
+}
+
+/// The error of [UnifyAddr::resolve()] and [ResolveAddr::resolve()], to tell a malformed address
+/// from a resolver failure.
+#[derive(Debug)]
+pub enum ResolveError {
+    /// The address is neither an IP address, a unix path, nor in the form of "host:port"
+    Parse(AddrParseError),
+    /// The DNS lookup of the host failed
+    Dns(io::Error),
+    /// The DNS lookup succeeded without any address
+    NoAddresses,
+}
+
+impl ResolveError {
+    /// The `io::ErrorKind` of the converted `io::Error`
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::Parse(_) => io::ErrorKind::InvalidInput,
+            Self::Dns(_) | Self::NoAddresses => io::ErrorKind::NotFound,
+        }
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "invalid addr: {}", e),
+            Self::Dns(e) => write!(f, "dns lookup failed: {}", e),
+            Self::NoAddresses => write!(f, "no address resolved"),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse(e) => Some(e),
+            Self::Dns(e) => Some(e),
+            Self::NoAddresses => None,
+        }
+    }
+}
+
+impl From<ResolveError> for io::Error {
+    #[inline]
+    fn from(e: ResolveError) -> Self {
+        io::Error::new(e.kind(), e)
+    }
+}
+
+/// Whether `s` is in the form of "host:port", which is worth a DNS lookup
+fn is_host_port(s: &str) -> bool {
+    matches!(s.rsplit_once(':'), Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok())
+}
+
+/// Unify behavior of tcp & unix addr, refer to [parse()](Self::parse) for the accepted forms
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum UnifyAddr {
     /// SocketAddr
     Socket(SocketAddr),
     Path(std::path::PathBuf),
+    /// The name of unix socket in abstract namespace, parsed from "@name"
+    #[cfg(target_os = "linux")]
+    Abstract(String),
 }
 
 macro_rules! from_sockaddr {
@@ -447,11 +1622,31 @@ impl From<PathBuf> for UnifyAddr {
 }
 
 impl UnifyAddr {
-    #[inline]
+    /// Parse the address without resolving, the grammar:
+    ///
+    /// - `tcp://<ip>:<port>`: always [Socket](Self::Socket), `[]` around the IPv6 address
+    /// - `unix:<path>`: always [Path](Self::Path), even for a relative path
+    /// - `/<path>`: [Path](Self::Path)
+    /// - `@<name>`: [Abstract](Self::Abstract) (Linux only)
+    /// - `<ip>:<port>`: [Socket](Self::Socket), e.g. `127.0.0.1:80`, `[::1]:80`
     pub fn parse(s: &str) -> Result<Self, AddrParseError> {
-        if s.as_bytes()[0] as char == '/' {
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            return Ok(Self::Socket(addr.parse::<SocketAddr>()?));
+        }
+        if let Some(path) = s.strip_prefix("unix:") {
+            if path.is_empty() {
+                // Borrow the error of SocketAddr, which can't be constructed otherwise
+                return Err(path.parse::<SocketAddr>().unwrap_err());
+            }
+            return Ok(Self::Path(std::path::PathBuf::from(path)));
+        }
+        if s.starts_with('/') {
             return Ok(Self::Path(std::path::PathBuf::from(s)));
         }
+        #[cfg(target_os = "linux")]
+        if let Some(name) = s.strip_prefix('@') {
+            return Ok(Self::Abstract(name.to_string()));
+        }
         let a = s.parse::<SocketAddr>()?;
         Ok(Self::Socket(a))
     }
@@ -462,23 +1657,42 @@ impl UnifyAddr {
     #[inline]
     pub fn resolve<E: AsyncExec>(
         s: &str,
-    ) -> impl Future<Output = Result<Self, AddrParseError>> + Send {
+    ) -> impl Future<Output = Result<Self, ResolveError>> + Send {
         async move {
-            // TODO change this to async
-            match Self::parse(s) {
-                Ok(addr) => return Ok(addr),
-                Err(e) => {
-                    let s = s.to_string();
-                    let task = E::spawn_blocking(move || s.to_socket_addrs());
-                    match task.await.expect("resolve addr task") {
-                        Ok(mut _v) => match _v.next() {
-                            Some(a) => Ok(Self::Socket(a)),
-                            None => Err(e),
-                        },
-                        Err(_) => Err(e),
-                    }
-                }
-            }
+            let mut addrs = Self::lookup::<E>(s).await?;
+            Ok(addrs.swap_remove(0))
+        }
+    }
+
+    /// Parse or resolve the address name to all the addresses, in the order of the resolver.
+    ///
+    /// The unix path (and abstract name) yields a single address. Fails with
+    /// [AddrError::InvalidAddr] if malformed, or [AddrError::ResolveFailed] if nothing is
+    /// resolved.
+    pub async fn resolve_all<E: AsyncExec>(s: &str) -> io::Result<Vec<Self>> {
+        Self::lookup::<E>(s).await.map_err(|e| resolve_error(s, e))
+    }
+
+    /// Parse or resolve to at least one address
+    async fn lookup<E: AsyncExec>(s: &str) -> Result<Vec<Self>, ResolveError> {
+        // TODO change this to async
+        let e = match Self::parse(s) {
+            Ok(addr) => return Ok(vec![addr]),
+            Err(e) => e,
+        };
+        // The host name may follow the tcp scheme as well
+        let name = s.strip_prefix("tcp://").unwrap_or(s);
+        if !is_host_port(name) {
+            return Err(ResolveError::Parse(e));
+        }
+        let name = name.to_string();
+        let task = E::spawn_blocking(move || {
+            name.to_socket_addrs().map(|addrs| addrs.collect::<Vec<_>>())
+        });
+        match task.await.expect("resolve addr task") {
+            Ok(addrs) if addrs.is_empty() => Err(ResolveError::NoAddresses),
+            Ok(addrs) => Ok(addrs.into_iter().map(Self::Socket).collect()),
+            Err(e) => Err(ResolveError::Dns(e)),
         }
     }
 }
@@ -490,39 +1704,65 @@ impl UnifyAddr {
 /// When we can't directly resolve the IP, try to resolve it through the domain name with
 /// background spawn thread, will not block current thread.
 ///
-/// If multiple IP addresses are resolved, only the first result is taken
+/// If multiple IP addresses are resolved, only the first result is taken, use
+/// [resolve_all()](Self::resolve_all) to get all of them.
 pub trait ResolveAddr: fmt::Debug + Send + Sync {
     // Trait are ?Sized by default
-    fn resolve<E: AsyncExec>(
-        &self,
-    ) -> impl Future<Output = Result<UnifyAddr, AddrParseError>> + Send;
+    fn resolve<E: AsyncExec>(&self)
+    -> impl Future<Output = Result<UnifyAddr, ResolveError>> + Send;
+
+    /// Resolve to all the addresses in order, for the clients doing their own selection or
+    /// health checks. Defaults to the single address of [resolve()](Self::resolve).
+    fn resolve_all<E: AsyncExec>(&self) -> impl Future<Output = io::Result<Vec<UnifyAddr>>> + Send {
+        async move {
+            match self.resolve::<E>().await {
+                Ok(addr) => Ok(vec![addr]),
+                Err(e) => Err(resolve_error(self, e)),
+            }
+        }
+    }
 }
 
 impl ResolveAddr for str {
     #[inline]
-    async fn resolve<E: AsyncExec>(&self) -> Result<UnifyAddr, AddrParseError> {
+    async fn resolve<E: AsyncExec>(&self) -> Result<UnifyAddr, ResolveError> {
         return UnifyAddr::resolve::<E>(self).await;
     }
+
+    #[inline]
+    async fn resolve_all<E: AsyncExec>(&self) -> io::Result<Vec<UnifyAddr>> {
+        UnifyAddr::resolve_all::<E>(self).await
+    }
 }
 
 // For &&str.resolve()
 impl ResolveAddr for &str {
     #[inline]
-    async fn resolve<E: AsyncExec>(&self) -> Result<UnifyAddr, AddrParseError> {
+    async fn resolve<E: AsyncExec>(&self) -> Result<UnifyAddr, ResolveError> {
         return UnifyAddr::resolve::<E>(self).await;
     }
+
+    #[inline]
+    async fn resolve_all<E: AsyncExec>(&self) -> io::Result<Vec<UnifyAddr>> {
+        UnifyAddr::resolve_all::<E>(self).await
+    }
 }
 
 impl ResolveAddr for String {
     #[inline]
-    async fn resolve<E: AsyncExec>(&self) -> Result<UnifyAddr, AddrParseError> {
+    async fn resolve<E: AsyncExec>(&self) -> Result<UnifyAddr, ResolveError> {
         return UnifyAddr::resolve::<E>(self.as_str()).await;
     }
+
+    #[inline]
+    async fn resolve_all<E: AsyncExec>(&self) -> io::Result<Vec<UnifyAddr>> {
+        UnifyAddr::resolve_all::<E>(self.as_str()).await
+    }
 }
 
 impl<T: Into<UnifyAddr> + Clone + Send + Sync + fmt::Debug> ResolveAddr for T {
     #[inline]
-    async fn resolve<E: AsyncExec>(&self) -> Result<UnifyAddr, AddrParseError> {
+    async fn resolve<E: AsyncExec>(&self) -> Result<UnifyAddr, ResolveError> {
         Ok(self.clone().into())
     }
 }
@@ -532,6 +1772,8 @@ impl fmt::Display for UnifyAddr {
         match self {
             Self::Socket(s) => write!(f, "{}", s),
             Self::Path(p) => write!(f, "{}", p.display()),
+            #[cfg(target_os = "linux")]
+            Self::Abstract(name) => write!(f, "@{}", name),
         }
     }
 }
@@ -541,6 +1783,8 @@ impl fmt::Debug for UnifyAddr {
         match self {
             Self::Socket(s) => write!(f, "path {}", s),
             Self::Path(p) => write!(f, "sock addr {}", p.display()),
+            #[cfg(target_os = "linux")]
+            Self::Abstract(name) => write!(f, "abstract @{}", name),
         }
     }
 }
@@ -551,7 +1795,7 @@ impl ToSocketAddrs for UnifyAddr {
     fn to_socket_addrs(&self) -> io::Result<Self::Iter> {
         match self {
             Self::Socket(addr) => Ok(vec![*addr].into_iter()),
-            Self::Path(_) => Err(io::Error::new(
+            _ => Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "Unix domain socket paths cannot be converted to SocketAddr",
             )),
@@ -584,6 +1828,8 @@ impl PartialEq<str> for UnifyAddr {
                 }
             }
             Self::Path(p) => *p == std::path::Path::new(other),
+            #[cfg(target_os = "linux")]
+            Self::Abstract(name) => other.strip_prefix('@') == Some(name.as_str()),
         }
     }
 }
@@ -609,29 +1855,12 @@ impl<IO: AsyncIO> UnifyStream<IO> {
     ///
     /// A future that resolves to a `Result` containing either the connected
     /// UnifyStream or an I/O error.
+    #[inline]
     pub async fn connect<A: ResolveAddr + ?Sized>(addr: &A) -> io::Result<Self>
     where
         IO: AsyncExec + AsyncTime,
     {
-        // generic params are Sized by default, while str is ?Sized
-        match addr.resolve::<IO>().await {
-            Err(e) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("addr {:?} invalid: {:?}", addr, e),
-                ));
-            }
-            Ok(UnifyAddr::Socket(socket_addr)) => {
-                let stream = IO::connect_tcp(&socket_addr).await?;
-                let tcp_stream = TcpStream { inner: stream };
-                Ok(UnifyStream::Tcp(tcp_stream))
-            }
-            Ok(UnifyAddr::Path(path)) => {
-                let stream = IO::connect_unix(&path).await?;
-                let unix_stream = UnixStream { inner: stream };
-                Ok(UnifyStream::Unix(unix_stream))
-            }
-        }
+        Self::connect_with(addr, &ConnectOptions::default()).await
     }
 
     /// Connect to a unified address asynchronously with a timeout.
@@ -650,13 +1879,13 @@ impl<IO: AsyncIO> UnifyStream<IO> {
     ///
     /// A future that resolves to a `Result` containing either the connected
     /// UnifyStream or an I/O error.
+    #[inline]
     pub async fn connect_timeout<A>(addr: &A, timeout: Duration) -> io::Result<Self>
     where
         IO: AsyncTime + AsyncExec,
         A: ResolveAddr + ?Sized,
     {
-        // generic params are Sized by default, while str is ?Sized
-        io_with_timeout!(IO, timeout, Self::connect::<A>(addr))
+        Self::connect_with(addr, &ConnectOptions::new().timeout(timeout)).await
     }
 
     #[inline]
@@ -681,6 +1910,26 @@ impl<IO: AsyncIO> UnifyStream<IO> {
             )),
         }
     }
+
+    /// The peer address of both transports, for logging.
+    ///
+    /// For unix socket, returns the path the peer bound to, or an empty path if it did not bind.
+    pub fn peer_unify_addr(&self) -> io::Result<UnifyAddr> {
+        match self {
+            UnifyStream::Tcp(stream) => Ok(UnifyAddr::Socket(stream.peer_addr()?)),
+            UnifyStream::Unix(stream) => Ok(unix_to_unify_addr(&stream.inner.peer_addr()?)),
+        }
+    }
+}
+
+impl<IO: AsyncIO> StreamStats for UnifyStream<IO> {
+    #[inline]
+    fn stats(&self) -> io::Result<StreamStatsSnapshot> {
+        match self {
+            UnifyStream::Tcp(stream) => stream.stats(),
+            UnifyStream::Unix(stream) => stream.stats(),
+        }
+    }
 }
 
 impl<IO: AsyncIO> fmt::Debug for UnifyStream<IO> {
@@ -701,6 +1950,22 @@ impl<IO: AsyncIO> AsyncRead for UnifyStream<IO> {
             UnifyStream::Unix(stream) => stream.read(buf).await,
         }
     }
+
+    #[inline(always)]
+    async fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        match self {
+            UnifyStream::Tcp(stream) => stream.read_vectored(bufs).await,
+            UnifyStream::Unix(stream) => stream.read_vectored(bufs).await,
+        }
+    }
+
+    #[inline(always)]
+    async fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        match self {
+            UnifyStream::Tcp(stream) => stream.read_buf(buf).await,
+            UnifyStream::Unix(stream) => stream.read_buf(buf).await,
+        }
+    }
 }
 
 impl<IO: AsyncIO> AsyncWrite for UnifyStream<IO> {
@@ -710,6 +1975,61 @@ impl<IO: AsyncIO> AsyncWrite for UnifyStream<IO> {
             UnifyStream::Unix(stream) => stream.write(buf).await,
         }
     }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        match self {
+            UnifyStream::Tcp(stream) => stream.shutdown().await,
+            UnifyStream::Unix(stream) => stream.shutdown().await,
+        }
+    }
+}
+
+/// Read and write through the shared reference, refer to [TcpStream].
+impl<IO: AsyncIO> AsyncRead for &UnifyStream<IO> {
+    #[inline(always)]
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            UnifyStream::Tcp(stream) => {
+                let mut stream = stream;
+                stream.read(buf).await
+            }
+            UnifyStream::Unix(stream) => {
+                let mut stream = stream;
+                stream.read(buf).await
+            }
+        }
+    }
+}
+
+impl<IO: AsyncIO> AsyncWrite for &UnifyStream<IO> {
+    #[inline(always)]
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            UnifyStream::Tcp(stream) => {
+                let mut stream = stream;
+                stream.write(buf).await
+            }
+            UnifyStream::Unix(stream) => {
+                let mut stream = stream;
+                stream.write(buf).await
+            }
+        }
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        match self {
+            UnifyStream::Tcp(stream) => {
+                let mut stream = stream;
+                stream.shutdown().await
+            }
+            UnifyStream::Unix(stream) => {
+                let mut stream = stream;
+                stream.shutdown().await
+            }
+        }
+    }
 }
 
 /// Unify behavior of tcp & unix socket listener, provides ad bind that directly accept str
@@ -733,23 +2053,46 @@ impl<IO: AsyncIO> UnifyListener<IO> {
     ///
     /// For unix, will remove the path if exist, prevent failure
     pub async fn bind<A: ResolveAddr + ?Sized>(addr: &A) -> io::Result<Self>
+    where
+        IO: AsyncExec,
+    {
+        Self::bind_inner(addr, None).await
+    }
+
+    /// Same as [bind](Self::bind), and set the permission `mode` of the unix socket file, refer
+    /// to [UnixListener::bind_with_mode()].
+    ///
+    /// The `mode` is ignored for TCP (and the abstract namespace).
+    pub async fn bind_with_mode<A: ResolveAddr + ?Sized>(addr: &A, mode: u32) -> io::Result<Self>
+    where
+        IO: AsyncExec,
+    {
+        Self::bind_inner(addr, Some(mode)).await
+    }
+
+    async fn bind_inner<A: ResolveAddr + ?Sized>(addr: &A, mode: Option<u32>) -> io::Result<Self>
     where
         IO: AsyncExec,
     {
         // generic params are Sized by default, while str is ?Sized
         match addr.resolve::<IO>().await {
             Err(e) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("addr {:?} invalid: {:?}", addr, e),
-                ));
+                return Err(resolve_error(addr, e));
             }
             Ok(UnifyAddr::Socket(_addr)) => Ok(Self::Tcp(TcpListener::<IO>::bind(&_addr).await?)),
             Ok(UnifyAddr::Path(ref path)) => {
                 if path.exists() {
                     std::fs::remove_file(path)?;
                 }
-                return Ok(Self::Unix(UnixListener::<IO>::bind(path)?));
+                let listener = match mode {
+                    Some(mode) => UnixListener::<IO>::bind_with_mode(path, mode)?,
+                    None => UnixListener::<IO>::bind(path)?,
+                };
+                return Ok(Self::Unix(listener));
+            }
+            #[cfg(target_os = "linux")]
+            Ok(UnifyAddr::Abstract(ref name)) => {
+                Ok(Self::Unix(UnixListener::<IO>::bind_abstract(name)?))
             }
         }
     }
@@ -768,6 +2111,17 @@ impl<IO: AsyncIO> UnifyListener<IO> {
         }
     }
 
+    /// Accept a new connection, fails with `TimedOut` if none arrives within `timeout`.
+    ///
+    /// `timeout` of 0 means no timeout.
+    #[inline]
+    pub async fn accept_timeout(&mut self, timeout: Duration) -> io::Result<UnifyStream<IO>>
+    where
+        IO: AsyncTime,
+    {
+        io_with_timeout!(IO, timeout, self.accept())
+    }
+
     #[inline]
     pub fn local_addr(&self) -> io::Result<String> {
         match self {
@@ -776,16 +2130,22 @@ impl<IO: AsyncIO> UnifyListener<IO> {
         }
     }
 
+    /// Get the local address of the listener, typed as [UnifyAddr]
+    #[inline]
+    pub fn local_unify_addr(&self) -> io::Result<UnifyAddr> {
+        match self {
+            UnifyListener::Tcp(listener) => Ok(UnifyAddr::Socket(listener.local_socket_addr()?)),
+            UnifyListener::Unix(listener) => listener.local_unify_addr(),
+        }
+    }
+
     /// This function is for graceful restart, recognize address type according to string
     pub unsafe fn try_from_raw_fd(addr: &str, raw_fd: RawFd) -> io::Result<Self>
     where
         Self: AsRawFd,
     {
         match UnifyAddr::from_str(addr) {
-            Err(e) => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("addr {:?} invalid: {:?}", addr, e),
-            )),
+            Err(_) => Err(AddrError::InvalidAddr(format!("{:?}", addr)).into()),
             Ok(UnifyAddr::Socket(_)) => {
                 let listener = unsafe { StdTcpListener::from_raw_fd(raw_fd) };
                 match TcpListener::from_std(listener) {
@@ -793,7 +2153,7 @@ impl<IO: AsyncIO> UnifyListener<IO> {
                     Err(e) => Err(e),
                 }
             }
-            Ok(UnifyAddr::Path(_)) => {
+            Ok(_) => {
                 let listener = unsafe { StdUnixListener::from_raw_fd(raw_fd) };
                 match UnixListener::from_std(listener) {
                     Ok(l) => Ok(UnifyListener::Unix(l)),
@@ -832,6 +2192,15 @@ impl<IO: AsyncIO + AsyncExec> AsyncListener for UnifyListener<IO> {
     }
 }
 
+impl<IO: AsyncIO + AsyncExec + AsyncTime> Transport for UnifyStream<IO> {
+    type Listener = UnifyListener<IO>;
+
+    #[inline]
+    async fn connect(addr: &str) -> io::Result<Self> {
+        UnifyStream::<IO>::connect(addr).await
+    }
+}
+
 impl<IO: AsyncIO> fmt::Debug for UnifyListener<IO> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -841,6 +2210,15 @@ impl<IO: AsyncIO> fmt::Debug for UnifyListener<IO> {
     }
 }
 
+impl<IO: AsyncIO> AsRawFd for UnifyStream<IO> {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Self::Tcp(stream) => stream.as_raw_fd(),
+            Self::Unix(stream) => stream.as_raw_fd(),
+        }
+    }
+}
+
 impl<IO: AsyncIO> AsRawFd for UnifyListener<IO> {
     fn as_raw_fd(&self) -> RawFd {
         match self {
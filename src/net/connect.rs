@@ -0,0 +1,261 @@
+use super::{
+    AddrError, ResolveAddr, ResolveError, TcpStream, UnifyAddr, UnifyStream, UnixStream,
+    interleave_family, race_connect, resolve_error, resolve_tcp_addr, tcp_socket,
+};
+use crate::io::{AsyncIO, io_with_timeout};
+use crate::runtime::AsyncExec;
+use crate::time::AsyncTime;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// The options of [TcpStream::connect_with()] and [UnifyStream::connect_with()], reusable across
+/// the connections.
+///
+/// The default is the same as [TcpStream::connect()].
+///
+/// # Example
+///
+/// ```no_run
+/// use orb::prelude::*;
+/// use orb::net::{ConnectOptions, TcpStream};
+/// use std::time::Duration;
+///
+/// async fn dial<RT: AsyncRuntime>() -> std::io::Result<TcpStream<RT>> {
+///     let opts = ConnectOptions::new()
+///         .timeout(Duration::from_secs(3))
+///         .nodelay(true)
+///         .happy_eyeballs_delay(Duration::from_millis(250));
+///     TcpStream::<RT>::connect_with("example.com:80", &opts).await
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectOptions {
+    timeout: Duration,
+    nodelay: bool,
+    keepalive: Option<Duration>,
+    local_addr: Option<SocketAddr>,
+    happy_eyeballs_delay: Option<Duration>,
+}
+
+impl ConnectOptions {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The timeout of the whole connect including resolving, 0 for no timeout (the default).
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set TCP_NODELAY on the connected stream.
+    #[inline]
+    pub fn nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    /// Enable SO_KEEPALIVE on the connected stream, with the idle time before the probes.
+    #[inline]
+    pub fn keepalive(mut self, idle: Duration) -> Self {
+        self.keepalive = Some(idle);
+        self
+    }
+
+    /// Bind the local address before connecting, refer to [TcpStream::connect_from()].
+    ///
+    /// Only the resolved addresses of the same family are tried.
+    #[inline]
+    pub fn local_addr(mut self, local: SocketAddr) -> Self {
+        self.local_addr = Some(local);
+        self
+    }
+
+    /// Resolve all the addresses and race them with Happy Eyeballs, refer to
+    /// [TcpStream::connect_happy_eyeballs()]. Otherwise only the first address is tried.
+    #[inline]
+    pub fn happy_eyeballs_delay(mut self, delay: Duration) -> Self {
+        self.happy_eyeballs_delay = Some(delay);
+        self
+    }
+}
+
+impl<IO: AsyncIO> TcpStream<IO> {
+    /// Connect with the [ConnectOptions].
+    pub async fn connect_with<A>(addr: &A, opts: &ConnectOptions) -> io::Result<Self>
+    where
+        IO: AsyncExec + AsyncTime,
+        A: ResolveAddr + ?Sized,
+    {
+        io_with_timeout!(IO, opts.timeout, async {
+            let addrs = match opts.happy_eyeballs_delay {
+                Some(_) => socket_addrs(addr, addr.resolve_all::<IO>().await?)?,
+                None => vec![resolve_tcp_addr::<IO, A>(addr).await?],
+            };
+            Self::connect_addrs(addrs, opts).await
+        })
+    }
+
+    /// Connect to one of the resolved `addrs` with `opts`, except the timeout
+    async fn connect_addrs(mut addrs: Vec<SocketAddr>, opts: &ConnectOptions) -> io::Result<Self>
+    where
+        IO: AsyncTime,
+    {
+        if let Some(local) = opts.local_addr {
+            addrs.retain(|a| a.is_ipv4() == local.is_ipv4());
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("no address of the same family as local {}", local),
+                ));
+            }
+        }
+        let connect = |addr: SocketAddr| async move {
+            match opts.local_addr {
+                Some(local) => Self::connect_bound(local, &addr).await,
+                None => Ok(TcpStream { inner: IO::connect_tcp(&addr).await? }),
+            }
+        };
+        let stream = match opts.happy_eyeballs_delay {
+            Some(delay) => {
+                race_connect::<IO, _, _>(&interleave_family(addrs), delay, connect).await?
+            }
+            None => connect(addrs[0]).await?,
+        };
+        let socket = socket2::SockRef::from(&*stream.inner);
+        if opts.nodelay {
+            socket.set_tcp_nodelay(true)?;
+        }
+        if let Some(idle) = opts.keepalive {
+            socket.set_tcp_keepalive(&socket2::TcpKeepalive::new().with_time(idle))?;
+        }
+        Ok(stream)
+    }
+
+    /// Connect to `addr` from the `local` address
+    pub(super) async fn connect_bound(local: SocketAddr, addr: &SocketAddr) -> io::Result<Self> {
+        let socket = tcp_socket(addr, false)?;
+        socket.bind(&local.into())?;
+        Self::connect_with_socket(socket, addr).await
+    }
+}
+
+impl<IO: AsyncIO> UnifyStream<IO> {
+    /// Connect with the [ConnectOptions], the tcp options are ignored for the unix socket.
+    pub async fn connect_with<A>(addr: &A, opts: &ConnectOptions) -> io::Result<Self>
+    where
+        IO: AsyncExec + AsyncTime,
+        A: ResolveAddr + ?Sized,
+    {
+        io_with_timeout!(IO, opts.timeout, async {
+            let resolved = match opts.happy_eyeballs_delay {
+                Some(_) => addr.resolve_all::<IO>().await?,
+                None => vec![addr.resolve::<IO>().await.map_err(|e| resolve_error(addr, e))?],
+            };
+            match resolved.first() {
+                None => Err(resolve_error(addr, ResolveError::NoAddresses)),
+                Some(UnifyAddr::Socket(_)) => {
+                    let addrs = socket_addrs(addr, resolved)?;
+                    Ok(UnifyStream::Tcp(TcpStream::connect_addrs(addrs, opts).await?))
+                }
+                Some(UnifyAddr::Path(path)) => {
+                    let stream = IO::connect_unix(path).await?;
+                    Ok(UnifyStream::Unix(UnixStream { inner: stream }))
+                }
+                #[cfg(target_os = "linux")]
+                Some(UnifyAddr::Abstract(name)) => {
+                    Ok(UnifyStream::Unix(UnixStream::connect_abstract(name).await?))
+                }
+            }
+        })
+    }
+}
+
+/// The exponential backoff of [connect_retry()]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackoffPolicy {
+    /// The delay before the first retry
+    pub initial: Duration,
+    /// The upper bound of the delay
+    pub max: Duration,
+    /// The delay grows by it after each retry
+    pub multiplier: f64,
+    /// The retries after the first attempt, 0 for no retry
+    pub max_retries: u32,
+    /// Randomize each delay within +/- the ratio (0.0 to 1.0), so that the clients disconnected
+    /// together don't reconnect at the same time
+    pub jitter: f64,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(10),
+            multiplier: 2.0,
+            max_retries: 5,
+            jitter: 0.1,
+        }
+    }
+}
+
+impl BackoffPolicy {
+    /// The delay before the `retry`-th retry (starting from 0), never exceeds `max`
+    pub fn delay(&self, retry: u32) -> Duration {
+        let max = self.max.as_secs_f64();
+        let mut secs = self.initial.as_secs_f64() * self.multiplier.max(1.0).powi(retry as i32);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter > 0.0 {
+            // A random factor in [-1, 1), without the dependency of rand
+            let r = RandomState::new().hash_one(retry) as f64 / u64::MAX as f64 * 2.0 - 1.0;
+            secs *= 1.0 + jitter * r;
+        }
+        secs = secs.min(max);
+        // Overflows with the max near Duration::MAX
+        Duration::try_from_secs_f64(secs).unwrap_or(self.max)
+    }
+}
+
+/// Connect with [UnifyStream::connect()], retrying with the exponential backoff on failure.
+///
+/// Returns the last error after `max_retries` retries are exhausted. The `InvalidInput` error
+/// (malformed address etc.) is returned at once, as retrying won't help.
+pub async fn connect_retry<IO, A>(addr: &A, policy: BackoffPolicy) -> io::Result<UnifyStream<IO>>
+where
+    IO: AsyncIO + AsyncExec + AsyncTime,
+    A: ResolveAddr + ?Sized,
+{
+    let mut retry = 0;
+    loop {
+        match UnifyStream::<IO>::connect(addr).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) if retry >= policy.max_retries || e.kind() == io::ErrorKind::InvalidInput => {
+                return Err(e);
+            }
+            Err(_) => {}
+        }
+        IO::sleep(policy.delay(retry)).await;
+        retry += 1;
+    }
+}
+
+/// Take the socket addresses resolved from `addr` for tcp, which should not be empty
+fn socket_addrs<A: ResolveAddr + ?Sized>(
+    addr: &A, resolved: Vec<UnifyAddr>,
+) -> io::Result<Vec<SocketAddr>> {
+    if resolved.is_empty() {
+        return Err(resolve_error(addr, ResolveError::NoAddresses));
+    }
+    resolved
+        .into_iter()
+        .map(|a| match a {
+            UnifyAddr::Socket(a) => Ok(a),
+            _ => Err(AddrError::WrongScheme(format!("{:?}", addr)).into()),
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+use crate::io::{AsyncRead, AsyncWrite, io_with_timeout};
+use crate::time::AsyncTime;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// Fail the read with `TimedOut` when no data arrives within the idle window, against the
+/// slowloris-style clients.
+///
+/// The window restarts on each read, so a slow but steady peer is fine. The writes pass through.
+/// Zero idle means no timeout, like the other `*_timeout()` of the crate.
+/// Works over any stream, and composes with [AsyncBufStream](crate::io::AsyncBufStream).
+///
+/// NOTE: The read of the inner stream is dropped on timeout, so it should be cancel safe (the
+/// sockets are).
+pub struct IdleTimeout<S, IO> {
+    inner: S,
+    idle: Duration,
+    _phan: PhantomData<fn() -> IO>,
+}
+
+impl<S, IO: AsyncTime> IdleTimeout<S, IO> {
+    #[inline]
+    pub fn new(inner: S, idle: Duration) -> Self {
+        Self { inner, idle, _phan: PhantomData }
+    }
+
+    #[inline]
+    pub fn idle(&self) -> Duration {
+        self.idle
+    }
+
+    #[inline]
+    pub fn set_idle(&mut self, idle: Duration) {
+        self.idle = idle;
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: AsyncRead, IO: AsyncTime> AsyncRead for IdleTimeout<S, IO> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        io_with_timeout!(IO, self.idle, self.inner.read(buf))
+    }
+}
+
+impl<S: AsyncWrite, IO: AsyncTime> AsyncWrite for IdleTimeout<S, IO> {
+    #[inline]
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf).await
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush().await
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.inner.shutdown().await
+    }
+}
+
+impl<S: fmt::Debug, IO> fmt::Debug for IdleTimeout<S, IO> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "IdleTimeout({:?}, {:?})", self.inner, self.idle)
+    }
+}
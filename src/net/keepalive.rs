@@ -0,0 +1,258 @@
+use super::UnifyStream;
+use crate::io::{AsyncIO, AsyncRead, AsyncWrite};
+use crate::runtime::{AsyncExec, AsyncHandle};
+use crate::time::{AsyncTime, TimeInterval};
+use crate::utils::sync::Mutex as AsyncMutex;
+use futures_lite::future::poll_fn;
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+impl<IO: AsyncIO> UnifyStream<IO> {
+    /// Check the liveness on the application layer, refer to [AppKeepalive].
+    ///
+    /// # Parameters
+    ///
+    /// * `rt` - The runtime to spawn the pinger task
+    /// * `ping` - The frame to send when idle, must not be empty
+    /// * `period` - The interval to send ping
+    /// * `timeout` - Close the connection if nothing received within it, should be larger than
+    ///   `period`
+    pub fn with_app_keepalive(
+        self, rt: &IO, ping: Vec<u8>, period: Duration, timeout: Duration,
+    ) -> AppKeepalive<IO>
+    where
+        IO: AsyncExec + AsyncTime,
+    {
+        assert!(!ping.is_empty(), "ping must not be empty");
+        let shared = Arc::new(Shared {
+            stream: self,
+            write_lock: AsyncMutex::new(()),
+            state: Mutex::new(State { last_recv: IO::now(), written: false, timed_out: false }),
+        });
+        let pinger = rt.spawn(pinger(shared.clone(), ping.clone(), IO::tick(period), timeout));
+        AppKeepalive {
+            shared,
+            ping,
+            pinger: Some(pinger),
+            matched: 0,
+            read_buf: Vec::new(),
+            pending: Vec::new(),
+            pending_pos: 0,
+        }
+    }
+}
+
+struct State {
+    last_recv: Instant,
+    // Written by the user since the last tick
+    written: bool,
+    timed_out: bool,
+}
+
+struct Shared<IO: AsyncIO> {
+    stream: UnifyStream<IO>,
+    // Keep the ping from splitting a write of the user
+    write_lock: AsyncMutex<()>,
+    state: Mutex<State>,
+}
+
+impl<IO: AsyncIO> Shared<IO> {
+    #[inline]
+    fn timed_out(&self) -> bool {
+        self.state.lock().unwrap().timed_out
+    }
+
+    /// Shutdown both directions, the pending read / write of the user returns at once
+    fn close(&self) {
+        self.state.lock().unwrap().timed_out = true;
+        let _ = match &self.stream {
+            UnifyStream::Tcp(stream) => stream.inner.shutdown(std::net::Shutdown::Both),
+            UnifyStream::Unix(stream) => stream.inner.shutdown(std::net::Shutdown::Both),
+        };
+    }
+}
+
+/// The companion task sends the ping when idle, and closes the connection on timeout
+async fn pinger<IO: AsyncIO + AsyncTime>(
+    shared: Arc<Shared<IO>>, ping: Vec<u8>, mut interval: IO::Interval, timeout: Duration,
+) {
+    loop {
+        poll_fn(|cx| Pin::new(&mut interval).poll_tick(cx)).await;
+        let (idle, written) = {
+            let mut state = shared.state.lock().unwrap();
+            let written = std::mem::replace(&mut state.written, false);
+            (IO::now().saturating_duration_since(state.last_recv), written)
+        };
+        if idle >= timeout {
+            shared.close();
+            return;
+        }
+        if written {
+            continue;
+        }
+        // Skip when the user is writing, which also tells the peer we're alive
+        let Some(_guard) = shared.write_lock.try_lock() else {
+            continue;
+        };
+        // The peer not reading should not stall the timeout
+        let mut stream = &shared.stream;
+        match IO::timeout(timeout - idle, stream.write_all(&ping)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(_)) => return,
+            Err(()) => {
+                shared.close();
+                return;
+            }
+        }
+    }
+}
+
+/// A stream with liveness check on the application layer, returned by
+/// [UnifyStream::with_app_keepalive()].
+///
+/// Both sides are expected to wrap the stream with the same `ping`. A companion task ticks on
+/// `period`, sends the ping if nothing is written since the last tick, and shuts down the
+/// connection when nothing is received within `timeout`, whether the stream is being read or
+/// not. Then the reads / writes return `ErrorKind::TimedOut`. The ping received is stripped
+/// from the read stream.
+///
+/// The timeout is checked with the granularity of `period`. The companion task is aborted on
+/// drop.
+///
+/// The ping might land between two writes of the user (not inside one), and it is stripped
+/// wherever it occurs, so choose a byte sequence which never appears in the payload.
+pub struct AppKeepalive<IO: AsyncIO + AsyncExec + AsyncTime> {
+    shared: Arc<Shared<IO>>,
+    ping: Vec<u8>,
+    pinger: Option<IO::AsyncHandle<()>>,
+    matched: usize,
+    read_buf: Vec<u8>,
+    pending: Vec<u8>,
+    pending_pos: usize,
+}
+
+impl<IO: AsyncIO + AsyncExec + AsyncTime> AppKeepalive<IO> {
+    #[inline]
+    pub fn get_ref(&self) -> &UnifyStream<IO> {
+        &self.shared.stream
+    }
+
+    #[inline]
+    fn check_timeout(&self) -> io::Result<()> {
+        if self.shared.timed_out() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "keepalive timeout"));
+        }
+        Ok(())
+    }
+}
+
+impl<IO: AsyncIO + AsyncExec + AsyncTime> AsyncRead for AppKeepalive<IO> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pending_pos < self.pending.len() {
+                let n = std::cmp::min(buf.len(), self.pending.len() - self.pending_pos);
+                buf[..n].copy_from_slice(&self.pending[self.pending_pos..self.pending_pos + n]);
+                self.pending_pos += n;
+                return Ok(n);
+            }
+            self.check_timeout()?;
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            if self.read_buf.len() < buf.len() {
+                self.read_buf.resize(buf.len(), 0);
+            }
+            let mut stream = &self.shared.stream;
+            let r = stream.read(&mut self.read_buf[..buf.len()]).await;
+            // The shutdown on timeout wakes up the read
+            self.check_timeout()?;
+            match r? {
+                0 => {
+                    // Not a ping at EOF
+                    self.pending.clear();
+                    self.pending.extend_from_slice(&self.ping[..self.matched]);
+                    self.pending_pos = 0;
+                    self.matched = 0;
+                    if self.pending.is_empty() {
+                        return Ok(0);
+                    }
+                }
+                n => {
+                    self.shared.state.lock().unwrap().last_recv = IO::now();
+                    self.pending.clear();
+                    self.pending_pos = 0;
+                    strip_pattern(
+                        &self.ping,
+                        &mut self.matched,
+                        &self.read_buf[..n],
+                        &mut self.pending,
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl<IO: AsyncIO + AsyncExec + AsyncTime> AsyncWrite for AppKeepalive<IO> {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_timeout()?;
+        let _guard = self.shared.write_lock.lock().await;
+        let mut stream = &self.shared.stream;
+        let r = stream.write(buf).await;
+        self.check_timeout()?;
+        self.shared.state.lock().unwrap().written = true;
+        r
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        let _guard = self.shared.write_lock.lock().await;
+        let mut stream = &self.shared.stream;
+        stream.shutdown().await
+    }
+}
+
+impl<IO: AsyncIO + AsyncExec + AsyncTime> Drop for AppKeepalive<IO> {
+    fn drop(&mut self) {
+        if let Some(pinger) = self.pinger.take() {
+            pinger.abort();
+        }
+    }
+}
+
+impl<IO: AsyncIO + AsyncExec + AsyncTime> fmt::Debug for AppKeepalive<IO> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.shared.stream.fmt(f)
+    }
+}
+
+/// Strip the occurrences of `pattern` from `data` into `out`.
+///
+/// `matched` is the length of pattern prefix at the end of previous data, which is withheld
+/// until the match is decided.
+fn strip_pattern(pattern: &[u8], matched: &mut usize, data: &[u8], out: &mut Vec<u8>) {
+    for &b in data {
+        loop {
+            if b == pattern[*matched] {
+                *matched += 1;
+                if *matched == pattern.len() {
+                    *matched = 0;
+                }
+                break;
+            }
+            if *matched == 0 {
+                out.push(b);
+                break;
+            }
+            // Mismatch, the first withheld byte is data, match the rest again
+            let withheld = *matched;
+            out.push(pattern[0]);
+            *matched = 0;
+            strip_pattern(pattern, matched, &pattern[1..withheld], out);
+        }
+    }
+}
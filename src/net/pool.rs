@@ -0,0 +1,328 @@
+//! A connection pool of [UnifyStream], keyed by the address.
+//!
+//! [Pool::get()] reuses an idle connection to the address, or connects a new one. The
+//! [PooledConn] returns to the pool on drop if still healthy, so the client code of a request /
+//! response protocol is written as if connecting every time.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use orb::prelude::*;
+//! use orb::net::pool::{Pool, PoolConfig};
+//!
+//! async fn call<RT: AsyncRuntime>(pool: &Pool<RT>) -> std::io::Result<()> {
+//!     let mut conn = pool.get("127.0.0.1:8080").await?;
+//!     conn.write_all(b"ping").await?;
+//!     let mut buf = [0u8; 4];
+//!     conn.read_exact(&mut buf).await?;
+//!     // back to the pool on drop
+//!     Ok(())
+//! }
+//! ```
+
+use super::{ResolveAddr, UnifyAddr, UnifyStream, resolve_error};
+use crate::io::{AsyncIO, AsyncRead, AsyncWrite};
+use crate::runtime::AsyncExec;
+use crate::time::{AsyncTime, TimeInterval};
+use crate::utils::sync::{OwnedPermit, Semaphore};
+use futures_lite::future::poll_fn;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::os::fd::AsRawFd;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// The permits of the host when `max_per_host` is 0
+const UNLIMITED: usize = usize::MAX >> 3;
+
+/// The options of [Pool]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// The max connections to one address, including the ones in use and idle, 0 for unlimited.
+    /// [Pool::get()] waits when reaching the limit.
+    pub max_per_host: usize,
+    /// Close the connections idle longer than it
+    pub idle_timeout: Duration,
+    /// The timeout of connecting, 0 for no timeout
+    pub connect_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_per_host: 16,
+            idle_timeout: Duration::from_secs(90),
+            connect_timeout: Duration::from_secs(0),
+        }
+    }
+}
+
+struct Idle<IO: AsyncIO> {
+    stream: UnifyStream<IO>,
+    since: Instant,
+}
+
+struct Host<IO: AsyncIO> {
+    // The most recently used is at the end
+    idle: Vec<Idle<IO>>,
+    limit: Arc<Semaphore>,
+}
+
+struct Shared<IO: AsyncIO> {
+    hosts: Mutex<HashMap<UnifyAddr, Host<IO>>>,
+    config: PoolConfig,
+    // The clock of the runtime, for the Drop of PooledConn which is not bounded by AsyncTime
+    now: fn() -> Instant,
+}
+
+impl<IO: AsyncIO> Shared<IO> {
+    /// Close the connections idle too long, and remove the hosts not in use
+    fn evict(&self, now: Instant) {
+        let mut expired = Vec::new();
+        {
+            let mut hosts = self.hosts.lock().unwrap();
+            hosts.retain(|_, host| {
+                let mut i = 0;
+                while i < host.idle.len() {
+                    if now.saturating_duration_since(host.idle[i].since) >= self.config.idle_timeout
+                    {
+                        expired.push(host.idle.remove(i));
+                    } else {
+                        i += 1;
+                    }
+                }
+                // No one holds or waits for the permits
+                !host.idle.is_empty() || Arc::strong_count(&host.limit) > 1
+            });
+        }
+        // Close outside the lock
+        drop(expired);
+    }
+
+    fn pop_idle(&self, addr: &UnifyAddr) -> Option<Idle<IO>> {
+        self.hosts.lock().unwrap().get_mut(addr)?.idle.pop()
+    }
+}
+
+/// A connection pool of [UnifyStream], keyed by the address.
+///
+/// Idle connections are closed after `idle_timeout` by a background task, which exits when the
+/// pool (and all its clones) is dropped.
+pub struct Pool<IO: AsyncIO> {
+    shared: Arc<Shared<IO>>,
+}
+
+impl<IO: AsyncIO + AsyncExec + AsyncTime> Pool<IO> {
+    pub fn new(rt: &IO, config: PoolConfig) -> Self {
+        let period = (config.idle_timeout / 2).max(Duration::from_millis(1));
+        let shared = Arc::new(Shared { hosts: Mutex::new(HashMap::new()), config, now: IO::now });
+        rt.spawn_detach(Self::evict_loop(Arc::downgrade(&shared), period));
+        Self { shared }
+    }
+
+    async fn evict_loop(shared: Weak<Shared<IO>>, period: Duration) {
+        let mut interval = IO::tick(period);
+        loop {
+            poll_fn(|cx| Pin::new(&mut interval).poll_tick(cx)).await;
+            match shared.upgrade() {
+                Some(shared) => shared.evict(IO::now()),
+                None => return,
+            }
+        }
+    }
+
+    /// Get a connection to `addr`, reusing the idle one if any, otherwise connect.
+    ///
+    /// Waits when the connections to `addr` reaches `max_per_host`. The future is cancel safe.
+    pub async fn get<A: ResolveAddr + ?Sized>(&self, addr: &A) -> io::Result<PooledConn<IO>> {
+        let key = match addr.resolve::<IO>().await {
+            Ok(key) => key,
+            Err(e) => return Err(resolve_error(addr, e)),
+        };
+        let limit = {
+            let mut hosts = self.shared.hosts.lock().unwrap();
+            let max = match self.shared.config.max_per_host {
+                0 => UNLIMITED,
+                n => n,
+            };
+            let host = hosts
+                .entry(key.clone())
+                .or_insert_with(|| Host { idle: Vec::new(), limit: Arc::new(Semaphore::new(max)) });
+            host.limit.clone()
+        };
+        let permit = limit.acquire_owned().await;
+        let now = IO::now();
+        while let Some(idle) = self.shared.pop_idle(&key) {
+            if now.saturating_duration_since(idle.since) < self.shared.config.idle_timeout
+                && is_healthy(&idle.stream)
+            {
+                return Ok(PooledConn::new(self, key, idle.stream, permit, true));
+            }
+        }
+        let timeout = self.shared.config.connect_timeout;
+        let stream = UnifyStream::<IO>::connect_timeout(&key, timeout).await?;
+        Ok(PooledConn::new(self, key, stream, permit, false))
+    }
+}
+
+impl<IO: AsyncIO> Pool<IO> {
+    /// The number of the idle connections of all the addresses
+    pub fn idle_count(&self) -> usize {
+        self.shared.hosts.lock().unwrap().values().map(|host| host.idle.len()).sum()
+    }
+
+    /// Close all the idle connections
+    pub fn clear(&self) {
+        let mut idle = Vec::new();
+        for host in self.shared.hosts.lock().unwrap().values_mut() {
+            idle.append(&mut host.idle);
+        }
+        drop(idle);
+    }
+}
+
+impl<IO: AsyncIO> Clone for Pool<IO> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<IO: AsyncIO> fmt::Debug for Pool<IO> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Pool(idle={})", self.idle_count())
+    }
+}
+
+/// An idle connection is healthy when there's nothing to read, otherwise it's closed by the
+/// peer, or has unexpected data.
+fn is_healthy<IO: AsyncIO>(stream: &UnifyStream<IO>) -> bool {
+    let mut b = 0u8;
+    let r = unsafe {
+        libc::recv(
+            stream.as_raw_fd(),
+            &mut b as *mut u8 as *mut libc::c_void,
+            1,
+            libc::MSG_PEEK | libc::MSG_DONTWAIT,
+        )
+    };
+    r < 0 && io::Error::last_os_error().kind() == io::ErrorKind::WouldBlock
+}
+
+/// The connection from [Pool::get()], returns to the pool on drop if still healthy.
+///
+/// The connection is not returned after an I/O error or EOF through it. Call
+/// [discard()](Self::discard) when it's left in an unknown state of the protocol (for example, the
+/// response is not read completely).
+pub struct PooledConn<IO: AsyncIO> {
+    stream: Option<UnifyStream<IO>>,
+    key: UnifyAddr,
+    shared: Arc<Shared<IO>>,
+    reused: bool,
+    broken: bool,
+    // Released after the stream is returned
+    _permit: OwnedPermit,
+}
+
+impl<IO: AsyncIO> PooledConn<IO> {
+    fn new(
+        pool: &Pool<IO>, key: UnifyAddr, stream: UnifyStream<IO>, permit: OwnedPermit, reused: bool,
+    ) -> Self {
+        Self {
+            stream: Some(stream),
+            key,
+            shared: pool.shared.clone(),
+            reused,
+            broken: false,
+            _permit: permit,
+        }
+    }
+
+    /// Whether it is an idle connection reused
+    #[inline]
+    pub fn is_reused(&self) -> bool {
+        self.reused
+    }
+
+    /// The address connected
+    #[inline]
+    pub fn addr(&self) -> &UnifyAddr {
+        &self.key
+    }
+
+    /// Close the connection instead of returning to the pool
+    #[inline]
+    pub fn discard(mut self) {
+        self.broken = true;
+    }
+
+    /// Take the connection out of the pool
+    #[inline]
+    pub fn into_inner(mut self) -> UnifyStream<IO> {
+        self.stream.take().unwrap()
+    }
+}
+
+impl<IO: AsyncIO> Deref for PooledConn<IO> {
+    type Target = UnifyStream<IO>;
+
+    #[inline]
+    fn deref(&self) -> &UnifyStream<IO> {
+        self.stream.as_ref().unwrap()
+    }
+}
+
+impl<IO: AsyncIO> DerefMut for PooledConn<IO> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut UnifyStream<IO> {
+        self.stream.as_mut().unwrap()
+    }
+}
+
+impl<IO: AsyncIO> AsyncRead for PooledConn<IO> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let r = self.stream.as_mut().unwrap().read(buf).await;
+        if !matches!(r, Ok(n) if n > 0 || buf.is_empty()) {
+            self.broken = true;
+        }
+        r
+    }
+}
+
+impl<IO: AsyncIO> AsyncWrite for PooledConn<IO> {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let r = self.stream.as_mut().unwrap().write(buf).await;
+        if r.is_err() {
+            self.broken = true;
+        }
+        r
+    }
+
+    /// The connection is not returned to the pool after shutdown
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.broken = true;
+        self.stream.as_mut().unwrap().shutdown().await
+    }
+}
+
+impl<IO: AsyncIO> Drop for PooledConn<IO> {
+    fn drop(&mut self) {
+        let Some(stream) = self.stream.take() else { return };
+        if self.broken || !is_healthy(&stream) {
+            return;
+        }
+        let idle = Idle { stream, since: (self.shared.now)() };
+        if let Some(host) = self.shared.hosts.lock().unwrap().get_mut(&self.key) {
+            host.idle.push(idle);
+        }
+    }
+}
+
+impl<IO: AsyncIO> fmt::Debug for PooledConn<IO> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PooledConn({})", self.key)
+    }
+}
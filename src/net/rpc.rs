@@ -0,0 +1,232 @@
+//! Request / response correlation over a single connection.
+//!
+//! [Client] allows concurrent calls on one connection, each request is tagged with an id, and the
+//! server replies with the same id, in any order.
+//!
+//! The frame on the wire is `[id: u64][len: u32][payload]`, in big endian. Use [read_frame()] and
+//! [write_frame()] to implement the server side.
+
+use crate::io::{AsyncRead, AsyncWrite};
+use crate::runtime::AsyncExec;
+use crate::utils::sync::{WaitList, poll_once};
+use crate::utils::{Either, select2};
+use std::collections::HashMap;
+use std::io;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The length of the frame header: id (u64) + payload length (u32)
+pub const HEADER_LEN: usize = 12;
+
+/// The limit of the payload length, the larger frames are rejected as InvalidData
+pub const MAX_PAYLOAD: usize = 16 * 1024 * 1024;
+
+/// Read a frame, returns (id, payload)
+pub async fn read_frame<R: AsyncRead>(reader: &mut R) -> io::Result<(u64, Vec<u8>)> {
+    let mut header = [0u8; HEADER_LEN];
+    reader.read_exact(&mut header).await?;
+    let (id, len) = parse_header(&header)?;
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await?;
+    Ok((id, payload))
+}
+
+/// Write a frame of `id` and `payload`
+pub async fn write_frame<W: AsyncWrite>(writer: &mut W, id: u64, payload: &[u8]) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(HEADER_LEN + payload.len());
+    encode_frame(&mut buf, id, payload)?;
+    writer.write_all(&buf).await
+}
+
+fn encode_frame(buf: &mut Vec<u8>, id: u64, payload: &[u8]) -> io::Result<()> {
+    if payload.len() > MAX_PAYLOAD {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "payload too large"));
+    }
+    buf.extend_from_slice(&id.to_be_bytes());
+    buf.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    buf.extend_from_slice(payload);
+    Ok(())
+}
+
+fn parse_header(header: &[u8]) -> io::Result<(u64, usize)> {
+    let id = u64::from_be_bytes(header[0..8].try_into().unwrap());
+    let len = u32::from_be_bytes(header[8..12].try_into().unwrap()) as usize;
+    if len > MAX_PAYLOAD {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "payload too large"));
+    }
+    Ok((id, len))
+}
+
+/// The response slot of a call
+#[derive(Default)]
+struct Slot {
+    result: Mutex<Option<io::Result<Vec<u8>>>>,
+    ready: WaitList,
+}
+
+impl Slot {
+    fn set(&self, r: io::Result<Vec<u8>>) {
+        *self.result.lock().unwrap() = Some(r);
+        self.ready.notify_all();
+    }
+
+    async fn wait(&self) -> io::Result<Vec<u8>> {
+        loop {
+            let mut listen = self.ready.listen();
+            poll_once(&mut listen).await;
+            if let Some(r) = self.result.lock().unwrap().take() {
+                return r;
+            }
+            listen.await;
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    pending: HashMap<u64, Arc<Slot>>,
+    // Encoded frames to write
+    outgoing: Vec<u8>,
+    closed: bool,
+    err: Option<io::ErrorKind>,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    next_id: AtomicU64,
+    // Notify the background task there's data to write, or closed
+    wake: WaitList,
+}
+
+impl Shared {
+    /// Fail all the pending calls, and the calls afterwards
+    fn fail(&self, kind: io::ErrorKind) {
+        let pending = {
+            let mut state = self.state.lock().unwrap();
+            state.err.get_or_insert(kind);
+            state.closed = true;
+            std::mem::take(&mut state.pending)
+        };
+        for slot in pending.values() {
+            slot.set(Err(kind.into()));
+        }
+    }
+}
+
+/// Remove the slot when the call is cancelled
+struct PendingGuard<'a> {
+    shared: &'a Shared,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().pending.remove(&self.id);
+    }
+}
+
+/// A RPC client multiplexing the concurrent calls over one connection.
+///
+/// A background task owns the stream, it writes the requests, and dispatches the responses to the
+/// callers by id. When the connection fails, all the pending calls and the calls afterwards
+/// return the error. Dropping the client stops the background task.
+///
+/// # Type Parameters
+///
+/// * `S` - The stream, owned by the background task
+pub struct Client<S> {
+    shared: Arc<Shared>,
+    _phan: PhantomData<fn(S)>,
+}
+
+impl<S: AsyncRead + AsyncWrite + 'static> Client<S> {
+    pub fn new<RT: AsyncExec>(rt: &RT, stream: S) -> Self {
+        let shared = Arc::new(Shared::default());
+        rt.spawn_detach(Self::run(shared.clone(), stream));
+        Self { shared, _phan: Default::default() }
+    }
+
+    /// Send the request and wait for its response.
+    ///
+    /// Cancel safe, the late response of a cancelled call is discarded.
+    pub async fn call(&self, payload: Vec<u8>) -> io::Result<Vec<u8>> {
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        let slot = Arc::new(Slot::default());
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            if let Some(kind) = state.err {
+                return Err(kind.into());
+            }
+            if state.closed {
+                return Err(io::ErrorKind::NotConnected.into());
+            }
+            encode_frame(&mut state.outgoing, id, &payload)?;
+            state.pending.insert(id, slot.clone());
+        }
+        let _guard = PendingGuard { shared: &self.shared, id };
+        self.shared.wake.notify_one();
+        slot.wait().await
+    }
+
+    async fn run(shared: Arc<Shared>, mut stream: S) {
+        let r = Self::io_loop(&shared, &mut stream).await;
+        shared.fail(match r {
+            Ok(()) => io::ErrorKind::NotConnected,
+            Err(e) => e.kind(),
+        });
+    }
+
+    async fn io_loop(shared: &Shared, stream: &mut S) -> io::Result<()> {
+        let mut buf = Vec::new();
+        let mut chunk = vec![0u8; 8192];
+        let mut out = Vec::new();
+        loop {
+            let mut listen = shared.wake.listen();
+            poll_once(&mut listen).await;
+            {
+                let mut state = shared.state.lock().unwrap();
+                if state.closed {
+                    return Ok(());
+                }
+                std::mem::swap(&mut state.outgoing, &mut out);
+            }
+            if !out.is_empty() {
+                stream.write_all(&out).await?;
+                out.clear();
+                continue;
+            }
+            // read() is cancel safe, the partial frame is kept in buf
+            let n = match select2(stream.read(&mut chunk), listen).await {
+                Either::Left(r) => r?,
+                Either::Right(()) => continue,
+            };
+            if n == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            buf.extend_from_slice(&chunk[..n]);
+            let mut parsed = 0;
+            while buf.len() - parsed >= HEADER_LEN {
+                let (id, len) = parse_header(&buf[parsed..])?;
+                let end = parsed + HEADER_LEN + len;
+                if buf.len() < end {
+                    break;
+                }
+                let slot = shared.state.lock().unwrap().pending.remove(&id);
+                if let Some(slot) = slot {
+                    slot.set(Ok(buf[parsed + HEADER_LEN..end].to_vec()));
+                }
+                parsed = end;
+            }
+            buf.drain(..parsed);
+        }
+    }
+}
+
+impl<S> Drop for Client<S> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.wake.notify_one();
+    }
+}
@@ -0,0 +1,55 @@
+//! The hook to layer TLS over orb streams.
+//!
+//! Orb does not depend on any TLS library, the implementation is provided by other crates
+//! (like `orb-rustls`), through the [TlsUpgrade] trait. So the code can be written against the
+//! trait without choosing a TLS library.
+
+use crate::io::{AsyncRead, AsyncWrite};
+use std::future::Future;
+use std::io;
+
+/// The parameters of the client handshake
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsClientConfig {
+    /// The server name for SNI and certificate verification
+    pub server_name: String,
+    /// The ALPN protocols to offer (e.g. `b"h2"`, `b"http/1.1"`), in order of preference. Empty
+    /// to skip ALPN.
+    pub alpn: Vec<Vec<u8>>,
+}
+
+impl TlsClientConfig {
+    #[inline]
+    pub fn new(server_name: impl Into<String>) -> Self {
+        Self { server_name: server_name.into(), alpn: Vec::new() }
+    }
+
+    /// Add a protocol to offer in ALPN
+    #[inline]
+    pub fn alpn(mut self, protocol: impl Into<Vec<u8>>) -> Self {
+        self.alpn.push(protocol.into());
+        self
+    }
+}
+
+/// The stream after the TLS handshake
+pub trait TlsStream: AsyncRead + AsyncWrite {
+    /// The protocol agreed by ALPN, None if not negotiated
+    fn negotiated_alpn(&self) -> Option<Vec<u8>>;
+}
+
+/// Upgrade a plain stream into TLS, with the configuration held by the implementation (the
+/// certificates, the trusted roots and the server side ALPN protocols).
+pub trait TlsUpgrade: Send + Sync + 'static {
+    type Stream<S: AsyncRead + AsyncWrite + 'static>: TlsStream;
+
+    /// Handshake as client
+    fn connect<S: AsyncRead + AsyncWrite + 'static>(
+        &self, config: &TlsClientConfig, stream: S,
+    ) -> impl Future<Output = io::Result<Self::Stream<S>>> + Send;
+
+    /// Handshake as server
+    fn accept<S: AsyncRead + AsyncWrite + 'static>(
+        &self, stream: S,
+    ) -> impl Future<Output = io::Result<Self::Stream<S>>> + Send;
+}
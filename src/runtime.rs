@@ -3,7 +3,24 @@
 //! This module defines the interface for spawning, executing, and managing
 //! asynchronous tasks across different runtime implementations.
 //!
+use crate::time::AsyncTime;
+use std::any::Any;
+use std::fmt;
 use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+mod metrics;
+pub use metrics::{Metered, Metrics, MetricsSnapshot};
+mod profile;
+pub use profile::{ProfiledHandle, TaskProfile};
+mod abort_on_drop;
+pub use abort_on_drop::AbortOnDrop;
+mod blocking_pool;
+pub use blocking_pool::{BlockingPool, PoolThreadHandle};
+mod started;
+pub use started::StartedSignal;
 
 /// Trait for async runtime execution capabilities.
 ///
@@ -69,6 +86,69 @@ pub trait AsyncExec: Send + Sync + 'static {
         F: Future<Output = R> + Send + 'static,
         R: Send + 'static;
 
+    /// Spawn a task with a name, for debugging tools like tokio-console.
+    ///
+    /// The naming is best-effort, the default implementation ignores the name and calls
+    /// [spawn()](Self::spawn). The runtime adapter may override it when supported.
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - The name of the task
+    /// * `f` - The future to spawn
+    #[inline(always)]
+    fn spawn_named<F, R>(&self, name: &str, f: F) -> Self::AsyncHandle<R>
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let _ = name;
+        self.spawn(f)
+    }
+
+    /// Spawn a task which records the time spent polling and idle, for diagnosis of scheduling
+    /// delay.
+    ///
+    /// The returned [ProfiledHandle] provides [profile()](ProfiledHandle::profile), and can be
+    /// used as the normal handle. The accounting is done by wrapping the future, with the cost of
+    /// reading the clock twice on each poll.
+    #[inline]
+    fn spawn_profiled<F, R>(&self, f: F) -> ProfiledHandle<Self::AsyncHandle<R>>
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (f, reader) = profile::Profiled::new(f);
+        ProfiledHandle::new(self.spawn(f), reader)
+    }
+
+    /// Spawn a task, with a [StartedSignal] to wait until the task begins to run (the first poll).
+    ///
+    /// Avoids the race of "spawned but not yet scheduled", e.g. in benchmarks or tests, without
+    /// guessing with sleep.
+    #[inline]
+    fn spawn_started<F, R>(&self, f: F) -> (Self::AsyncHandle<R>, StartedSignal)
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (f, signal) = started::NotifyStart::new(f);
+        (self.spawn(f), signal)
+    }
+
+    /// Spawn a task which is aborted when the handle is dropped, for request-scoped work.
+    ///
+    /// Unlike the handle from [spawn()](Self::spawn) which detaches on drop, the returned
+    /// [AbortOnDrop] calls [abort()](AsyncHandle::abort) in drop, unless
+    /// [disarm()](AbortOnDrop::disarm) is called.
+    #[inline]
+    fn spawn_abortable<F, R>(&self, f: F) -> AbortOnDrop<Self::AsyncHandle<R>>
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        AbortOnDrop::new(self.spawn(f))
+    }
+
     /// Spawn a task and detach it (no handle returned).
     ///
     /// This method creates a new task that runs in the background without
@@ -120,6 +200,30 @@ pub trait AsyncExec: Send + Sync + 'static {
         F: FnOnce() -> R + Send + 'static,
         R: Send + 'static;
 
+    /// Same as [spawn_blocking()](Self::spawn_blocking), with a name for diagnosis.
+    ///
+    /// The naming is best-effort, the default implementation ignores the name.
+    #[inline(always)]
+    fn spawn_blocking_named<F, R>(name: &str, f: F) -> Self::ThreadHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let _ = name;
+        Self::spawn_blocking(f)
+    }
+
+    /// Run blocking code in the dedicated [BlockingPool] instead of the shared pool of the
+    /// runtime, to prevent the slow tasks from blocking the others.
+    #[inline(always)]
+    fn spawn_blocking_on<F, R>(&self, pool: &BlockingPool, f: F) -> PoolThreadHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        pool.spawn(f)
+    }
+
     /// Run a future to completion on the runtime.
     ///
     /// This method blocks the current thread until the provided future
@@ -141,6 +245,51 @@ pub trait AsyncExec: Send + Sync + 'static {
     where
         F: Future<Output = R> + Send,
         R: Send + 'static;
+
+    /// Same as [block_on()](Self::block_on), but allows `!Send` future (e.g. holding a `Rc`).
+    ///
+    /// The future runs on the current thread and never moves to other threads, even for a multi
+    /// threaded runtime (only the spawned tasks run on the workers). Which is fine as long as the
+    /// `!Send` values are not passed to [spawn()](Self::spawn).
+    ///
+    /// The default implementation polls `f` with `futures_lite::future::block_on()`, without
+    /// entering the context of the runtime, so the reactor and timers of the runtime might not be
+    /// reachable from `f`. The runtime adapters override it.
+    #[inline]
+    fn block_on_local<F, R>(&self, f: F) -> R
+    where
+        F: Future<Output = R>,
+    {
+        futures_lite::future::block_on(f)
+    }
+
+    /// The number of worker threads, for library code to default its parallelism.
+    ///
+    /// Returns None if unknown (the default implementation).
+    #[inline(always)]
+    fn worker_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// The scheduling model of the runtime, [RuntimeFlavor::Unknown] by default.
+    #[inline(always)]
+    fn flavor(&self) -> RuntimeFlavor {
+        RuntimeFlavor::Unknown
+    }
+}
+
+/// The scheduling model of the runtime, returned by [AsyncExec::flavor()]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RuntimeFlavor {
+    /// Tasks run on a pool of worker threads
+    MultiThread,
+    /// Tasks run on the thread calling `block_on()`
+    CurrentThread,
+    /// Tasks run on a process-wide executor, (e.g. smol global executor)
+    Global,
+    /// Not reported by the runtime
+    Unknown,
 }
 
 impl<FT: std::ops::Deref<Target = T> + Send + Sync + 'static, T: AsyncExec> AsyncExec for FT {
@@ -157,6 +306,15 @@ impl<FT: std::ops::Deref<Target = T> + Send + Sync + 'static, T: AsyncExec> Asyn
         T::spawn(self.deref(), f)
     }
 
+    #[inline(always)]
+    fn spawn_named<F, R>(&self, name: &str, f: F) -> Self::AsyncHandle<R>
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        T::spawn_named(self.deref(), name, f)
+    }
+
     #[inline(always)]
     fn spawn_detach<F, R>(&self, f: F)
     where
@@ -175,6 +333,15 @@ impl<FT: std::ops::Deref<Target = T> + Send + Sync + 'static, T: AsyncExec> Asyn
         T::spawn_blocking(f)
     }
 
+    #[inline(always)]
+    fn spawn_blocking_named<F, R>(name: &str, f: F) -> Self::ThreadHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        T::spawn_blocking_named(name, f)
+    }
+
     #[inline(always)]
     fn block_on<F, R>(&self, f: F) -> R
     where
@@ -183,6 +350,24 @@ impl<FT: std::ops::Deref<Target = T> + Send + Sync + 'static, T: AsyncExec> Asyn
     {
         T::block_on(self, f)
     }
+
+    #[inline(always)]
+    fn block_on_local<F, R>(&self, f: F) -> R
+    where
+        F: Future<Output = R>,
+    {
+        T::block_on_local(self, f)
+    }
+
+    #[inline(always)]
+    fn worker_count(&self) -> Option<usize> {
+        T::worker_count(self.deref())
+    }
+
+    #[inline(always)]
+    fn flavor(&self) -> RuntimeFlavor {
+        T::flavor(self.deref())
+    }
 }
 
 /// A handle for managing spawned async tasks.
@@ -215,6 +400,43 @@ pub trait AsyncHandle<T>: Future<Output = Result<T, ()>> + Send {
 
     /// Abort the task execution, don't care for it's result
     fn abort(self);
+
+    /// Take the result without waiting if the task is finished, otherwise returns `Err(self)`.
+    ///
+    /// Requires the handle to be `Unpin`, which is the case of the runtimes in this project.
+    fn try_join(mut self) -> Result<Result<T, ()>, Self>
+    where
+        Self: Sized + Unpin,
+    {
+        if !self.is_finished() {
+            return Err(self);
+        }
+        let mut cx = Context::from_waker(Waker::noop());
+        match Pin::new(&mut self).poll(&mut cx) {
+            Poll::Ready(r) => Ok(r),
+            Poll::Pending => Err(self),
+        }
+    }
+
+    /// Wait for the task result within `d`.
+    ///
+    /// Returns `Ok(result)` if the task finished in time, otherwise `Err(self)` with the task
+    /// still running, so the caller can abort, detach or keep waiting.
+    ///
+    /// Requires the handle to be `Unpin`, so it can be polled by reference without consuming it.
+    fn join_timeout<IO: AsyncTime>(
+        mut self, d: Duration,
+    ) -> impl Future<Output = Result<Result<T, ()>, Self>> + Send
+    where
+        Self: Sized + Unpin,
+    {
+        async move {
+            match IO::timeout(d, &mut self).await {
+                Ok(r) => Ok(r),
+                Err(()) => Err(self),
+            }
+        }
+    }
 }
 
 /// A handle for spawn_blocking()
@@ -222,7 +444,7 @@ pub trait AsyncHandle<T>: Future<Output = Result<T, ()>> + Send {
 /// This trait provides methods for waiting for a blocking task's completion or
 /// detaching it to run in the background.
 ///
-/// Calling await on the ThreadHandle will get Result<T, ()>.
+/// Calling await on the ThreadHandle will get `Result<T, BlockingError>`.
 ///
 /// # NOTE:
 ///
@@ -235,8 +457,85 @@ pub trait AsyncHandle<T>: Future<Output = Result<T, ()>> + Send {
 /// # Returns
 ///
 /// A future that resolves to `Ok(T)` if the task completed successfully,
-/// or `Err(())` if the task panics.
-pub trait ThreadHandle<T>: Future<Output = Result<T, ()>> {
+/// or `Err(BlockingError)` if the task panics or is cancelled.
+pub trait ThreadHandle<T>: Future<Output = Result<T, BlockingError>> {
     /// Whether a task can be join immediately
     fn is_finished(&self) -> bool;
 }
+
+/// The error returned by awaiting a [ThreadHandle].
+///
+/// Since blocking tasks run on real threads, they can not be aborted by user. `Cancelled` only
+/// happens when the runtime shuts down before the task starts to run (tokio).
+pub enum BlockingError {
+    /// The task panicked, carrying the panic payload.
+    Panic(Box<dyn Any + Send + 'static>),
+    /// The task is dropped by the runtime before it runs.
+    Cancelled,
+}
+
+impl BlockingError {
+    #[inline]
+    pub fn is_panic(&self) -> bool {
+        matches!(self, Self::Panic(_))
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled)
+    }
+
+    /// Return the panic message, if the payload is a string (the common case of `panic!()`).
+    pub fn panic_message(&self) -> Option<&str> {
+        match self {
+            Self::Panic(payload) => {
+                if let Some(s) = payload.downcast_ref::<&'static str>() {
+                    Some(s)
+                } else if let Some(s) = payload.downcast_ref::<String>() {
+                    Some(s.as_str())
+                } else {
+                    None
+                }
+            }
+            Self::Cancelled => None,
+        }
+    }
+
+    /// Consume the error and return the panic payload, which can be passed to
+    /// `std::panic::resume_unwind()`.
+    ///
+    /// Returns `Err(self)` if the task is cancelled.
+    #[inline]
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send + 'static>, Self> {
+        match self {
+            Self::Panic(payload) => Ok(payload),
+            Self::Cancelled => Err(self),
+        }
+    }
+}
+
+impl fmt::Debug for BlockingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Panic(_) => match self.panic_message() {
+                Some(msg) => write!(f, "Panic({:?})", msg),
+                None => write!(f, "Panic(..)"),
+            },
+            Self::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl fmt::Display for BlockingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Panic(_) => match self.panic_message() {
+                Some(msg) => write!(f, "blocking task panicked: {}", msg),
+                None => write!(f, "blocking task panicked"),
+            },
+            Self::Cancelled => write!(f, "blocking task cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for BlockingError {}
@@ -0,0 +1,69 @@
+use super::AsyncHandle;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The handle returned by [spawn_abortable()](super::AsyncExec::spawn_abortable), which aborts
+/// the task on drop, instead of detaching.
+///
+/// Call [disarm()](Self::disarm) to get back the plain handle with the detach-on-drop behavior.
+pub struct AbortOnDrop<H> {
+    handle: Option<H>,
+    // Drop can not have the bound of AsyncHandle<T>, so keep the abort function of the handle
+    abort: fn(H),
+}
+
+impl<H> AbortOnDrop<H> {
+    #[inline]
+    pub fn new<T>(handle: H) -> Self
+    where
+        H: AsyncHandle<T>,
+    {
+        Self { handle: Some(handle), abort: <H as AsyncHandle<T>>::abort }
+    }
+
+    /// Take the inner handle, so the task is no longer aborted on drop.
+    #[inline]
+    pub fn disarm(mut self) -> H {
+        self.handle.take().unwrap()
+    }
+
+    #[inline]
+    fn inner(&self) -> &H {
+        self.handle.as_ref().unwrap()
+    }
+}
+
+impl<H: Future + Unpin> Future for AbortOnDrop<H> {
+    type Output = H::Output;
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(self.handle.as_mut().unwrap()).poll(cx)
+    }
+}
+
+impl<T, H: AsyncHandle<T> + Unpin> AsyncHandle<T> for AbortOnDrop<H> {
+    #[inline]
+    fn is_finished(&self) -> bool {
+        self.inner().is_finished()
+    }
+
+    #[inline]
+    fn detach(self) {
+        self.disarm().detach()
+    }
+
+    #[inline]
+    fn abort(self) {
+        // Abort in drop
+    }
+}
+
+impl<H> Drop for AbortOnDrop<H> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            (self.abort)(handle);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use super::{BlockingError, ThreadHandle};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+#[derive(Default)]
+struct Queue {
+    jobs: VecDeque<Job>,
+    threads: usize,
+    idle: usize,
+    closed: bool,
+}
+
+struct PoolInner {
+    name: String,
+    size: usize,
+    queue: Mutex<Queue>,
+    cond: Condvar,
+}
+
+/// A bounded pool of named threads for blocking tasks, with its own queue.
+///
+/// The blocking pool of the runtime is shared by all `spawn_blocking()` calls, the slow
+/// CPU-heavy tasks may delay the short blocking I/O. Run them with
+/// [spawn_blocking_on()](super::AsyncExec::spawn_blocking_on) in a separate pool, so they only
+/// queue behind each other.
+///
+/// The threads are started on demand up to `size`, named as `{name}-{n}`. Dropping the pool lets
+/// the threads exit after the queued tasks are done.
+pub struct BlockingPool {
+    inner: Arc<PoolInner>,
+}
+
+impl BlockingPool {
+    pub fn new(name: impl Into<String>, size: usize) -> Self {
+        assert!(size > 0, "size {} must > 0", size);
+        Self {
+            inner: Arc::new(PoolInner {
+                name: name.into(),
+                size,
+                queue: Mutex::new(Queue::default()),
+                cond: Condvar::new(),
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.inner.size
+    }
+
+    /// Run `f` on a thread of the pool
+    pub fn spawn<F, R>(&self, f: F) -> PoolThreadHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Slot { result: None, waker: None }));
+        let _slot = slot.clone();
+        let job = Box::new(move || {
+            let r = catch_unwind(AssertUnwindSafe(f)).map_err(BlockingError::Panic);
+            let waker = {
+                let mut slot = _slot.lock().unwrap();
+                slot.result = Some(r);
+                slot.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        });
+        let mut queue = self.inner.queue.lock().unwrap();
+        queue.jobs.push_back(job);
+        if queue.idle == 0 && queue.threads < self.inner.size {
+            queue.threads += 1;
+            let inner = self.inner.clone();
+            let name = format!("{}-{}", self.inner.name, queue.threads);
+            std::thread::Builder::new()
+                .name(name)
+                .spawn(move || inner.run())
+                .expect("spawn thread");
+        } else {
+            self.inner.cond.notify_one();
+        }
+        PoolThreadHandle { slot, taken: false }
+    }
+}
+
+impl PoolInner {
+    fn run(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            if let Some(job) = queue.jobs.pop_front() {
+                drop(queue);
+                job();
+                queue = self.queue.lock().unwrap();
+            } else if queue.closed {
+                return;
+            } else {
+                queue.idle += 1;
+                queue = self.cond.wait(queue).unwrap();
+                queue.idle -= 1;
+            }
+        }
+    }
+}
+
+impl Drop for BlockingPool {
+    fn drop(&mut self) {
+        self.inner.queue.lock().unwrap().closed = true;
+        self.inner.cond.notify_all();
+    }
+}
+
+struct Slot<R> {
+    result: Option<Result<R, BlockingError>>,
+    waker: Option<Waker>,
+}
+
+/// The handle returned by [BlockingPool::spawn()], implements [ThreadHandle].
+///
+/// Dropping the handle does not stop the task.
+pub struct PoolThreadHandle<R> {
+    slot: Arc<Mutex<Slot<R>>>,
+    taken: bool,
+}
+
+impl<R> Future for PoolThreadHandle<R> {
+    type Output = Result<R, BlockingError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.taken {
+            return Poll::Ready(Err(BlockingError::Cancelled));
+        }
+        let r = {
+            let mut slot = self.slot.lock().unwrap();
+            match slot.result.take() {
+                Some(r) => r,
+                None => {
+                    slot.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        };
+        self.taken = true;
+        Poll::Ready(r)
+    }
+}
+
+impl<R: Send> ThreadHandle<R> for PoolThreadHandle<R> {
+    #[inline]
+    fn is_finished(&self) -> bool {
+        self.taken || self.slot.lock().unwrap().result.is_some()
+    }
+}
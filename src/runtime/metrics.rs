@@ -0,0 +1,282 @@
+use super::{AsyncExec, RuntimeFlavor};
+use crate::AsyncRuntime;
+use crate::io::{AsyncIO, Interest};
+use crate::time::AsyncTime;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::{SocketAddr, TcpStream};
+use std::os::fd::{AsFd, AsRawFd};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Counters of the spawned tasks, shared by [Metered] runtimes.
+///
+/// Can also be used with other spawn functions through [Metrics::track()].
+#[derive(Default)]
+pub struct Metrics {
+    spawned: AtomicUsize,
+    completed: AtomicUsize,
+    panicked: AtomicUsize,
+    active: AtomicUsize,
+}
+
+/// A snapshot of the counters in [Metrics]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Total tasks spawned
+    pub spawned: usize,
+    /// Tasks run to completion
+    pub completed: usize,
+    /// Tasks panicked
+    pub panicked: usize,
+    /// Tasks not finished yet. The aborted tasks are not counted as completed or panicked.
+    pub active: usize,
+}
+
+impl Metrics {
+    #[inline]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            spawned: self.spawned.load(Ordering::Acquire),
+            completed: self.completed.load(Ordering::Acquire),
+            panicked: self.panicked.load(Ordering::Acquire),
+            active: self.active.load(Ordering::Acquire),
+        }
+    }
+
+    /// Count the future as a spawned task, the counters are updated when it finishes or dropped.
+    pub fn track<F: Future>(self: &Arc<Self>, f: F) -> impl Future<Output = F::Output> + use<F> {
+        self.spawned.fetch_add(1, Ordering::AcqRel);
+        self.active.fetch_add(1, Ordering::AcqRel);
+        let guard = TrackGuard { metrics: self.clone(), done: false };
+        async move {
+            let mut guard = guard;
+            let r = f.await;
+            guard.done = true;
+            r
+        }
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.snapshot().fmt(f)
+    }
+}
+
+struct TrackGuard {
+    metrics: Arc<Metrics>,
+    done: bool,
+}
+
+impl Drop for TrackGuard {
+    fn drop(&mut self) {
+        if self.done {
+            self.metrics.completed.fetch_add(1, Ordering::AcqRel);
+        } else if std::thread::panicking() {
+            self.metrics.panicked.fetch_add(1, Ordering::AcqRel);
+        }
+        self.metrics.active.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A runtime wrapper which counts the tasks from `spawn()` / `spawn_detach()` / `spawn_named()`
+/// into [Metrics].
+///
+/// The plain runtime has no overhead, only attach when needed:
+///
+/// ```rust,ignore
+/// let metrics = Metrics::new();
+/// let rt = Metered::new(TokioRT::new_multi_thread(4), metrics.clone());
+/// ```
+///
+/// NOTE: `spawn_blocking()` is static, so it's not counted.
+///
+/// Whether a panicked task is counted depends on the runtime capturing the panic (tokio does, smol
+/// with `unwind` feature).
+#[derive(Clone)]
+pub struct Metered<RT> {
+    rt: RT,
+    metrics: Arc<Metrics>,
+}
+
+impl<RT> Metered<RT> {
+    #[inline]
+    pub fn new(rt: RT, metrics: Arc<Metrics>) -> Self {
+        Self { rt, metrics }
+    }
+
+    #[inline]
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &RT {
+        &self.rt
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> RT {
+        self.rt
+    }
+}
+
+impl<RT: fmt::Debug> fmt::Debug for Metered<RT> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "metered({:?})", self.rt)
+    }
+}
+
+impl<RT: AsyncExec> AsyncExec for Metered<RT> {
+    type AsyncHandle<R: Send> = RT::AsyncHandle<R>;
+
+    type ThreadHandle<R: Send> = RT::ThreadHandle<R>;
+
+    #[inline]
+    fn spawn<F, R>(&self, f: F) -> Self::AsyncHandle<R>
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.rt.spawn(self.metrics.track(f))
+    }
+
+    #[inline]
+    fn spawn_named<F, R>(&self, name: &str, f: F) -> Self::AsyncHandle<R>
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.rt.spawn_named(name, self.metrics.track(f))
+    }
+
+    #[inline]
+    fn spawn_detach<F, R>(&self, f: F)
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.rt.spawn_detach(self.metrics.track(f))
+    }
+
+    #[inline(always)]
+    fn spawn_blocking<F, R>(f: F) -> Self::ThreadHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        RT::spawn_blocking(f)
+    }
+
+    #[inline(always)]
+    fn spawn_blocking_named<F, R>(name: &str, f: F) -> Self::ThreadHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        RT::spawn_blocking_named(name, f)
+    }
+
+    #[inline(always)]
+    fn block_on<F, R>(&self, f: F) -> R
+    where
+        F: Future<Output = R> + Send,
+        R: Send + 'static,
+    {
+        self.rt.block_on(f)
+    }
+
+    #[inline(always)]
+    fn block_on_local<F, R>(&self, f: F) -> R
+    where
+        F: Future<Output = R>,
+    {
+        self.rt.block_on_local(f)
+    }
+
+    #[inline(always)]
+    fn worker_count(&self) -> Option<usize> {
+        self.rt.worker_count()
+    }
+
+    #[inline(always)]
+    fn flavor(&self) -> RuntimeFlavor {
+        self.rt.flavor()
+    }
+}
+
+impl<RT: AsyncIO> AsyncIO for Metered<RT> {
+    type AsyncFd<T: AsRawFd + AsFd + Send + Sync + 'static> = RT::AsyncFd<T>;
+
+    #[inline(always)]
+    fn connect_tcp(
+        addr: &SocketAddr,
+    ) -> impl Future<Output = io::Result<Self::AsyncFd<TcpStream>>> + Send {
+        RT::connect_tcp(addr)
+    }
+
+    #[inline(always)]
+    fn connect_unix(
+        addr: &PathBuf,
+    ) -> impl Future<Output = io::Result<Self::AsyncFd<UnixStream>>> + Send {
+        RT::connect_unix(addr)
+    }
+
+    #[inline(always)]
+    fn to_async_fd_rd<T: AsRawFd + AsFd + Send + Sync + 'static>(
+        fd: T,
+    ) -> io::Result<Self::AsyncFd<T>> {
+        RT::to_async_fd_rd(fd)
+    }
+
+    #[inline(always)]
+    fn to_async_fd_rw<T: AsRawFd + AsFd + Send + Sync + 'static>(
+        fd: T,
+    ) -> io::Result<Self::AsyncFd<T>> {
+        RT::to_async_fd_rw(fd)
+    }
+
+    #[inline(always)]
+    fn to_async_fd<T: AsRawFd + AsFd + Send + Sync + 'static>(
+        fd: T, interest: Interest,
+    ) -> io::Result<Self::AsyncFd<T>> {
+        RT::to_async_fd(fd, interest)
+    }
+}
+
+impl<RT: AsyncTime> AsyncTime for Metered<RT> {
+    type Interval = RT::Interval;
+
+    type Delay = RT::Delay;
+
+    #[inline(always)]
+    fn sleep(d: Duration) -> impl Future + Send {
+        RT::sleep(d)
+    }
+
+    #[inline(always)]
+    fn tick(d: Duration) -> Self::Interval {
+        RT::tick(d)
+    }
+
+    #[inline(always)]
+    fn delay(d: Duration) -> Self::Delay {
+        RT::delay(d)
+    }
+
+    #[inline(always)]
+    fn now() -> Instant {
+        RT::now()
+    }
+}
+
+impl<RT: AsyncRuntime> AsyncRuntime for Metered<RT> {}
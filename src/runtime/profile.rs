@@ -0,0 +1,128 @@
+use super::AsyncHandle;
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// The time accounting of a task from [spawn_profiled()](super::AsyncExec::spawn_profiled).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaskProfile {
+    /// Total time spent inside `poll()`
+    pub poll_time: Duration,
+    /// Total time between spawn and the first poll, and between the polls. It includes both the
+    /// time waiting for events (timer, I/O) and the time scheduled but not yet polled.
+    pub idle_time: Duration,
+    /// Number of the polls
+    pub polls: usize,
+}
+
+struct ProfileState {
+    profile: TaskProfile,
+    // The end of the last poll, or the spawn time
+    last: Instant,
+    finished: bool,
+}
+
+pin_project! {
+    /// Wrap the future to record the time of poll and idle.
+    pub(super) struct Profiled<F> {
+        #[pin]
+        future: F,
+        state: Arc<Mutex<ProfileState>>,
+    }
+}
+
+impl<F: Future> Profiled<F> {
+    pub(super) fn new(future: F) -> (Self, ProfileReader) {
+        let state = Arc::new(Mutex::new(ProfileState {
+            profile: TaskProfile::default(),
+            last: Instant::now(),
+            finished: false,
+        }));
+        (Self { future, state: state.clone() }, ProfileReader(state))
+    }
+}
+
+impl<F: Future> Future for Profiled<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let start = Instant::now();
+        let r = this.future.poll(cx);
+        let end = Instant::now();
+        let mut state = this.state.lock().unwrap();
+        let idle = start.saturating_duration_since(state.last);
+        state.profile.idle_time += idle;
+        state.profile.poll_time += end - start;
+        state.profile.polls += 1;
+        state.last = end;
+        state.finished = r.is_ready();
+        r
+    }
+}
+
+pub(super) struct ProfileReader(Arc<Mutex<ProfileState>>);
+
+impl ProfileReader {
+    fn get(&self) -> TaskProfile {
+        let state = self.0.lock().unwrap();
+        let mut profile = state.profile;
+        if !state.finished {
+            // Count the ongoing idle period
+            profile.idle_time += Instant::now().saturating_duration_since(state.last);
+        }
+        profile
+    }
+}
+
+pin_project! {
+    /// The handle returned by [spawn_profiled()](super::AsyncExec::spawn_profiled), which
+    /// provides [profile()](Self::profile) besides the [AsyncHandle] functions.
+    pub struct ProfiledHandle<H> {
+        #[pin]
+        handle: H,
+        reader: ProfileReader,
+    }
+}
+
+impl<H> ProfiledHandle<H> {
+    #[inline]
+    pub(super) fn new(handle: H, reader: ProfileReader) -> Self {
+        Self { handle, reader }
+    }
+
+    /// The profile so far, can be read while the task is running.
+    #[inline]
+    pub fn profile(&self) -> TaskProfile {
+        self.reader.get()
+    }
+}
+
+impl<H: Future> Future for ProfiledHandle<H> {
+    type Output = H::Output;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.project().handle.poll(cx)
+    }
+}
+
+impl<T, H: AsyncHandle<T>> AsyncHandle<T> for ProfiledHandle<H> {
+    #[inline]
+    fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    #[inline]
+    fn detach(self) {
+        self.handle.detach()
+    }
+
+    #[inline]
+    fn abort(self) {
+        self.handle.abort()
+    }
+}
@@ -0,0 +1,84 @@
+use crate::utils::sync::{WaitList, poll_once};
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+
+#[derive(Default)]
+struct StartState {
+    started: AtomicBool,
+    // Set when the task is dropped, whether started or not
+    gone: AtomicBool,
+    wait: WaitList,
+}
+
+impl StartState {
+    fn trip(&self, flag: &AtomicBool) {
+        if !flag.swap(true, Ordering::AcqRel) {
+            self.wait.notify_all();
+        }
+    }
+}
+
+pin_project! {
+    /// Wrap the future to trip the signal on the first poll.
+    pub(super) struct NotifyStart<F> {
+        #[pin]
+        future: F,
+        state: Arc<StartState>,
+    }
+
+    impl<F> PinnedDrop for NotifyStart<F> {
+        fn drop(this: Pin<&mut Self>) {
+            let state = &this.state;
+            state.trip(&state.gone);
+        }
+    }
+}
+
+impl<F: Future> NotifyStart<F> {
+    pub(super) fn new(future: F) -> (Self, StartedSignal) {
+        let state = Arc::new(StartState::default());
+        (Self { future, state: state.clone() }, StartedSignal(state))
+    }
+}
+
+impl<F: Future> Future for NotifyStart<F> {
+    type Output = F::Output;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        this.state.trip(&this.state.started);
+        this.future.poll(cx)
+    }
+}
+
+/// Returned by [spawn_started()](super::AsyncExec::spawn_started), to know the task has begun
+/// to run (polled for the first time).
+pub struct StartedSignal(Arc<StartState>);
+
+impl StartedSignal {
+    /// Whether the task has been polled
+    #[inline]
+    pub fn is_started(&self) -> bool {
+        self.0.started.load(Ordering::Acquire)
+    }
+
+    /// Wait until the task is polled for the first time.
+    ///
+    /// Also returns if the task is dropped (aborted, or the runtime shut down) without running,
+    /// check with [is_started()](Self::is_started) in that case.
+    pub async fn wait(&self) {
+        loop {
+            let mut listen = self.0.wait.listen();
+            poll_once(&mut listen).await;
+            if self.is_started() || self.0.gone.load(Ordering::Acquire) {
+                return;
+            }
+            listen.await;
+        }
+    }
+}
@@ -18,10 +18,14 @@ use std::time::{Duration, Instant};
 /// # Associated Types
 ///
 /// * `Interval` - The type used for periodic timers
+/// * `Delay` - The type used for resettable timers
 pub trait AsyncTime {
     /// The type used for periodic timers.
     type Interval: TimeInterval;
 
+    /// The type used for resettable timers.
+    type Delay: TimeDelay;
+
     /// Sleep for the specified duration.
     ///
     /// This method returns a future that completes after the specified
@@ -50,6 +54,20 @@ pub trait AsyncTime {
     /// An interval object that implements [`TimeInterval`]
     fn tick(d: Duration) -> Self::Interval;
 
+    /// Create a timer completes after `d`, which can be reset or cancelled, for debouncing etc.
+    ///
+    /// Unlike [sleep()](Self::sleep), the deadline can be moved with [TimeDelay::reset()].
+    fn delay(d: Duration) -> Self::Delay;
+
+    /// The current time of the clock the runtime schedules the timers on.
+    ///
+    /// Measure the elapsed time with it instead of `Instant::now()`, so that it agrees with
+    /// [sleep()](Self::sleep) when the runtime clock is paused or mocked in tests.
+    #[inline]
+    fn now() -> Instant {
+        Instant::now()
+    }
+
     /// Apply a timeout to a future.
     ///
     /// This method returns a future that completes either when the provided
@@ -78,6 +96,8 @@ pub trait AsyncTime {
 impl<F: std::ops::Deref<Target = T>, T: AsyncTime> AsyncTime for F {
     type Interval = T::Interval;
 
+    type Delay = T::Delay;
+
     #[inline(always)]
     fn sleep(d: Duration) -> impl Future + Send {
         T::sleep(d)
@@ -87,6 +107,43 @@ impl<F: std::ops::Deref<Target = T>, T: AsyncTime> AsyncTime for F {
     fn tick(d: Duration) -> Self::Interval {
         T::tick(d)
     }
+
+    #[inline(always)]
+    fn delay(d: Duration) -> Self::Delay {
+        T::delay(d)
+    }
+
+    #[inline(always)]
+    fn now() -> Instant {
+        T::now()
+    }
+}
+
+/// Optional trait to control the clock of the runtime, for deterministic tests of the timeouts.
+///
+/// When paused, the clock (and [AsyncTime::now()]) only moves with [advance()](Self::advance), so
+/// a timeout of seconds completes without waiting in real time. Runtimes which can't do this
+/// simply don't implement it.
+pub trait AsyncTimeControl: AsyncTime {
+    /// Freeze the clock of the runtime.
+    fn pause(&self);
+
+    /// Unfreeze the clock, it goes on from where it was paused.
+    fn resume(&self);
+
+    /// Move the paused clock forward by `d`, the timers due are fired.
+    fn advance(&self, d: Duration) -> impl Future<Output = ()> + Send;
+}
+
+/// Trait for resettable timers, returned by [AsyncTime::delay()].
+///
+/// It's a future completes at the deadline. After completion, it can be reset to wait again.
+pub trait TimeDelay: Future<Output = ()> + Unpin + Send {
+    /// Move the deadline to `d` after now, whether it's completed, pending or cancelled.
+    fn reset(&mut self, d: Duration);
+
+    /// Cancel the timer, it never completes until [reset()](Self::reset).
+    fn cancel(&mut self);
 }
 
 /// Trait for periodic timers.
@@ -109,6 +166,12 @@ pub trait TimeInterval: Unpin + Send {
     /// if the tick is not yet ready.
     fn poll_tick(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Instant>;
 
+    /// The number of the ticks missed when the last tick is consumed, to log the lag.
+    ///
+    /// It's the whole periods elapsed since the deadline of the last tick, 0 when the ticks are
+    /// consumed in time. The missed ticks are not skipped, they are due immediately afterward.
+    fn missed_ticks(&self) -> u32;
+
     /// Wait asynchronously for the next tick.
     ///
     /// This method returns a future that completes when the next timer tick occurs.
@@ -138,6 +201,55 @@ pub trait TimeInterval: Unpin + Send {
     {
         IntervalStream::new(self)
     }
+
+    /// Convert this interval into a stream that yields exactly `n` ticks, then ends.
+    ///
+    /// # Parameters
+    ///
+    /// * `n` - The number of ticks to yield
+    ///
+    /// # Returns
+    ///
+    /// A stream that yields the instant of each tick.
+    #[inline(always)]
+    fn take(self, n: usize) -> TakeInterval<Self>
+    where
+        Self: Sized,
+    {
+        TakeInterval::new(self, n)
+    }
+
+    /// Convert this interval into a stream that keeps ticking until `total` has elapsed.
+    ///
+    /// The deadline is counted from the time this method is called, on the clock of the runtime
+    /// `IO`. The stream ends at the deadline (with a timer of `IO`), even if the next tick is far
+    /// beyond it.
+    ///
+    /// # Parameters
+    ///
+    /// * `total` - The total duration to keep ticking
+    ///
+    /// # Returns
+    ///
+    /// A stream that yields the instant of each tick.
+    #[inline(always)]
+    fn for_duration<IO: AsyncTime>(self, total: Duration) -> ForDuration<Self, IO::Delay>
+    where
+        Self: Sized,
+    {
+        ForDuration::new::<IO>(self, total)
+    }
+}
+
+/// Count the whole periods from the `deadline` of the tick to `now`, for
+/// [TimeInterval::missed_ticks()].
+#[inline]
+pub fn count_missed_ticks(deadline: Instant, now: Instant, period: Duration) -> u32 {
+    let late = now.saturating_duration_since(deadline);
+    if period.is_zero() {
+        return 0;
+    }
+    (late.as_nanos() / period.as_nanos()).min(u32::MAX as u128) as u32
 }
 
 /// A wrapper that implements `Stream` for a `TimeInterval`.
@@ -175,6 +287,89 @@ impl<T: TimeInterval> Stream for IntervalStream<T> {
     }
 }
 
+/// A stream yields a limited number of ticks, returned by [TimeInterval::take()].
+///
+/// # Type Parameters
+///
+/// * `T` - The underlying interval type
+pub struct TakeInterval<T: TimeInterval> {
+    interval: T,
+    remaining: usize,
+}
+
+impl<T: TimeInterval> TakeInterval<T> {
+    /// Create a new stream which yields `n` ticks of the interval.
+    pub fn new(interval: T, n: usize) -> Self {
+        Self { interval, remaining: n }
+    }
+}
+
+impl<T: TimeInterval> Stream for TakeInterval<T> {
+    type Item = Instant;
+
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.remaining == 0 {
+            return Poll::Ready(None);
+        }
+        match Pin::new(&mut self.interval).poll_tick(ctx) {
+            Poll::Ready(i) => {
+                self.remaining -= 1;
+                Poll::Ready(Some(i))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// A stream yields ticks until the deadline, returned by [TimeInterval::for_duration()].
+///
+/// # Type Parameters
+///
+/// * `T` - The underlying interval type
+/// * `D` - The timer of the deadline
+pub struct ForDuration<T: TimeInterval, D: TimeDelay> {
+    interval: T,
+    timer: D,
+    deadline: Instant,
+    done: bool,
+}
+
+impl<T: TimeInterval, D: TimeDelay> ForDuration<T, D> {
+    /// Create a new stream which yields the ticks of the interval within `total` from now.
+    pub fn new<IO: AsyncTime<Delay = D>>(interval: T, total: Duration) -> Self {
+        Self { interval, timer: IO::delay(total), deadline: IO::now() + total, done: false }
+    }
+}
+
+impl<T: TimeInterval, D: TimeDelay> Stream for ForDuration<T, D> {
+    type Item = Instant;
+
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        if Pin::new(&mut self.timer).poll(ctx).is_ready() {
+            self.done = true;
+            return Poll::Ready(None);
+        }
+        match Pin::new(&mut self.interval).poll_tick(ctx) {
+            Poll::Ready(i) => {
+                if i > self.deadline {
+                    self.done = true;
+                    Poll::Ready(None)
+                } else {
+                    Poll::Ready(Some(i))
+                }
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
 /// Future for the tick operation.
 ///
 /// This future completes when the next timer tick occurs.
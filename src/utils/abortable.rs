@@ -0,0 +1,119 @@
+use pin_project_lite::pin_project;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+struct AbortInner {
+    aborted: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+/// Make `f` abortable from elsewhere, without spawning it as a task.
+///
+/// The [Abortable] resolves to `Err(Aborted)` on the next poll after [AbortHandle::abort()]. The
+/// inner future is not polled any more, and dropped along with the [Abortable].
+///
+/// # Example
+///
+/// ```rust
+/// use orb::utils::{Aborted, abortable};
+///
+/// let (fut, handle) = abortable(std::future::pending::<()>());
+/// handle.abort();
+/// assert_eq!(futures_lite::future::block_on(fut), Err(Aborted));
+/// ```
+pub fn abortable<F: Future>(f: F) -> (Abortable<F>, AbortHandle) {
+    let inner = Arc::new(AbortInner { aborted: AtomicBool::new(false), waker: Mutex::new(None) });
+    (Abortable { future: f, inner: inner.clone() }, AbortHandle(inner))
+}
+
+pin_project! {
+    /// The future returned by [abortable()]
+    pub struct Abortable<F> {
+        #[pin]
+        future: F,
+        inner: Arc<AbortInner>,
+    }
+}
+
+impl<F> Abortable<F> {
+    #[inline]
+    pub fn is_aborted(&self) -> bool {
+        self.inner.aborted.load(Ordering::Acquire)
+    }
+}
+
+impl<F: Future> Future for Abortable<F> {
+    type Output = Result<F::Output, Aborted>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _self = self.project();
+        if _self.inner.aborted.load(Ordering::Acquire) {
+            return Poll::Ready(Err(Aborted));
+        }
+        if let Poll::Ready(output) = _self.future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        {
+            let mut waker = _self.inner.waker.lock().unwrap();
+            match waker.as_mut() {
+                Some(w) => w.clone_from(cx.waker()),
+                None => *waker = Some(cx.waker().clone()),
+            }
+        }
+        // Check again after registered, in case aborted in between
+        if _self.inner.aborted.load(Ordering::Acquire) {
+            return Poll::Ready(Err(Aborted));
+        }
+        Poll::Pending
+    }
+}
+
+impl<F> fmt::Debug for Abortable<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Abortable(aborted={})", self.is_aborted())
+    }
+}
+
+/// The handle to abort the [Abortable], returned by [abortable()].
+///
+/// It can be cloned and sent to other tasks. Dropping it does not abort.
+#[derive(Clone)]
+pub struct AbortHandle(Arc<AbortInner>);
+
+impl AbortHandle {
+    /// The [Abortable] will resolve to `Err(Aborted)`, even if the inner future is ready.
+    pub fn abort(&self) {
+        self.0.aborted.store(true, Ordering::Release);
+        let waker = self.0.waker.lock().unwrap().take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    #[inline]
+    pub fn is_aborted(&self) -> bool {
+        self.0.aborted.load(Ordering::Acquire)
+    }
+}
+
+impl fmt::Debug for AbortHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AbortHandle(aborted={})", self.is_aborted())
+    }
+}
+
+/// The error of the [Abortable] which is aborted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aborted;
+
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "future aborted")
+    }
+}
+
+impl std::error::Error for Aborted {}
@@ -0,0 +1,315 @@
+//! A runtime agnostic bounded mpsc channel.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use orb::utils::channel;
+//!
+//! async fn run() {
+//!     let (tx, mut rx) = channel::bounded::<u32>(16);
+//!     tx.send(1).await.expect("closed");
+//!     drop(tx);
+//!     assert_eq!(rx.recv().await, Ok(1));
+//!     assert!(rx.recv().await.is_err());
+//! }
+//! ```
+
+use super::sync::{WaitList, poll_once};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Create a channel buffering at most `cap` messages.
+///
+/// # Panics
+///
+/// Panics if `cap` is 0.
+pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(cap > 0, "channel capacity must be positive");
+    let chan = Arc::new(Chan {
+        state: Mutex::new(State { queue: VecDeque::with_capacity(cap), senders: 1, closed: false }),
+        cap,
+        send_wait: WaitList::default(),
+        recv_wait: WaitList::default(),
+    });
+    (Sender { chan: chan.clone() }, Receiver { chan })
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    // The receiver is closed or dropped
+    closed: bool,
+}
+
+struct Chan<T> {
+    state: Mutex<State<T>>,
+    cap: usize,
+    // The senders waiting for room
+    send_wait: WaitList,
+    // The receiver waiting for message
+    recv_wait: WaitList,
+}
+
+impl<T> Chan<T> {
+    fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(v) = state.queue.pop_front() {
+            drop(state);
+            self.send_wait.notify_one();
+            return Ok(v);
+        }
+        if state.senders == 0 || state.closed {
+            Err(TryRecvError::Closed)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+}
+
+/// Returned by [Sender::send()] when the receiver is gone, with the message not sent.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SendError(..)")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "channel closed")
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+/// Returned by [Sender::try_send()], with the message not sent.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    Full(T),
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Take back the message
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(v) | Self::Closed(v) => v,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Full(_) => write!(f, "Full(..)"),
+            Self::Closed(_) => write!(f, "Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Full(_) => write!(f, "channel full"),
+            Self::Closed(_) => write!(f, "channel closed"),
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {}
+
+/// Returned by [Receiver::recv()] when all the senders are dropped and the messages are drained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "channel closed")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Returned by [Receiver::try_recv()]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Closed,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "channel empty"),
+            Self::Closed => write!(f, "channel closed"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+/// The sending side of [bounded()], can be cloned.
+pub struct Sender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send the message, wait when the channel is full.
+    ///
+    /// The future is cancel safe, the message is dropped on cancellation.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        loop {
+            let mut listen = self.chan.send_wait.listen();
+            poll_once(&mut listen).await;
+            {
+                let mut state = self.chan.state.lock().unwrap();
+                if state.closed {
+                    return Err(SendError(value));
+                }
+                if state.queue.len() < self.chan.cap {
+                    state.queue.push_back(value);
+                    drop(state);
+                    self.chan.recv_wait.notify_one();
+                    return Ok(());
+                }
+            }
+            listen.await;
+        }
+    }
+
+    /// Send without waiting
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.chan.state.lock().unwrap();
+        if state.closed {
+            return Err(TrySendError::Closed(value));
+        }
+        if state.queue.len() >= self.chan.cap {
+            return Err(TrySendError::Full(value));
+        }
+        state.queue.push_back(value);
+        drop(state);
+        self.chan.recv_wait.notify_one();
+        Ok(())
+    }
+
+    /// Whether the receiver is closed or dropped
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.chan.state.lock().unwrap().closed
+    }
+
+    /// The number of the messages buffered
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.chan.state.lock().unwrap().queue.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.chan.cap
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.chan.state.lock().unwrap().senders += 1;
+        Self { chan: self.chan.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let last = {
+            let mut state = self.chan.state.lock().unwrap();
+            state.senders -= 1;
+            state.senders == 0
+        };
+        if last {
+            self.chan.recv_wait.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+
+/// The receiving side of [bounded()].
+///
+/// Dropping it closes the channel, the messages not received are dropped.
+pub struct Receiver<T> {
+    chan: Arc<Chan<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive a message, wait when the channel is empty.
+    ///
+    /// Returns [RecvError] after all the senders are dropped and the buffered messages are
+    /// received. The future is cancel safe.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        loop {
+            let mut listen = self.chan.recv_wait.listen();
+            poll_once(&mut listen).await;
+            match self.chan.try_recv() {
+                Ok(v) => return Ok(v),
+                Err(TryRecvError::Closed) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {}
+            }
+            listen.await;
+        }
+    }
+
+    /// Receive without waiting
+    #[inline]
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.chan.try_recv()
+    }
+
+    /// Close the channel, the following sends fail, while the buffered messages can still be
+    /// received.
+    pub fn close(&mut self) {
+        self.chan.state.lock().unwrap().closed = true;
+        self.chan.send_wait.notify_all();
+    }
+
+    /// The number of the messages buffered
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.chan.state.lock().unwrap().queue.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let queue = {
+            let mut state = self.chan.state.lock().unwrap();
+            state.closed = true;
+            std::mem::take(&mut state.queue)
+        };
+        self.chan.send_wait.notify_all();
+        // Drop the messages outside the lock
+        drop(queue);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Receiver")
+    }
+}
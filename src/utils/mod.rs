@@ -0,0 +1,171 @@
+//! Utility types and functions for async operations.
+//!
+//! This module provides helper types and functions that support the
+//! other modules in the crate.
+
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+mod abortable;
+pub mod channel;
+pub mod oneshot;
+mod shutdown;
+pub mod sync;
+pub use abortable::{AbortHandle, Abortable, Aborted, abortable};
+pub use oneshot::oneshot;
+pub use shutdown::{ShutdownToken, ShutdownTrigger};
+pub use sync::Condvar;
+
+pin_project! {
+    /// A cancellable future that can be aborted when another future completes.
+    ///
+    /// This struct allows you to race two futures and cancel one when the
+    /// other completes. It's primarily used internally to implement timeouts.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `F` - The main future that provides the primary result
+    /// * `C` - The cancellation future that, when completed, aborts the main future
+    pub struct Cancellable<F, C> {
+        #[pin]
+        future: F,
+        #[pin]
+        cancel_future: C,
+    }
+}
+
+impl<F: Future + Send, C: Future + Send> Cancellable<F, C> {
+    /// Create a new cancellable future.
+    ///
+    /// # Parameters
+    ///
+    /// * `future` - The main future to execute
+    /// * `cancel_future` - The future that, when completed, cancels the main future
+    ///
+    /// # Returns
+    ///
+    /// A new cancellable future
+    pub fn new(future: F, cancel_future: C) -> Self {
+        Self { future, cancel_future }
+    }
+}
+
+impl<F: Future + Send, C: Future + Send> Future for Cancellable<F, C> {
+    type Output = Result<F::Output, ()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut _self = self.project();
+        let future = unsafe { Pin::new_unchecked(&mut _self.future) };
+        if let Poll::Ready(output) = future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        let cancel_future = unsafe { Pin::new_unchecked(&mut _self.cancel_future) };
+        if let Poll::Ready(_) = cancel_future.poll(cx) {
+            return Poll::Ready(Err(()));
+        }
+        return Poll::Pending;
+    }
+}
+
+pin_project! {
+    /// Similar to [Cancellable], but returns the output of the cancel future.
+    ///
+    /// The main future is polled first, then the cancel future.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `F` - The main future that provides the primary result
+    /// * `C` - The cancellation future, its output is returned as Err on completion
+    pub struct CancellableWith<F, C> {
+        #[pin]
+        future: F,
+        #[pin]
+        cancel_future: C,
+    }
+}
+
+impl<F: Future + Send, C: Future + Send> CancellableWith<F, C> {
+    /// Create a new cancellable future.
+    pub fn new(future: F, cancel_future: C) -> Self {
+        Self { future, cancel_future }
+    }
+}
+
+impl<F: Future + Send, C: Future + Send> Future for CancellableWith<F, C> {
+    type Output = Result<F::Output, C::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _self = self.project();
+        if let Poll::Ready(output) = _self.future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        if let Poll::Ready(output) = _self.cancel_future.poll(cx) {
+            return Poll::Ready(Err(output));
+        }
+        Poll::Pending
+    }
+}
+
+/// The output of [select2()], telling which side completed first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<A, B> Either<A, B> {
+    #[inline]
+    pub fn is_left(&self) -> bool {
+        matches!(self, Self::Left(_))
+    }
+
+    #[inline]
+    pub fn is_right(&self) -> bool {
+        matches!(self, Self::Right(_))
+    }
+}
+
+impl<T> Either<T, T> {
+    /// Get the inner value when both sides are the same type
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Left(v) | Self::Right(v) => v,
+        }
+    }
+}
+
+pin_project! {
+    /// The future returned by [select2()]
+    pub struct Select2<A, B> {
+        #[pin]
+        a: A,
+        #[pin]
+        b: B,
+    }
+}
+
+/// Race two futures, returns the output of the first completed one, the other is dropped.
+///
+/// `a` is polled first, so it wins if both are ready.
+#[inline]
+pub fn select2<A: Future, B: Future>(a: A, b: B) -> Select2<A, B> {
+    Select2 { a, b }
+}
+
+impl<A: Future, B: Future> Future for Select2<A, B> {
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _self = self.project();
+        if let Poll::Ready(output) = _self.a.poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+        if let Poll::Ready(output) = _self.b.poll(cx) {
+            return Poll::Ready(Either::Right(output));
+        }
+        Poll::Pending
+    }
+}
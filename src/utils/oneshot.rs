@@ -0,0 +1,156 @@
+//! A runtime agnostic oneshot channel, to get the result back from a detached task.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use orb::prelude::*;
+//! use orb::utils::oneshot;
+//!
+//! async fn run<RT: AsyncExec>(rt: &RT) {
+//!     let (tx, rx) = oneshot::<u32>();
+//!     rt.spawn_detach(async move {
+//!         let _ = tx.send(1);
+//!     });
+//!     assert_eq!(rx.await, Ok(1));
+//! }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Create a oneshot channel
+pub fn oneshot<T>() -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Mutex::new(State {
+        value: None,
+        waker: None,
+        tx_dropped: false,
+        rx_dropped: false,
+    }));
+    (Sender { inner: inner.clone() }, Receiver { inner })
+}
+
+struct State<T> {
+    value: Option<T>,
+    // The waker of the receiver
+    waker: Option<Waker>,
+    tx_dropped: bool,
+    rx_dropped: bool,
+}
+
+/// Returned by [Receiver] when the sender is dropped without sending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Canceled;
+
+impl fmt::Display for Canceled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "oneshot canceled")
+    }
+}
+
+impl std::error::Error for Canceled {}
+
+/// The sending side of [oneshot()]
+pub struct Sender<T> {
+    inner: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send the value, returns it back if the receiver is dropped.
+    pub fn send(self, value: T) -> Result<(), T> {
+        let waker = {
+            let mut state = self.inner.lock().unwrap();
+            if state.rx_dropped {
+                return Err(value);
+            }
+            state.value = Some(value);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Whether the receiver is dropped
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.inner.lock().unwrap().rx_dropped
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut state = self.inner.lock().unwrap();
+            state.tx_dropped = true;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "oneshot::Sender")
+    }
+}
+
+/// The receiving side of [oneshot()], await it for the value.
+///
+/// Returns [Canceled] if the sender is dropped without sending.
+pub struct Receiver<T> {
+    inner: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Take the value without waiting, returns None if not yet sent.
+    pub fn try_recv(&mut self) -> Result<Option<T>, Canceled> {
+        let mut state = self.inner.lock().unwrap();
+        if let Some(v) = state.value.take() {
+            return Ok(Some(v));
+        }
+        if state.tx_dropped { Err(Canceled) } else { Ok(None) }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, Canceled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.inner.lock().unwrap();
+        if let Some(v) = state.value.take() {
+            return Poll::Ready(Ok(v));
+        }
+        if state.tx_dropped {
+            return Poll::Ready(Err(Canceled));
+        }
+        match state.waker.as_ref() {
+            Some(w) if w.will_wake(cx.waker()) => {}
+            _ => state.waker = Some(cx.waker().clone()),
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let value = {
+            let mut state = self.inner.lock().unwrap();
+            state.rx_dropped = true;
+            state.value.take()
+        };
+        // Drop the value outside the lock
+        drop(value);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "oneshot::Receiver")
+    }
+}
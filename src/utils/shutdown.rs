@@ -0,0 +1,86 @@
+use super::sync::{WaitList, poll_once};
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+struct ShutdownInner {
+    cancelled: AtomicBool,
+    waiters: WaitList,
+}
+
+/// A graceful shutdown signal, shared by all the tasks.
+///
+/// Created with [ShutdownToken::new()] along with a [ShutdownTrigger].
+/// To stop a future on shutdown, compose with [Cancellable](super::Cancellable):
+///
+/// ```rust,ignore
+/// let r = Cancellable::new(listener.accept(), token.cancelled()).await;
+/// ```
+#[derive(Clone)]
+pub struct ShutdownToken(Arc<ShutdownInner>);
+
+/// Trigger the shutdown of all the [ShutdownToken]s.
+///
+/// NOTE: Dropping the trigger does not shut down.
+pub struct ShutdownTrigger(Arc<ShutdownInner>);
+
+impl ShutdownToken {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> (ShutdownTrigger, ShutdownToken) {
+        let inner = Arc::new(ShutdownInner {
+            cancelled: AtomicBool::new(false),
+            waiters: WaitList::default(),
+        });
+        (ShutdownTrigger(inner.clone()), ShutdownToken(inner))
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Returns when shutdown is triggered, immediately if already triggered.
+    pub async fn cancelled(&self) {
+        if self.is_cancelled() {
+            return;
+        }
+        let mut listen = self.0.waiters.listen();
+        poll_once(&mut listen).await;
+        // Check again after registered, in case shutdown in between
+        if self.is_cancelled() {
+            return;
+        }
+        listen.await;
+    }
+}
+
+impl ShutdownTrigger {
+    /// Wake up all the waiters, the subsequent `cancelled()` will return immediately.
+    pub fn shutdown(&self) {
+        self.0.cancelled.store(true, Ordering::Release);
+        self.0.waiters.notify_all();
+    }
+
+    #[inline]
+    pub fn is_shutdown(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Get a new token associated with this trigger
+    #[inline]
+    pub fn token(&self) -> ShutdownToken {
+        ShutdownToken(self.0.clone())
+    }
+}
+
+impl fmt::Debug for ShutdownToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ShutdownToken(cancelled={})", self.is_cancelled())
+    }
+}
+
+impl fmt::Debug for ShutdownTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ShutdownTrigger(shutdown={})", self.is_shutdown())
+    }
+}
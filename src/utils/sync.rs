@@ -0,0 +1,793 @@
+//! Runtime agnostic synchronization primitives.
+//!
+//! All the primitives are built on a waker queue, without tie-in to any runtime.
+
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::task::{Context, Poll, Waker};
+
+/// A waiter in the queue, woken up when `done` is set.
+struct Waiter {
+    need: usize,
+    done: AtomicBool,
+    waker: StdMutex<Option<Waker>>,
+}
+
+impl Waiter {
+    fn new(need: usize, waker: &Waker) -> Arc<Self> {
+        Arc::new(Self {
+            need,
+            done: AtomicBool::new(false),
+            waker: StdMutex::new(Some(waker.clone())),
+        })
+    }
+
+    #[inline]
+    fn is_done(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+
+    fn wake(&self) {
+        self.done.store(true, Ordering::Release);
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+
+    fn set_waker(&self, waker: &Waker) {
+        let mut guard = self.waker.lock().unwrap();
+        match guard.as_ref() {
+            Some(w) if w.will_wake(waker) => {}
+            _ => *guard = Some(waker.clone()),
+        }
+    }
+}
+
+struct SemaState {
+    permits: usize,
+    waiters: VecDeque<Arc<Waiter>>,
+}
+
+impl SemaState {
+    /// Grant the permits to the waiters in FIFO order
+    fn grant(&mut self) {
+        while let Some(waiter) = self.waiters.front() {
+            if waiter.need > self.permits {
+                break;
+            }
+            self.permits -= waiter.need;
+            self.waiters.pop_front().unwrap().wake();
+        }
+    }
+
+    fn remove(&mut self, waiter: &Arc<Waiter>) {
+        if let Some(i) = self.waiters.iter().position(|w| Arc::ptr_eq(w, waiter)) {
+            self.waiters.remove(i);
+            // The removed one might be blocking the others
+            self.grant();
+        }
+    }
+}
+
+/// A fair semaphore, the permits are granted to the waiters in FIFO order.
+pub(crate) struct Sema {
+    state: StdMutex<SemaState>,
+}
+
+impl Sema {
+    pub(crate) fn new(permits: usize) -> Self {
+        Self { state: StdMutex::new(SemaState { permits, waiters: VecDeque::new() }) }
+    }
+
+    /// Only succeed when there's no one waiting, to prevent starvation.
+    pub(crate) fn try_acquire(&self, n: usize) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.waiters.is_empty() && state.permits >= n {
+            state.permits -= n;
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    pub(crate) fn acquire(&self, n: usize) -> Acquire<'_> {
+        Acquire { sema: self, need: n, waiter: None }
+    }
+
+    pub(crate) fn release(&self, n: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.permits += n;
+        state.grant();
+    }
+
+    #[inline]
+    pub(crate) fn available(&self) -> usize {
+        self.state.lock().unwrap().permits
+    }
+}
+
+/// The future of [Sema::acquire()], cancel safe.
+pub(crate) struct Acquire<'a> {
+    sema: &'a Sema,
+    need: usize,
+    waiter: Option<Arc<Waiter>>,
+}
+
+impl Future for Acquire<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let _self = self.get_mut();
+        if let Some(waiter) = _self.waiter.as_ref() {
+            if waiter.is_done() {
+                _self.waiter = None;
+                return Poll::Ready(());
+            }
+            waiter.set_waker(cx.waker());
+            // Check again in case granted before the waker is set
+            if waiter.is_done() {
+                _self.waiter = None;
+                return Poll::Ready(());
+            }
+            return Poll::Pending;
+        }
+        let mut state = _self.sema.state.lock().unwrap();
+        if state.waiters.is_empty() && state.permits >= _self.need {
+            state.permits -= _self.need;
+            return Poll::Ready(());
+        }
+        let waiter = Waiter::new(_self.need, cx.waker());
+        state.waiters.push_back(waiter.clone());
+        _self.waiter = Some(waiter);
+        Poll::Pending
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if let Some(waiter) = self.waiter.take() {
+            let mut state = self.sema.state.lock().unwrap();
+            if waiter.is_done() {
+                // Granted but not observed, give back
+                state.permits += waiter.need;
+                state.grant();
+            } else {
+                state.remove(&waiter);
+            }
+        }
+    }
+}
+
+/// An async semaphore to limit the concurrency, the permits are granted in FIFO order.
+///
+/// # Example
+///
+/// ```no_run
+/// use orb::prelude::*;
+/// use orb::net::UnifyListener;
+/// use orb::utils::sync::Semaphore;
+/// use std::sync::Arc;
+///
+/// async fn serve<RT: AsyncRuntime>(rt: &RT, mut listener: UnifyListener<RT>) {
+///     let limit = Arc::new(Semaphore::new(100));
+///     loop {
+///         // Stop accepting when there are 100 connections in flight
+///         let permit = limit.clone().acquire_owned().await;
+///         let Ok(conn) = listener.accept().await else { break };
+///         rt.spawn_detach(async move {
+///             let _permit = permit;
+///             // handle conn
+///             drop(conn);
+///         });
+///     }
+/// }
+/// ```
+pub struct Semaphore {
+    sema: Sema,
+}
+
+impl Semaphore {
+    #[inline]
+    pub fn new(permits: usize) -> Self {
+        Self { sema: Sema::new(permits) }
+    }
+
+    /// Acquire a permit, the future is cancel safe.
+    #[inline]
+    pub async fn acquire(&self) -> Permit<'_> {
+        self.sema.acquire(1).await;
+        Permit { sema: self }
+    }
+
+    /// Returns None when there's no permit, or someone is waiting.
+    #[inline]
+    pub fn try_acquire(&self) -> Option<Permit<'_>> {
+        if self.sema.try_acquire(1) { Some(Permit { sema: self }) } else { None }
+    }
+
+    /// Like [acquire()](Self::acquire), the permit can be moved into a spawned task.
+    #[inline]
+    pub async fn acquire_owned(self: Arc<Self>) -> OwnedPermit {
+        self.sema.acquire(1).await;
+        OwnedPermit { sema: self, armed: true }
+    }
+
+    /// Like [try_acquire()](Self::try_acquire), the permit can be moved into a spawned task.
+    #[inline]
+    pub fn try_acquire_owned(self: Arc<Self>) -> Option<OwnedPermit> {
+        if self.sema.try_acquire(1) { Some(OwnedPermit { sema: self, armed: true }) } else { None }
+    }
+
+    /// Add `n` permits, and wake up the waiters.
+    #[inline]
+    pub fn add_permits(&self, n: usize) {
+        self.sema.release(n);
+    }
+
+    /// The permits available currently
+    #[inline]
+    pub fn available_permits(&self) -> usize {
+        self.sema.available()
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Semaphore(available={})", self.available_permits())
+    }
+}
+
+/// The permit of [Semaphore], released on drop.
+#[must_use]
+pub struct Permit<'a> {
+    sema: &'a Semaphore,
+}
+
+impl Permit<'_> {
+    /// Drop the permit without releasing it, the total permits are reduced.
+    #[inline]
+    pub fn forget(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for Permit<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.sema.add_permits(1);
+    }
+}
+
+/// The permit of [Semaphore::acquire_owned()], released on drop.
+#[must_use]
+pub struct OwnedPermit {
+    sema: Arc<Semaphore>,
+    armed: bool,
+}
+
+impl OwnedPermit {
+    /// Drop the permit without releasing it, the total permits are reduced.
+    #[inline]
+    pub fn forget(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for OwnedPermit {
+    #[inline]
+    fn drop(&mut self) {
+        if self.armed {
+            self.sema.add_permits(1);
+        }
+    }
+}
+
+/// An async mutex, fair in FIFO order.
+pub struct Mutex<T: ?Sized> {
+    sema: Sema,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self { sema: Sema::new(1), value: UnsafeCell::new(value) }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Acquire the lock, the future is cancel safe.
+    #[inline]
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        self.sema.acquire(1).await;
+        MutexGuard { mutex: self }
+    }
+
+    /// Returns None when the lock is held, or someone is waiting for it.
+    #[inline]
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        if self.sema.try_acquire(1) { Some(MutexGuard { mutex: self }) } else { None }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Mutex")
+    }
+}
+
+/// The guard of [Mutex], unlock on drop.
+pub struct MutexGuard<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.mutex.sema.release(1);
+    }
+}
+
+/// The permits of [RwLock], a reader takes one, a writer takes all.
+const RW_PERMITS: usize = usize::MAX >> 3;
+
+/// An async reader-writer lock, fair in FIFO order.
+///
+/// A waiting writer blocks the readers coming after it, so the writers will not starve.
+pub struct RwLock<T: ?Sized> {
+    sema: Sema,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+impl<T> RwLock<T> {
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self { sema: Sema::new(RW_PERMITS), value: UnsafeCell::new(value) }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Acquire the shared read lock, the future is cancel safe.
+    #[inline]
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.sema.acquire(1).await;
+        RwLockReadGuard { lock: self }
+    }
+
+    /// Acquire the exclusive write lock, the future is cancel safe.
+    #[inline]
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.sema.acquire(RW_PERMITS).await;
+        RwLockWriteGuard { lock: self }
+    }
+
+    /// Returns None when the write lock is held, or someone is waiting.
+    #[inline]
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        if self.sema.try_acquire(1) { Some(RwLockReadGuard { lock: self }) } else { None }
+    }
+
+    /// Returns None when any lock is held, or someone is waiting.
+    #[inline]
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        if self.sema.try_acquire(RW_PERMITS) { Some(RwLockWriteGuard { lock: self }) } else { None }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RwLock")
+    }
+}
+
+/// The shared guard of [RwLock], unlock on drop.
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for RwLockReadGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.lock.sema.release(1);
+    }
+}
+
+/// The exclusive guard of [RwLock], unlock on drop.
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for RwLockWriteGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.lock.sema.release(RW_PERMITS);
+    }
+}
+
+/// An async condition variable, used with [Mutex].
+///
+/// Similar to std `Condvar`, spurious wakeup is possible, use [wait_while](Self::wait_while) to
+/// check the condition in a loop.
+#[derive(Default)]
+pub struct Condvar {
+    waiters: WaitList,
+}
+
+impl Condvar {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Release the lock and wait for notification, re-acquire the lock before return.
+    ///
+    /// The future is cancel safe, the lock is released on cancellation.
+    pub async fn wait<'a, T: ?Sized>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+        let mutex = guard.mutex;
+        // Register before unlock, so that the notification will not be missed
+        let mut listen = self.waiters.listen();
+        poll_once(&mut listen).await;
+        drop(guard);
+        listen.await;
+        mutex.lock().await
+    }
+
+    /// Wait while `condition` returns true, the lock is held when checking the condition.
+    pub async fn wait_while<'a, T: ?Sized, F>(
+        &self, mut guard: MutexGuard<'a, T>, mut condition: F,
+    ) -> MutexGuard<'a, T>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        while condition(&mut *guard) {
+            guard = self.wait(guard).await;
+        }
+        guard
+    }
+
+    /// Wake up one waiter
+    #[inline]
+    pub fn notify_one(&self) {
+        self.waiters.notify_one();
+    }
+
+    /// Wake up all the waiters
+    #[inline]
+    pub fn notify_all(&self) {
+        self.waiters.notify_all();
+    }
+}
+
+impl fmt::Debug for Condvar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Condvar")
+    }
+}
+
+const NOTIFY_NONE: u8 = 0;
+const NOTIFY_ONE: u8 = 1;
+const NOTIFY_ALL: u8 = 2;
+
+struct NotifyWaiter {
+    // How it is notified
+    notified: AtomicU8,
+    waker: StdMutex<Option<Waker>>,
+}
+
+struct NotifyState {
+    permit: bool,
+    // Increased by notify_waiters()
+    generation: u64,
+    waiters: VecDeque<Arc<NotifyWaiter>>,
+}
+
+/// Notify the tasks of an event, without passing a value.
+///
+/// The semantic is the same as tokio's: [notify_one()](Self::notify_one) wakes up one waiter,
+/// or stores a permit for the next [notified()](Self::notified) if no one is waiting.
+/// [notify_waiters()](Self::notify_waiters) wakes up all the current waiters, including the
+/// futures created but not yet polled, without storing a permit.
+pub struct Notify {
+    state: StdMutex<NotifyState>,
+}
+
+impl Notify {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            state: StdMutex::new(NotifyState {
+                permit: false,
+                generation: 0,
+                waiters: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Wait for the notification, the future is cancel safe.
+    #[inline]
+    pub fn notified(&self) -> Notified<'_> {
+        let generation = self.state.lock().unwrap().generation;
+        Notified { notify: self, generation, waiter: None }
+    }
+
+    /// Wake up the first waiter, or store a permit if no one is waiting.
+    pub fn notify_one(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(waiter) = state.waiters.pop_front() {
+            drop(state);
+            Self::wake(&waiter, NOTIFY_ONE);
+        } else {
+            state.permit = true;
+        }
+    }
+
+    /// Wake up all the current waiters.
+    pub fn notify_waiters(&self) {
+        let waiters = {
+            let mut state = self.state.lock().unwrap();
+            state.generation += 1;
+            std::mem::take(&mut state.waiters)
+        };
+        for waiter in waiters {
+            Self::wake(&waiter, NOTIFY_ALL);
+        }
+    }
+
+    fn wake(waiter: &NotifyWaiter, how: u8) {
+        waiter.notified.store(how, Ordering::Release);
+        if let Some(waker) = waiter.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for Notify {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Notify")
+    }
+}
+
+/// The future of [Notify::notified()]
+pub struct Notified<'a> {
+    notify: &'a Notify,
+    generation: u64,
+    waiter: Option<Arc<NotifyWaiter>>,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let _self = self.get_mut();
+        if let Some(waiter) = _self.waiter.as_ref() {
+            if waiter.notified.load(Ordering::Acquire) != NOTIFY_NONE {
+                _self.waiter = None;
+                return Poll::Ready(());
+            }
+            let mut guard = waiter.waker.lock().unwrap();
+            // Check again in case notified before the waker is set
+            if waiter.notified.load(Ordering::Acquire) != NOTIFY_NONE {
+                drop(guard);
+                _self.waiter = None;
+                return Poll::Ready(());
+            }
+            match guard.as_ref() {
+                Some(w) if w.will_wake(cx.waker()) => {}
+                _ => *guard = Some(cx.waker().clone()),
+            }
+            return Poll::Pending;
+        }
+        let mut state = _self.notify.state.lock().unwrap();
+        if state.generation != _self.generation {
+            return Poll::Ready(());
+        }
+        if state.permit {
+            state.permit = false;
+            return Poll::Ready(());
+        }
+        let waiter = Arc::new(NotifyWaiter {
+            notified: AtomicU8::new(NOTIFY_NONE),
+            waker: StdMutex::new(Some(cx.waker().clone())),
+        });
+        state.waiters.push_back(waiter.clone());
+        _self.waiter = Some(waiter);
+        Poll::Pending
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        if let Some(waiter) = self.waiter.take() {
+            let mut state = self.notify.state.lock().unwrap();
+            if let Some(i) = state.waiters.iter().position(|w| Arc::ptr_eq(w, &waiter)) {
+                state.waiters.remove(i);
+            } else if waiter.notified.load(Ordering::Acquire) == NOTIFY_ONE {
+                drop(state);
+                // Pass the notification to the others
+                self.notify.notify_one();
+            }
+        }
+    }
+}
+
+/// A list of waiters without permit, only the registered waiters can be notified.
+#[derive(Default)]
+pub(crate) struct WaitList {
+    waiters: StdMutex<VecDeque<Arc<Waiter>>>,
+}
+
+impl WaitList {
+    /// The returned future registers on first poll.
+    #[inline]
+    pub(crate) fn listen(&self) -> Listen<'_> {
+        Listen { list: self, waiter: None }
+    }
+
+    pub(crate) fn notify_one(&self) {
+        if let Some(waiter) = self.waiters.lock().unwrap().pop_front() {
+            waiter.wake();
+        }
+    }
+
+    pub(crate) fn notify_all(&self) {
+        let waiters = std::mem::take(&mut *self.waiters.lock().unwrap());
+        for waiter in waiters {
+            waiter.wake();
+        }
+    }
+}
+
+/// The future of [WaitList::listen()], cancel safe.
+pub(crate) struct Listen<'a> {
+    list: &'a WaitList,
+    waiter: Option<Arc<Waiter>>,
+}
+
+impl Future for Listen<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let _self = self.get_mut();
+        if let Some(waiter) = _self.waiter.as_ref() {
+            if waiter.is_done() {
+                _self.waiter = None;
+                return Poll::Ready(());
+            }
+            waiter.set_waker(cx.waker());
+            if waiter.is_done() {
+                _self.waiter = None;
+                return Poll::Ready(());
+            }
+        } else {
+            let waiter = Waiter::new(1, cx.waker());
+            _self.list.waiters.lock().unwrap().push_back(waiter.clone());
+            _self.waiter = Some(waiter);
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Listen<'_> {
+    fn drop(&mut self) {
+        if let Some(waiter) = self.waiter.take() {
+            let mut waiters = self.list.waiters.lock().unwrap();
+            if let Some(i) = waiters.iter().position(|w| Arc::ptr_eq(w, &waiter)) {
+                waiters.remove(i);
+            } else if waiter.is_done() {
+                drop(waiters);
+                // Pass the notification to the others
+                self.list.notify_one();
+            }
+        }
+    }
+}
+
+/// Poll the future once for registration, without waiting for it.
+pub(crate) async fn poll_once<F: Future + Unpin>(f: &mut F) {
+    futures_lite::future::poll_fn(|cx| {
+        let _ = Pin::new(&mut *f).poll(cx);
+        Poll::Ready(())
+    })
+    .await
+}
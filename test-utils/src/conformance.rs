@@ -0,0 +1,147 @@
+//! Conformance suite for AsyncRuntime adapters.
+//!
+//! If you are implementing a new runtime adapter, [run_all] is the entry point to check whether
+//! your adapter conforms to orb, it runs all the test cases of this crate, including the
+//! cancellation safety of the sockets and the timing of the timers.
+//!
+//! The groups ([run_exec], [run_time], [run_io], [run_net]) can also be called separately on a
+//! runtime, as the tests of orb-tokio and orb-smol do.
+//!
+//! ```rust,ignore
+//! use orb_test_utils::conformance;
+//!
+//! #[test]
+//! fn test_conformance() {
+//!     conformance::run_all(|| MyRT::new());
+//! }
+//! ```
+
+use crate::{io::*, net::*, runtime::*, time::*};
+use orb::net::{TcpStream, UnixStream};
+use orb::AsyncRuntime;
+use std::fmt::Debug;
+
+/// Run all the test cases against the runtime, each group with a new runtime from `make_rt`.
+///
+/// Panics on the first failure.
+pub fn run_all<RT, F>(make_rt: F)
+where
+    RT: AsyncRuntime + Clone + Debug,
+    RT::AsyncHandle<i32>: Unpin,
+    RT::AsyncHandle<()>: Unpin,
+    F: Fn() -> RT,
+{
+    run_exec(&make_rt());
+    run_time(&make_rt());
+    run_io(&make_rt());
+    run_net(&make_rt());
+}
+
+/// Test cases of AsyncExec, the task handles need to be `Unpin` for `join_timeout()` and
+/// `try_join()`
+pub fn run_exec<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + Clone + Debug,
+    RT::AsyncHandle<i32>: Unpin,
+    RT::AsyncHandle<()>: Unpin,
+{
+    test_spawn_async(rt);
+    test_spawn_named(rt);
+    test_metered(rt);
+    test_spawn_profiled(rt);
+    test_join_timeout(rt);
+    test_try_join(rt);
+    test_spawn_abortable(rt);
+    test_blocking_pool(rt);
+    test_spawn_started(rt);
+    test_block_on_local(rt);
+    test_spawn_blocking::<RT>(rt);
+    test_spawn_blocking_panic(rt);
+}
+
+/// Test cases of AsyncTime
+pub fn run_time<RT: AsyncRuntime + Clone + Debug>(rt: &RT) {
+    test_sleep(rt);
+    test_now(rt);
+    test_timeout(rt);
+    test_tick(rt);
+    test_tick_stream(rt);
+    test_tick_take(rt);
+    test_tick_for_duration(rt);
+    test_tick_missed(rt);
+    test_delay_reset(rt);
+}
+
+/// Test cases of the io utilities
+pub fn run_io<RT: AsyncRuntime + Clone + Debug>(rt: &RT) {
+    test_prefetch_reader(rt);
+    test_futures_compat(rt);
+    test_file_seek(rt);
+    test_file_read_write(rt);
+    test_file_read_at_concurrent(rt);
+    test_auto_flush_writer(rt);
+    test_async_fd_write_only(rt);
+}
+
+/// Test cases of AsyncIO and the net module
+pub fn run_net<RT: AsyncRuntime + Clone + Debug>(rt: &RT) {
+    test_unify_addr_resolve::<RT>(rt);
+    test_resolve_all::<RT>(rt);
+    test_addr_error(rt);
+    test_tcp_client_server(rt);
+    test_unify_tcp_client_server(rt);
+    test_unify_app_keepalive(rt);
+    test_tcp_read_vectored(rt);
+    test_tcp_bind_with_backlog(rt);
+    test_write_fast_path(rt);
+    test_cancel_safety(rt);
+    test_read_buf(rt);
+    test_pool(rt);
+    test_connect_happy_eyeballs(rt);
+    test_tcp_bind_reuse_port(rt);
+    test_tcp_accept_with_addr(rt);
+    test_tcp_connect_with_socket(rt);
+    test_tcp_connect_from(rt);
+    test_connect_options(rt);
+    test_connect_retry(rt);
+    #[cfg(target_os = "linux")]
+    test_tcp_urgent(rt);
+    #[cfg(target_os = "linux")]
+    test_tcp_recv_lowat(rt);
+    #[cfg(target_os = "linux")]
+    test_tcp_mptcp(rt);
+    #[cfg(target_os = "linux")]
+    test_tcp_bind_v6_only(rt);
+    #[cfg(target_os = "linux")]
+    test_in_netns(rt);
+    test_unix_client_server(rt);
+    test_unify_unix_client_server(rt);
+    test_unix_bind_with_backlog(rt);
+    test_unix_connect_timeout(rt);
+    test_accept_timeout(rt);
+    test_listener_typed_local_addr(rt);
+    test_peer_unify_addr(rt);
+    test_async_fd_with_socket(rt);
+    test_copy_bidirectional(rt);
+    test_idle_timeout(rt);
+    test_throttle(rt);
+    test_unify_bind_with_mode(rt);
+    test_unix_accept_with_addr(rt);
+    #[cfg(target_os = "linux")]
+    test_unix_abstract(rt);
+    #[cfg(target_os = "linux")]
+    test_unix_peer_exe(rt);
+    test_listener_incoming(rt);
+    test_stream_stats(rt);
+    test_stream_stack(rt);
+    test_serve_graceful(rt);
+    test_accept_for(rt);
+    test_rpc_client(rt);
+    // Allow run_all() in parallel
+    test_transport_serve::<RT, MemStream>(rt, &temp_name("mem_conformance"));
+    test_transport_serve::<RT, TcpStream<RT>>(rt, "127.0.0.1:0");
+    let path = temp_path("conformance").display().to_string();
+    let _ = std::fs::remove_file(&path);
+    test_transport_serve::<RT, UnixStream<RT>>(rt, &path);
+    let _ = std::fs::remove_file(&path);
+}
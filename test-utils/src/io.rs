@@ -0,0 +1,367 @@
+use captains_log::logfn;
+use orb::io::{
+    AsyncRead, AsyncReadAt, AsyncSeek, AsyncWrite, AsyncWriteAt, AutoFlushWriter, File,
+    FuturesCompat, PrefetchReader,
+};
+use orb::net::{TcpListener, TcpStream};
+use orb::prelude::*;
+use std::io;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const CHUNK: usize = 1024;
+
+/// A unique temp path, allowing the tests run in parallel
+pub(crate) fn temp_name(name: &str) -> String {
+    static SEQ: AtomicUsize = AtomicUsize::new(0);
+    let seq = SEQ.fetch_add(1, Ordering::SeqCst);
+    format!("orb_{}_{}_{}", name, std::process::id(), seq)
+}
+
+pub(crate) fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(temp_name(name))
+}
+
+/// A reader which takes `delay` to return each chunk, optionally fail in the end
+struct SlowReader<RT: AsyncTime> {
+    remain: usize,
+    seq: u8,
+    delay: Duration,
+    fail: bool,
+    _phan: PhantomData<fn() -> RT>,
+}
+
+impl<RT: AsyncTime> SlowReader<RT> {
+    fn new(chunks: usize, delay: Duration, fail: bool) -> Self {
+        Self { remain: chunks, seq: 0, delay, fail, _phan: Default::default() }
+    }
+}
+
+impl<RT: AsyncTime> AsyncRead for SlowReader<RT> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        RT::sleep(self.delay).await;
+        if self.remain == 0 {
+            if self.fail {
+                return Err(io::ErrorKind::ConnectionReset.into());
+            }
+            return Ok(0);
+        }
+        self.remain -= 1;
+        let n = std::cmp::min(buf.len(), CHUNK);
+        buf[..n].fill(self.seq);
+        self.seq = self.seq.wrapping_add(1);
+        Ok(n)
+    }
+}
+
+#[logfn]
+pub fn test_prefetch_reader<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + Clone + std::fmt::Debug,
+{
+    let delay = Duration::from_millis(100);
+    let chunks = 5;
+    rt.block_on(async {
+        let start = Instant::now();
+        let mut reader =
+            PrefetchReader::new(rt.clone(), SlowReader::<RT>::new(chunks, delay, false), CHUNK);
+        let mut buf = vec![0u8; CHUNK];
+        for i in 0..chunks {
+            let n = reader.read(&mut buf).await.expect("read");
+            assert_eq!(n, CHUNK);
+            assert!(buf.iter().all(|b| *b == i as u8));
+            assert!(reader.is_prefetching());
+            // Slow consumer, the next read is in flight meanwhile
+            RT::sleep(delay).await;
+        }
+        assert_eq!(reader.read(&mut buf).await.expect("eof"), 0);
+        assert!(!reader.is_prefetching());
+        assert_eq!(reader.read(&mut buf).await.expect("eof"), 0);
+        let elapsed = start.elapsed();
+        // Without overlapping it takes (chunks * 2 + 1) * delay
+        assert!(elapsed < delay * (chunks as u32 * 2 - 1), "elapsed {:?}", elapsed);
+
+        // Error is returned after the buffered data
+        let mut reader =
+            PrefetchReader::new(rt.clone(), SlowReader::<RT>::new(1, delay, true), CHUNK);
+        let mut half = vec![0u8; CHUNK / 2];
+        assert_eq!(reader.read(&mut half).await.expect("read"), CHUNK / 2);
+        assert_eq!(reader.read(&mut half).await.expect("read"), CHUNK / 2);
+        let e = reader.read(&mut half).await.expect_err("should fail");
+        assert_eq!(e.kind(), io::ErrorKind::ConnectionReset);
+    });
+}
+
+/// Test round trip through futures_io traits of FuturesCompat
+#[logfn]
+pub fn test_futures_compat<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use futures_lite::io::{AsyncReadExt, AsyncWriteExt};
+
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let server = rt.spawn(async move {
+            let mut stream = listener.accept().await.expect("accept");
+            let mut buf = vec![0u8; 64 * 1024];
+            loop {
+                let n = AsyncRead::read(&mut stream, &mut buf).await.expect("server read");
+                if n == 0 {
+                    break;
+                }
+                AsyncWrite::write_all(&mut stream, &buf[..n]).await.expect("server write");
+            }
+        });
+        let client = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+        let mut compat = FuturesCompat::new(client);
+        let data: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+        for chunk in data.chunks(50_000) {
+            compat.write_all(chunk).await.expect("compat write");
+            compat.flush().await.expect("compat flush");
+            let mut echo = vec![0u8; chunk.len()];
+            // Read with small buffer
+            let mut read = 0;
+            while read < echo.len() {
+                let end = std::cmp::min(read + 1000, echo.len());
+                read += compat.read(&mut echo[read..end]).await.expect("compat read");
+            }
+            assert_eq!(&echo, chunk);
+        }
+
+        // Full-duplex with split halves, the write does not disturb the parked read
+        let (mut reader, mut writer) = futures_lite::io::split(compat);
+        let read = async {
+            let mut echo = vec![0u8; data.len()];
+            reader.read_exact(&mut echo).await.expect("compat read");
+            echo
+        };
+        let write = async {
+            RT::sleep(Duration::from_millis(50)).await;
+            writer.write_all(&data).await.expect("compat write");
+            writer.flush().await.expect("compat flush");
+        };
+        let (echo, _) = futures_lite::future::zip(read, write).await;
+        assert!(echo == data);
+        drop((reader, writer));
+        server.await.expect("server");
+    });
+}
+
+/// Test seeking within a file
+#[logfn]
+pub fn test_file_seek<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use std::io::Read;
+
+    let path = temp_path("test_file_seek");
+    let data: Vec<u8> = (0..100u8).collect();
+    std::fs::write(&path, &data).expect("write");
+    rt.block_on(async {
+        let mut file = File::<RT>::open(&path).await.expect("open");
+        let mut buf = [0u8; 4];
+        assert_eq!(file.seek(io::SeekFrom::Start(5)).await.expect("seek"), 5);
+        assert_eq!(file.seek(io::SeekFrom::Current(3)).await.expect("seek"), 8);
+        file.get_ref().read_exact(&mut buf).expect("read");
+        assert_eq!(buf, [8, 9, 10, 11]);
+        assert_eq!(file.stream_position().await.expect("position"), 12);
+        assert_eq!(file.seek(io::SeekFrom::End(-2)).await.expect("seek"), 98);
+        file.rewind().await.expect("rewind");
+        assert_eq!(file.stream_position().await.expect("position"), 0);
+        assert!(file.seek(io::SeekFrom::Current(-1)).await.is_err());
+        assert!(File::<RT>::open(path.with_extension("none")).await.is_err());
+    });
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Test round trip through a temp file
+#[logfn]
+pub fn test_file_read_write<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let path = temp_path("test_file_rw");
+    let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+    rt.block_on(async {
+        let mut file = File::<RT>::create(&path).await.expect("create");
+        file.write_all(&data).await.expect("write");
+        assert_eq!(file.write_at(b"orb", 10).await.expect("write_at"), 3);
+        assert_eq!(file.stream_position().await.expect("position"), data.len() as u64);
+        drop(file);
+
+        let mut file = File::<RT>::open(&path).await.expect("open");
+        let mut buf = vec![0u8; data.len()];
+        file.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf[..10], &data[..10]);
+        assert_eq!(&buf[10..13], b"orb");
+        assert_eq!(&buf[13..], &data[13..]);
+        assert_eq!(file.read(&mut buf).await.expect("eof"), 0);
+        let mut small = [0u8; 5];
+        assert_eq!(file.read_at(&mut small, 9).await.expect("read_at"), 5);
+        assert_eq!(&small, &[9, b'o', b'r', b'b', 13]);
+        assert_eq!(file.read_at(&mut small, data.len() as u64).await.expect("read_at"), 0);
+        // Write to a read-only file
+        assert!(file.write(b"x").await.is_err());
+    });
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Test positional read of two regions concurrently, sharing the file by reference
+#[logfn]
+pub fn test_file_read_at_concurrent<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let path = temp_path("test_file_read_at");
+    let len = 1024 * 1024;
+    rt.block_on(async {
+        let file = File::<RT>::from_std(
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&path)
+                .expect("create"),
+        );
+        let (first, second) = futures_lite::future::zip(
+            file.write_all_at(&vec![1u8; len], 0),
+            file.write_all_at(&vec![2u8; len], len as u64),
+        )
+        .await;
+        first.expect("write_all_at");
+        second.expect("write_all_at");
+
+        let mut buf1 = vec![0u8; len];
+        let mut buf2 = vec![0u8; len];
+        let (first, second) = futures_lite::future::zip(
+            file.read_exact_at(&mut buf1, 0),
+            file.read_exact_at(&mut buf2, len as u64),
+        )
+        .await;
+        first.expect("read_exact_at");
+        second.expect("read_exact_at");
+        assert!(buf1.iter().all(|b| *b == 1));
+        assert!(buf2.iter().all(|b| *b == 2));
+        // The current offset is not changed
+        let mut file = file;
+        assert_eq!(file.stream_position().await.expect("position"), 0);
+        let e = file.read_exact_at(&mut buf1, len as u64 + 1).await.expect_err("eof");
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+    });
+    let _ = std::fs::remove_file(&path);
+}
+
+/// A writer recording the data of each write call, which takes `delay` for each write
+struct RecordWriter<RT: AsyncTime> {
+    writes: Arc<Mutex<Vec<Vec<u8>>>>,
+    // The bytes written at the last flush
+    flushed: Arc<AtomicUsize>,
+    delay: Duration,
+    _phan: PhantomData<fn() -> RT>,
+}
+
+impl<RT: AsyncTime> AsyncWrite for RecordWriter<RT> {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        RT::sleep(self.delay).await;
+        self.writes.lock().unwrap().push(buf.to_vec());
+        Ok(buf.len())
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        let len = self.writes.lock().unwrap().iter().map(|w| w.len()).sum();
+        self.flushed.store(len, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// Test the writes in one task turn are flushed together
+#[logfn]
+pub fn test_auto_flush_writer<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let writes = Arc::new(Mutex::new(Vec::new()));
+        let flushed = Arc::new(AtomicUsize::new(0));
+        let delay = Duration::from_millis(20);
+        let record = RecordWriter::<RT> {
+            writes: writes.clone(),
+            flushed: flushed.clone(),
+            delay,
+            _phan: Default::default(),
+        };
+        let mut writer = AutoFlushWriter::new(rt, record, 1024);
+        for i in 0..10u8 {
+            writer.write_all(&[i; 10]).await.expect("write");
+        }
+        RT::sleep(delay * 5).await;
+        {
+            let writes = writes.lock().unwrap();
+            // The companion task might be running on other thread, and catch the first write
+            // alone. The rest are coalesced while it's busy.
+            assert!(writes.len() <= 2, "not coalesced: {} writes", writes.len());
+            let data: Vec<u8> = writes.concat();
+            assert_eq!(data.len(), 100);
+            assert_eq!(&data[90..], &[9u8; 10]);
+        }
+        // Exceeding the capacity waits for the flush
+        let big = vec![1u8; 3000];
+        writer.write_all(&big).await.expect("write");
+        writer.flush().await.expect("flush");
+        assert_eq!(writes.lock().unwrap().concat().len(), 3100);
+        // The underlying writer is flushed as well
+        assert_eq!(flushed.load(Ordering::SeqCst), 3100);
+        writer.write_all(b"tail").await.expect("write");
+        let record = writer.close().await.expect("close");
+        assert!(record.writes.lock().unwrap().concat().ends_with(b"tail"));
+        assert_eq!(flushed.load(Ordering::SeqCst), 3104);
+    });
+}
+
+/// Test the fd registered with write-only interest is woken up when writable
+#[logfn]
+pub fn test_async_fd_write_only<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::io::{AsyncFd, Interest};
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    rt.block_on(async {
+        let (tx, rx) = UnixStream::pair().expect("pair");
+        tx.set_nonblocking(true).expect("nonblocking");
+        let fd = RT::to_async_fd(tx, Interest::WRITE).expect("register");
+        // Fill up the socket buffer
+        let chunk = [1u8; 4096];
+        let mut total = 0;
+        loop {
+            match (&*fd).write(&chunk) {
+                Ok(n) => total += n,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => panic!("write: {}", e),
+            }
+        }
+        let reader = std::thread::spawn(move || {
+            let mut rx = rx;
+            std::thread::sleep(Duration::from_millis(50));
+            let mut buf = vec![0u8; 65536];
+            let mut received = 0;
+            loop {
+                match rx.read(&mut buf).expect("read") {
+                    0 => return received,
+                    n => received += n,
+                }
+            }
+        });
+        let r = RT::timeout(Duration::from_secs(5), fd.async_write(|mut s| s.write(&chunk))).await;
+        total += r.expect("timeout").expect("write");
+        drop(fd);
+        assert_eq!(reader.join().expect("reader"), total);
+    });
+}
@@ -1,5 +1,8 @@
 use captains_log::{recipe, ConsoleTarget, Level};
 
+pub mod conformance;
+pub mod io;
+pub mod mock;
 pub mod net;
 pub mod runtime;
 pub mod time;
@@ -0,0 +1,498 @@
+//! A deterministic runtime for unit tests, with virtual time.
+//!
+//! [MockRT] implements [AsyncExec] and [AsyncTime] without a reactor: the tasks run on the
+//! thread calling `block_on()`, and `sleep()` / `tick()` wait on a virtual clock, which moves
+//! only by [MockRT::advance()], or jumps to the next timer when all the tasks are waiting. So the
+//! timeout logic can be tested in microseconds instead of real seconds:
+//!
+//! ```rust
+//! use orb::prelude::*;
+//! use orb_test_utils::mock::MockRT;
+//! use std::time::Duration;
+//!
+//! let rt = MockRT::new();
+//! let start = rt.now();
+//! rt.block_on(async {
+//!     let r = MockRT::timeout(Duration::from_secs(3600), std::future::pending::<()>()).await;
+//!     assert!(r.is_err());
+//! });
+//! assert_eq!(rt.now() - start, Duration::from_secs(3600));
+//! ```
+//!
+//! NOTE: The time functions of [AsyncTime] are static, they find the clock of the current
+//! `block_on()` with a thread local, and panic if called outside of it.
+
+use futures_lite::FutureExt;
+use orb::runtime::{AsyncExec, AsyncHandle, BlockingPool, PoolThreadHandle, RuntimeFlavor};
+use orb::time::{count_missed_ticks, AsyncTime, TimeDelay, TimeInterval};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::Thread;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<Clock>>> = const { RefCell::new(None) };
+}
+
+static BLOCKING: LazyLock<BlockingPool> = LazyLock::new(|| BlockingPool::new("mock-blocking", 4));
+
+fn current_clock() -> Arc<Clock> {
+    CURRENT.with(|c| c.borrow().clone()).expect("MockRT time used outside of MockRT::block_on()")
+}
+
+struct ClockState {
+    // The virtual time since the clock created
+    now: Duration,
+    seq: u64,
+    timers: BTreeMap<(Duration, u64), Waker>,
+}
+
+struct Clock {
+    base: Instant,
+    state: Mutex<ClockState>,
+}
+
+impl Clock {
+    fn new() -> Self {
+        Self {
+            base: Instant::now(),
+            state: Mutex::new(ClockState { now: Duration::ZERO, seq: 0, timers: BTreeMap::new() }),
+        }
+    }
+
+    fn now(&self) -> Duration {
+        self.state.lock().unwrap().now
+    }
+
+    /// Move to `to` (if later than now), and wake the timers due
+    fn set(&self, to: Duration) {
+        let due = {
+            let mut state = self.state.lock().unwrap();
+            if to > state.now {
+                state.now = to;
+            }
+            let now = state.now;
+            let later = state.timers.split_off(&(now, u64::MAX));
+            std::mem::replace(&mut state.timers, later)
+        };
+        for (_, waker) in due {
+            waker.wake();
+        }
+    }
+
+    /// Jump to the earliest timer, returns false if there's no timer.
+    fn advance_to_next(&self) -> bool {
+        let next = self.state.lock().unwrap().timers.keys().next().map(|(deadline, _)| *deadline);
+        match next {
+            Some(deadline) => {
+                self.set(deadline);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns true if `deadline` passed, otherwise register the waker under `id`.
+    fn poll_timer(&self, deadline: Duration, id: &mut Option<u64>, waker: &Waker) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.now >= deadline {
+            if let Some(id) = id.take() {
+                state.timers.remove(&(deadline, id));
+            }
+            return true;
+        }
+        let seq = match *id {
+            Some(seq) => seq,
+            None => {
+                state.seq += 1;
+                *id = Some(state.seq);
+                state.seq
+            }
+        };
+        state.timers.insert((deadline, seq), waker.clone());
+        false
+    }
+
+    fn cancel_timer(&self, deadline: Duration, id: Option<u64>) {
+        if let Some(id) = id {
+            self.state.lock().unwrap().timers.remove(&(deadline, id));
+        }
+    }
+}
+
+/// The run queue, the tasks scheduled are run by `block_on()`
+struct Exec {
+    queue: Mutex<VecDeque<async_task::Runnable>>,
+    // The thread inside block_on(), to unpark on schedule
+    thread: Mutex<Option<Thread>>,
+}
+
+impl Exec {
+    fn schedule(&self, runnable: async_task::Runnable) {
+        self.queue.lock().unwrap().push_back(runnable);
+        if let Some(thread) = self.thread.lock().unwrap().as_ref() {
+            thread.unpark();
+        }
+    }
+
+    fn pop(&self) -> Option<async_task::Runnable> {
+        self.queue.lock().unwrap().pop_front()
+    }
+}
+
+struct MainWaker {
+    woken: AtomicBool,
+    thread: Thread,
+}
+
+impl Wake for MainWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
+}
+
+/// Set the clock of current thread, restore on drop
+struct EnterGuard(Option<Arc<Clock>>);
+
+impl EnterGuard {
+    fn new(clock: Arc<Clock>) -> Self {
+        Self(CURRENT.with(|c| c.borrow_mut().replace(clock)))
+    }
+}
+
+impl Drop for EnterGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|c| *c.borrow_mut() = self.0.take());
+    }
+}
+
+/// A single-threaded runtime with virtual time, for deterministic tests.
+///
+/// The tasks only run inside `block_on()`. When all the tasks (including the future of
+/// `block_on()`) are waiting, the clock jumps to the earliest timer, so the sleeps never wait
+/// for real time. If there's no timer either, it parks the thread for the wake up from other
+/// threads (e.g. `spawn_blocking()`).
+///
+/// NOTE: Waiting for other threads while a timer is pending fires the timer immediately. The
+/// panic inside the tasks is captured, the handle returns `Err(())`.
+#[derive(Clone)]
+pub struct MockRT {
+    exec: Arc<Exec>,
+    clock: Arc<Clock>,
+}
+
+impl MockRT {
+    pub fn new() -> Self {
+        Self {
+            exec: Arc::new(Exec { queue: Mutex::new(VecDeque::new()), thread: Mutex::new(None) }),
+            clock: Arc::new(Clock::new()),
+        }
+    }
+
+    /// Move the virtual clock forward by `d`, and wake the timers due.
+    pub fn advance(&self, d: Duration) {
+        self.clock.set(self.clock.now() + d);
+    }
+
+    /// The virtual time of the clock
+    pub fn now(&self) -> Instant {
+        self.clock.base + self.clock.now()
+    }
+}
+
+impl Default for MockRT {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for MockRT {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "mock(+{:?})", self.clock.now())
+    }
+}
+
+impl AsyncExec for MockRT {
+    type AsyncHandle<R: Send> = MockJoinHandle<R>;
+
+    type ThreadHandle<R: Send> = PoolThreadHandle<R>;
+
+    fn spawn<F, R>(&self, f: F) -> Self::AsyncHandle<R>
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let exec = self.exec.clone();
+        let f = AssertUnwindSafe(f).catch_unwind();
+        let (runnable, task) = async_task::spawn(f, move |r| exec.schedule(r));
+        runnable.schedule();
+        MockJoinHandle(Some(task))
+    }
+
+    #[inline]
+    fn spawn_detach<F, R>(&self, f: F)
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.spawn(f).detach();
+    }
+
+    /// Run on a shared pool of real threads
+    #[inline]
+    fn spawn_blocking<F, R>(f: F) -> Self::ThreadHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        BLOCKING.spawn(f)
+    }
+
+    #[inline]
+    fn block_on<F, R>(&self, f: F) -> R
+    where
+        F: Future<Output = R> + Send,
+        R: Send + 'static,
+    {
+        self.block_on_local(f)
+    }
+
+    fn block_on_local<F, R>(&self, f: F) -> R
+    where
+        F: Future<Output = R>,
+    {
+        let _guard = EnterGuard::new(self.clock.clone());
+        let thread = std::thread::current();
+        *self.exec.thread.lock().unwrap() = Some(thread.clone());
+        let main = Arc::new(MainWaker { woken: AtomicBool::new(true), thread });
+        let waker = Waker::from(main.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut f = pin!(f);
+        loop {
+            if main.woken.swap(false, Ordering::AcqRel) {
+                if let Poll::Ready(r) = f.as_mut().poll(&mut cx) {
+                    return r;
+                }
+            }
+            let mut ran = false;
+            while let Some(runnable) = self.exec.pop() {
+                runnable.run();
+                ran = true;
+            }
+            if ran || main.woken.load(Ordering::Acquire) {
+                continue;
+            }
+            // All the tasks are waiting
+            if !self.clock.advance_to_next() {
+                std::thread::park();
+            }
+        }
+    }
+
+    #[inline]
+    fn worker_count(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    #[inline]
+    fn flavor(&self) -> RuntimeFlavor {
+        RuntimeFlavor::CurrentThread
+    }
+}
+
+impl AsyncTime for MockRT {
+    type Interval = MockInterval;
+
+    type Delay = MockDelay;
+
+    #[inline]
+    fn sleep(d: Duration) -> impl Future + Send {
+        let clock = current_clock();
+        let deadline = clock.now() + d;
+        MockSleep { clock, deadline, id: None }
+    }
+
+    /// The first tick is after `d`
+    #[inline]
+    fn tick(d: Duration) -> Self::Interval {
+        let clock = current_clock();
+        let next = clock.now() + d;
+        MockInterval { clock, period: d, next, id: None, missed: 0 }
+    }
+
+    #[inline]
+    fn delay(d: Duration) -> Self::Delay {
+        let clock = current_clock();
+        let deadline = clock.now() + d;
+        MockDelay { clock, deadline: Some(deadline), id: None }
+    }
+
+    /// The virtual time
+    #[inline]
+    fn now() -> Instant {
+        let clock = current_clock();
+        clock.base + clock.now()
+    }
+}
+
+/// The future of [MockRT::sleep()](AsyncTime::sleep)
+pub struct MockSleep {
+    clock: Arc<Clock>,
+    deadline: Duration,
+    id: Option<u64>,
+}
+
+impl Future for MockSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if this.clock.poll_timer(this.deadline, &mut this.id, cx.waker()) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for MockSleep {
+    fn drop(&mut self) {
+        self.clock.cancel_timer(self.deadline, self.id);
+    }
+}
+
+/// The timer of [MockRT::delay()](AsyncTime::delay)
+pub struct MockDelay {
+    clock: Arc<Clock>,
+    // None when cancelled
+    deadline: Option<Duration>,
+    id: Option<u64>,
+}
+
+impl MockDelay {
+    fn cancel_timer(&mut self) {
+        if let Some(deadline) = self.deadline {
+            self.clock.cancel_timer(deadline, self.id.take());
+        }
+    }
+}
+
+impl Future for MockDelay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        match this.deadline {
+            Some(deadline) if this.clock.poll_timer(deadline, &mut this.id, cx.waker()) => {
+                Poll::Ready(())
+            }
+            _ => Poll::Pending,
+        }
+    }
+}
+
+impl TimeDelay for MockDelay {
+    fn reset(&mut self, d: Duration) {
+        self.cancel_timer();
+        self.deadline = Some(self.clock.now() + d);
+    }
+
+    fn cancel(&mut self) {
+        self.cancel_timer();
+        self.deadline = None;
+    }
+}
+
+impl Drop for MockDelay {
+    fn drop(&mut self) {
+        self.cancel_timer();
+    }
+}
+
+/// The interval of [MockRT::tick()](AsyncTime::tick)
+pub struct MockInterval {
+    clock: Arc<Clock>,
+    period: Duration,
+    next: Duration,
+    id: Option<u64>,
+    missed: u32,
+}
+
+impl TimeInterval for MockInterval {
+    fn poll_tick(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Instant> {
+        let this = self.get_mut();
+        if this.clock.poll_timer(this.next, &mut this.id, ctx.waker()) {
+            let at = this.clock.base + this.next;
+            let now = this.clock.base + this.clock.now();
+            this.missed = count_missed_ticks(at, now, this.period);
+            this.next += this.period;
+            Poll::Ready(at)
+        } else {
+            Poll::Pending
+        }
+    }
+
+    #[inline]
+    fn missed_ticks(&self) -> u32 {
+        self.missed
+    }
+}
+
+impl Drop for MockInterval {
+    fn drop(&mut self) {
+        self.clock.cancel_timer(self.next, self.id);
+    }
+}
+
+/// The [AsyncHandle] of [MockRT]. Detach on drop, like the other runtimes.
+pub struct MockJoinHandle<T>(Option<async_task::Task<Result<T, Box<dyn Any + Send>>>>);
+
+impl<T: Send> AsyncHandle<T> for MockJoinHandle<T> {
+    #[inline]
+    fn is_finished(&self) -> bool {
+        self.0.as_ref().unwrap().is_finished()
+    }
+
+    #[inline]
+    fn detach(mut self) {
+        self.0.take().unwrap().detach();
+    }
+
+    #[inline]
+    fn abort(mut self) {
+        // Dropping the task cancels it
+        drop(self.0.take());
+    }
+}
+
+impl<T> Future for MockJoinHandle<T> {
+    type Output = Result<T, ()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.get_mut().0.as_mut() {
+            Some(task) => Pin::new(task).poll(cx).map(|r| r.map_err(|_| ())),
+            None => Poll::Ready(Err(())),
+        }
+    }
+}
+
+impl<T> Drop for MockJoinHandle<T> {
+    fn drop(&mut self) {
+        if let Some(task) = self.0.take() {
+            task.detach();
+        }
+    }
+}
@@ -1,7 +1,22 @@
+use crate::io::{temp_name, temp_path};
 use captains_log::logfn;
-use orb::io::{AsyncRead, AsyncWrite};
-use orb::net::{TcpListener, TcpStream, UnifyListener, UnifyStream, UnixListener, UnixStream};
+use futures_lite::future::poll_fn;
+use orb::io::{AsyncRead, AsyncWrite, ReadBuf, StreamLayer, StreamStack};
+use orb::net::{
+    accept_for, serve, serve_graceful, AsyncListener, StreamStats, StreamStatsSnapshot,
+    TcpListener, TcpStream, Transport, UnifyListener, UnifyStream, UnixListener, UnixStream,
+};
 use orb::prelude::*;
+use orb::utils::ShutdownToken;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::os::fd::RawFd;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::task::{Poll, Waker};
 use std::time::Duration;
 
 /// Test UnifyAddr resolve functionality
@@ -48,6 +63,102 @@ pub fn test_unify_addr_resolve<RT: AsyncRuntime + std::fmt::Debug>(rt: &RT) {
         // Test invalid address resolution
         let invalid_addr = UnifyAddr::resolve::<RT>("invalid_address_that_does_not_exist");
         assert!(invalid_addr.await.is_err());
+
+        // Tell the malformed input from the dns failure
+        use orb::net::ResolveError;
+        for malformed in ["no_port", "host:port", "host:99999", ":80"] {
+            match UnifyAddr::resolve::<RT>(malformed).await {
+                Err(ResolveError::Parse(_)) => {}
+                r => panic!("{}: expect parse error, got {:?}", malformed, r),
+            }
+        }
+        match UnifyAddr::resolve::<RT>("orb-test.invalid:80").await {
+            Err(e @ ResolveError::Dns(_)) => assert_eq!(e.kind(), io::ErrorKind::NotFound),
+            r => panic!("expect dns error, got {:?}", r),
+        }
+    });
+}
+
+/// Test resolve_all() keeps all the addresses, and a single one for the literal IP or path
+#[logfn]
+pub fn test_resolve_all<RT: AsyncRuntime + std::fmt::Debug>(rt: &RT) {
+    use orb::net::{ResolveAddr, UnifyAddr};
+    use std::net::SocketAddr;
+    rt.block_on(async {
+        let addrs = "localhost:8080".resolve_all::<RT>().await.expect("resolve localhost");
+        assert!(!addrs.is_empty());
+        for addr in &addrs {
+            assert!(matches!(addr, UnifyAddr::Socket(a) if a.port() == 8080), "{:?}", addr);
+        }
+        // The first one agrees with resolve()
+        assert_eq!(addrs[0], "localhost:8080".resolve::<RT>().await.expect("resolve"));
+        // The host name after the tcp scheme
+        let with_scheme = "tcp://localhost:8080".resolve_all::<RT>().await.expect("resolve");
+        assert_eq!(with_scheme, addrs);
+
+        let addrs = "127.0.0.1:8080".to_string().resolve_all::<RT>().await.expect("resolve");
+        assert_eq!(addrs, vec![UnifyAddr::Socket("127.0.0.1:8080".parse().unwrap())]);
+        let addrs = "/tmp/test.sock".resolve_all::<RT>().await.expect("resolve");
+        assert_eq!(addrs, vec![UnifyAddr::Path("/tmp/test.sock".into())]);
+        let sock: SocketAddr = "[::1]:80".parse().unwrap();
+        assert_eq!(sock.resolve_all::<RT>().await.expect("resolve"), vec![UnifyAddr::Socket(sock)]);
+
+        let e = "invalid_address_that_does_not_exist".resolve_all::<RT>().await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        let e = "orb-test.invalid:80".resolve_all::<RT>().await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    });
+}
+
+/// Test the error kinds of bad addresses, and the typed AddrError source
+#[logfn]
+pub fn test_addr_error<RT: AsyncRuntime + std::fmt::Debug>(rt: &RT) {
+    use orb::net::{AddrError, ResolveError};
+
+    fn addr_error(e: &io::Error) -> &AddrError {
+        e.get_ref().and_then(|e| e.downcast_ref::<AddrError>()).expect("AddrError source")
+    }
+
+    rt.block_on(async {
+        // A unix path for tcp
+        let e = TcpStream::<RT>::connect("/tmp/orb_addr_error.sock").await.expect_err("path");
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert!(matches!(addr_error(&e), AddrError::WrongScheme(_)));
+        let e = TcpListener::<RT>::bind("/tmp/orb_addr_error.sock").await.expect_err("path");
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert!(matches!(addr_error(&e), AddrError::WrongScheme(_)));
+
+        // Malformed, no dns lookup
+        let e = UnifyStream::<RT>::connect("invalid_address_that_does_not_exist")
+            .await
+            .expect_err("parse");
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert!(matches!(addr_error(&e), AddrError::InvalidAddr(_)));
+        let e = UnifyListener::<RT>::bind("invalid_address_that_does_not_exist")
+            .await
+            .expect_err("parse");
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+
+        // The ".invalid" TLD never resolves (RFC 6761)
+        let e = UnifyStream::<RT>::connect("orb-test.invalid:80").await.expect_err("resolve");
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+        // The error of the resolver is kept as the source
+        match addr_error(&e) {
+            AddrError::ResolveFailed(_, cause) => {
+                assert!(matches!(&**cause, ResolveError::Dns(_)), "{:?}", cause);
+                let source = std::error::Error::source(addr_error(&e)).expect("source");
+                assert!(source.downcast_ref::<ResolveError>().is_some());
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        let e = TcpListener::<RT>::bind("orb-test.invalid:80").await.expect_err("resolve");
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+
+        // The fd is not touched for invalid addr
+        let e =
+            unsafe { UnifyListener::<RT>::try_from_raw_fd("no_port", -1) }.expect_err("invalid");
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert!(matches!(addr_error(&e), AddrError::InvalidAddr(a) if a == "\"no_port\""));
     });
 }
 
@@ -304,3 +415,1891 @@ where
     // Clean up the socket file after test
     let _ = std::fs::remove_file("/tmp/test_unify_socket_client_server");
 }
+
+/// Test the keepalive stream strips the ping, and errors after the peer goes silent, even when
+/// the stream is not being read
+#[logfn]
+pub fn test_unify_app_keepalive<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    const PING: &[u8] = b"\0PING\0";
+    let period = Duration::from_millis(50);
+    let timeout = Duration::from_millis(300);
+    rt.block_on(async {
+        let mut listener = UnifyListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let server = rt.spawn(async move {
+            let mut stream = listener.accept().await.expect("accept");
+            // The ping split across writes
+            stream.write_all(b"hello\0PI").await.expect("write");
+            RT::sleep(Duration::from_millis(10)).await;
+            stream.write_all(b"NG\0world\0P").await.expect("write");
+            // Then go silent, receive the pings until the client gives up
+            let mut received = Vec::new();
+            let mut buf = [0u8; 64];
+            loop {
+                match stream.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => received.extend_from_slice(&buf[..n]),
+                }
+            }
+            received
+        });
+        let stream = UnifyStream::<RT>::connect(&server_addr).await.expect("connect");
+        let mut stream = stream.with_app_keepalive(rt, PING.to_vec(), period, timeout);
+        let mut buf = [0u8; 64];
+        let mut data = Vec::new();
+        let start = std::time::Instant::now();
+        let e = loop {
+            match stream.read(&mut buf).await {
+                Ok(n) => {
+                    assert!(n > 0, "unexpected eof");
+                    data.extend_from_slice(&buf[..n]);
+                }
+                Err(e) => break e,
+            }
+        };
+        let elapsed = start.elapsed();
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+        // The partial ping in the end is withheld
+        assert_eq!(data, b"helloworld");
+        assert!(elapsed >= timeout && elapsed < timeout * 3, "elapsed {:?}", elapsed);
+        assert_eq!(stream.write(b"x").await.expect_err("timeout").kind(), io::ErrorKind::TimedOut);
+        // The server sees pings while idle, and eof after timeout
+        let received = server.await.expect("server");
+        assert!(received.len() >= PING.len() * 2, "received {:?}", received);
+        assert!(received.chunks(PING.len()).all(|c| c == PING));
+
+        // The idle connection not being read also pings, and is closed after the peer goes silent
+        let mut listener = UnifyListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let stream = UnifyStream::<RT>::connect(&server_addr).await.expect("connect");
+        let _stream = stream.with_app_keepalive(rt, PING.to_vec(), period, timeout);
+        let mut peer = listener.accept().await.expect("accept");
+        let start = std::time::Instant::now();
+        let mut received = Vec::new();
+        loop {
+            match peer.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => received.extend_from_slice(&buf[..n]),
+            }
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= timeout && elapsed < timeout * 3, "elapsed {:?}", elapsed);
+        assert!(received.len() >= PING.len() * 2, "received {:?}", received);
+        assert!(received.chunks(PING.len()).all(|c| c == PING));
+    });
+}
+
+/// Test scatter read into a header buffer and a body buffer
+#[logfn]
+pub fn test_tcp_read_vectored<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener =
+            TcpListener::<RT>::bind("127.0.0.1:0").await.expect("Failed to create TCP listener");
+        let server_addr = listener.local_addr().expect("Failed to get local address");
+
+        let server_handle = rt.spawn(async move {
+            let mut stream = listener.accept().await.expect("Failed to accept connection");
+            stream.write_all(b"headbody-data").await.expect("Failed to write to client");
+            true
+        });
+
+        let mut client_stream =
+            TcpStream::<RT>::connect(&server_addr).await.expect("Failed to connect to server");
+        assert!(server_handle.await.expect("Server task failed"));
+
+        let mut header = [0u8; 4];
+        let mut body = [0u8; 9];
+        let mut total = 0;
+        while total < header.len() + body.len() {
+            // skip the part already filled
+            let (h, b) = if total < header.len() {
+                (&mut header[total..], &mut body[..])
+            } else {
+                (&mut header[4..], &mut body[total - 4..])
+            };
+            let mut bufs = [std::io::IoSliceMut::new(h), std::io::IoSliceMut::new(b)];
+            let n = client_stream.read_vectored(&mut bufs).await.expect("Failed to read");
+            assert!(n > 0);
+            total += n;
+        }
+        assert_eq!(&header, b"head");
+        assert_eq!(&body, b"body-data");
+    });
+}
+
+/// Test sending and receiving TCP urgent byte, the normal data flow is not affected
+#[cfg(target_os = "linux")]
+#[logfn]
+pub fn test_tcp_urgent<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let mut client = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+        let mut server = listener.accept().await.expect("accept");
+        let e = server.recv_urgent().expect_err("nothing pending");
+        assert_eq!(e.kind(), io::ErrorKind::WouldBlock);
+
+        client.write_all(b"abc").await.expect("write");
+        client.send_urgent(b'!').expect("send_urgent");
+        client.write_all(b"def").await.expect("write");
+
+        // The normal read stops at the mark
+        let mut buf = [0u8; 3];
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"abc");
+        let mut urgent = None;
+        for _ in 0..100 {
+            match server.recv_urgent() {
+                Ok(b) => {
+                    urgent = Some(b);
+                    break;
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    RT::sleep(Duration::from_millis(10)).await;
+                }
+                Err(e) => panic!("recv_urgent {:?}", e),
+            }
+        }
+        assert_eq!(urgent, Some(b'!'));
+        assert!(server.at_mark().expect("at_mark"));
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"def");
+        assert!(!server.at_mark().expect("at_mark"));
+
+        // Inline mode, the urgent byte is in the normal stream
+        server.set_oob_inline(true).expect("set_oob_inline");
+        client.send_urgent(b'?').expect("send_urgent");
+        client.write_all(b"g").await.expect("write");
+        let mut buf = [0u8; 2];
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"?g");
+    });
+}
+
+/// Test the read is not woken up until the SO_RCVLOWAT bytes arrived
+#[cfg(target_os = "linux")]
+#[logfn]
+pub fn test_tcp_recv_lowat<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let mut client = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+        let mut server = listener.accept().await.expect("accept");
+        server.set_recv_lowat(8).expect("set_recv_lowat");
+        assert_eq!(server.recv_lowat().expect("recv_lowat"), 8);
+        // Not changeable on Linux
+        assert!(server.set_send_lowat(8).is_err());
+
+        let reader = rt.spawn(async move {
+            let mut buf = [0u8; 16];
+            let n = server.read(&mut buf).await.expect("read");
+            buf[..n].to_vec()
+        });
+        RT::sleep(Duration::from_millis(50)).await;
+        client.write_all(b"1234").await.expect("write");
+        RT::sleep(Duration::from_millis(100)).await;
+        assert!(!reader.is_finished(), "woken up before low-water mark");
+        client.write_all(b"5678").await.expect("write");
+        assert_eq!(reader.await.expect("reader"), b"12345678");
+    });
+}
+
+/// Test MPTCP connection over loopback, skipped if disabled in the kernel
+#[cfg(target_os = "linux")]
+#[logfn]
+pub fn test_tcp_mptcp<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let enabled = std::fs::read_to_string("/proc/sys/net/mptcp/enabled").unwrap_or_default();
+    if enabled.trim() != "1" {
+        log::warn!("mptcp not enabled, skip");
+        return;
+    }
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind_mptcp("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let mut client = TcpStream::<RT>::connect_mptcp(&server_addr).await.expect("connect");
+        let mut server = listener.accept().await.expect("accept");
+        assert!(client.is_multipath().expect("is_multipath"));
+        assert!(server.is_multipath().expect("is_multipath"));
+
+        client.write_all(b"multipath").await.expect("write");
+        let mut buf = [0u8; 9];
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"multipath");
+        server.write_all(b"ok").await.expect("write");
+        let mut buf = [0u8; 2];
+        client.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"ok");
+
+        // Plain TCP client to MPTCP listener
+        let plain = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+        assert!(!plain.is_multipath().expect("is_multipath"));
+        listener.accept().await.expect("accept");
+    });
+}
+
+/// Test the cancelled accept / read of the sockets lose nothing, so they can be raced with the
+/// timeouts
+#[logfn]
+pub fn test_cancel_safety<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let r = RT::timeout(Duration::from_millis(20), listener.accept()).await;
+        assert!(r.is_err());
+        let mut client = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+        let mut server = listener.accept().await.expect("accept");
+
+        let count = 200u32;
+        let write = async {
+            for i in 0..count {
+                if i % 10 == 0 {
+                    RT::sleep(Duration::from_millis(3)).await;
+                }
+                server.write_all(&i.to_be_bytes()).await.expect("write");
+            }
+            server
+        };
+        let read = async {
+            let mut received = Vec::new();
+            let mut buf = [0u8; 7];
+            while received.len() < count as usize * 4 {
+                // Cancelled often while the data is on the way
+                let r = RT::timeout(Duration::from_millis(1), client.read(&mut buf)).await;
+                if let Ok(r) = r {
+                    let n = r.expect("read");
+                    assert!(n > 0);
+                    received.extend_from_slice(&buf[..n]);
+                }
+            }
+            received
+        };
+        let (_server, received) = futures_lite::future::zip(write, read).await;
+        for (i, b) in received.chunks(4).enumerate() {
+            assert_eq!(u32::from_be_bytes(b.try_into().unwrap()), i as u32);
+        }
+    });
+}
+
+/// Test the small writes on a writable socket complete on the first poll, and the large write
+/// beyond the socket buffer waits on WouldBlock and delivers the data in order
+#[logfn]
+pub fn test_write_fast_path<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let mut client = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+        let mut server = listener.accept().await.expect("accept");
+        for i in 0..1000u32 {
+            let r = futures_lite::future::poll_once(client.write(&i.to_be_bytes())).await;
+            assert_eq!(r.expect("pending on small write").expect("write"), 4);
+        }
+        let mut buf = [0u8; 4];
+        for i in 0..1000u32 {
+            server.read_exact(&mut buf).await.expect("read");
+            assert_eq!(u32::from_be_bytes(buf), i);
+        }
+
+        // Larger than the socket buffer
+        let data: Vec<u8> = (0..8 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let len = data.len();
+        let reader = rt.spawn(async move {
+            let mut received = vec![0u8; len];
+            server.read_exact(&mut received).await.expect("read");
+            received
+        });
+        client.write_all(&data).await.expect("write_all");
+        assert!(reader.await.expect("reader") == data);
+
+        let path = temp_path("test_write_fast_path").display().to_string();
+        let _ = std::fs::remove_file(&path);
+        let mut listener = UnixListener::<RT>::bind(&path).expect("bind");
+        let mut client = UnixStream::<RT>::connect(&path).await.expect("connect");
+        let mut server = listener.accept().await.expect("accept");
+        let r = futures_lite::future::poll_once(client.write(b"unix")).await;
+        assert_eq!(r.expect("pending on small write").expect("write"), 4);
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"unix");
+        let _ = std::fs::remove_file(&path);
+    });
+}
+
+/// Test read_buf() of the sockets fills the uninitialized buffer without touching the bytes
+/// beyond the data received
+#[logfn]
+pub fn test_read_buf<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let mut client = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+        let mut server = listener.accept().await.expect("accept");
+        client.write_all(b"hello").await.expect("write");
+        // The sentinel stays if the bytes are not zeroed by the default path
+        let mut storage = [MaybeUninit::new(0xaau8); 64];
+        let mut buf = ReadBuf::uninit(&mut storage);
+        let n = server.read_buf(&mut buf).await.expect("read_buf");
+        assert_eq!(n, 5);
+        assert_eq!(buf.filled(), b"hello");
+        assert_eq!(buf.initialized().len(), 5);
+        assert_eq!(buf.remaining(), 59);
+        client.write_all(b" world").await.expect("write");
+        drop(client);
+        while server.read_buf(&mut buf).await.expect("read_buf") > 0 {}
+        assert_eq!(buf.filled(), b"hello world");
+        assert_eq!(buf.initialized().len(), 11);
+        for b in &storage[11..] {
+            assert_eq!(unsafe { b.assume_init() }, 0xaa);
+        }
+
+        let path = temp_path("test_read_buf").display().to_string();
+        let _ = std::fs::remove_file(&path);
+        let mut listener = UnixListener::<RT>::bind(&path).expect("bind");
+        let client = UnixStream::<RT>::connect(&path).await.expect("connect");
+        let server = listener.accept().await.expect("accept");
+        let mut client = UnifyStream::Unix(client);
+        let mut server = UnifyStream::Unix(server);
+        client.write_all(b"unix").await.expect("write");
+        let mut storage = [MaybeUninit::<u8>::uninit(); 16];
+        let mut buf = ReadBuf::uninit(&mut storage);
+        assert_eq!(server.read_buf(&mut buf).await.expect("read_buf"), 4);
+        assert_eq!(buf.filled(), b"unix");
+        assert_eq!(buf.initialized().len(), 4);
+        let _ = std::fs::remove_file(&path);
+    });
+}
+
+/// Test bind tcp listener with a large backlog, connections should be queued without accept
+#[logfn]
+pub fn test_tcp_bind_with_backlog<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind_with_backlog("127.0.0.1:0", 4096)
+            .await
+            .expect("Failed to create TCP listener");
+        let server_addr = listener.local_addr().expect("Failed to get local address");
+        let mut clients = Vec::new();
+        for _ in 0..16 {
+            clients.push(TcpStream::<RT>::connect(&server_addr).await.expect("Failed to connect"));
+        }
+        for _ in 0..16 {
+            listener.accept().await.expect("Failed to accept connection");
+        }
+    });
+}
+
+/// Test connect_timeout of unix stream, a path without listener should fail without hanging
+#[logfn]
+pub fn test_unix_connect_timeout<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let path = temp_path("test_unix_connect_timeout").display().to_string();
+    let _ = std::fs::remove_file(&path);
+    let timeout = Duration::from_secs(1);
+    rt.block_on(async {
+        let start = std::time::Instant::now();
+        assert!(UnixStream::<RT>::connect_timeout(&path, timeout).await.is_err());
+        // A stale socket file left by a closed listener
+        drop(UnixListener::<RT>::bind(&path).expect("bind"));
+        let e = UnixStream::<RT>::connect_timeout(&path, timeout).await.expect_err("no listener");
+        assert_ne!(e.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < timeout);
+        let _ = std::fs::remove_file(&path);
+
+        let _listener = UnixListener::<RT>::bind(&path).expect("bind");
+        UnixStream::<RT>::connect_timeout(&path, timeout).await.expect("connect");
+    });
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Test bind unix socket with the permission mode, and the mode is ignored for tcp
+#[logfn]
+pub fn test_unify_bind_with_mode<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = temp_path("test_bind_with_mode").display().to_string();
+    rt.block_on(async {
+        for mode in [0o600, 0o666] {
+            let mut listener =
+                UnifyListener::<RT>::bind_with_mode(path.as_str(), mode).await.expect("bind");
+            let meta = std::fs::metadata(&path).expect("metadata");
+            assert_eq!(meta.permissions().mode() & 0o777, mode);
+            let _client = UnifyStream::<RT>::connect(path.as_str()).await.expect("connect");
+            listener.accept().await.expect("accept");
+        }
+        let listener =
+            UnifyListener::<RT>::bind_with_mode("127.0.0.1:0", 0o600).await.expect("bind");
+        assert!(matches!(listener, UnifyListener::Tcp(_)));
+    });
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Test bind unix listener with a large backlog, connections should be queued without accept
+#[logfn]
+pub fn test_unix_bind_with_backlog<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let path = "/tmp/test_unix_bind_with_backlog";
+    let _ = std::fs::remove_file(path);
+    rt.block_on(async {
+        let mut listener =
+            UnixListener::<RT>::bind_with_backlog(path, 4096).expect("Failed to create listener");
+        let mut clients = Vec::new();
+        for _ in 0..16 {
+            clients.push(UnixStream::<RT>::connect(path).await.expect("Failed to connect"));
+        }
+        for _ in 0..16 {
+            listener.accept().await.expect("Failed to accept connection");
+        }
+    });
+    let _ = std::fs::remove_file(path);
+}
+
+/// Test accepting connections from the incoming stream
+#[logfn]
+pub fn test_listener_incoming<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    async fn accept_two<RT: AsyncRuntime, L: AsyncListener>(listener: L, addr: &str)
+    where
+        L::Conn: AsyncRead + AsyncWrite,
+    {
+        let server_addr = listener.local_addr().expect("Failed to get local address");
+        let mut incoming = std::pin::pin!(listener.incoming());
+        let mut clients = Vec::new();
+        for i in 0..2u8 {
+            let mut client =
+                UnifyStream::<RT>::connect(&server_addr).await.expect("Failed to connect");
+            client.write_all(&[i]).await.expect("write");
+            clients.push(client);
+        }
+        for i in 0..2u8 {
+            let mut conn = incoming.next().await.expect("stream ended").expect("accept");
+            let mut buf = [0u8; 1];
+            conn.read_exact(&mut buf).await.expect("read");
+            assert_eq!(buf[0], i, "{}", addr);
+        }
+    }
+
+    let path = "/tmp/test_listener_incoming";
+    let _ = std::fs::remove_file(path);
+    rt.block_on(async {
+        let listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind tcp");
+        accept_two::<RT, _>(listener, "tcp").await;
+        let listener = UnixListener::<RT>::bind(path).expect("bind unix");
+        accept_two::<RT, _>(listener, "unix").await;
+        let _ = std::fs::remove_file(path);
+        let listener = UnifyListener::<RT>::bind("127.0.0.1:0").await.expect("bind unify");
+        accept_two::<RT, _>(listener, "unify").await;
+    });
+    let _ = std::fs::remove_file(path);
+}
+
+/// Test accept_with_addr returns the address of the client
+#[logfn]
+pub fn test_tcp_accept_with_addr<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener =
+            TcpListener::<RT>::bind("127.0.0.1:0").await.expect("Failed to create TCP listener");
+        let server_addr = listener.local_addr().expect("Failed to get local address");
+        let client = TcpStream::<RT>::connect(&server_addr).await.expect("Failed to connect");
+        let (stream, addr) = listener.accept_with_addr().await.expect("Failed to accept");
+        assert_eq!(addr, client.local_addr().expect("local_addr"));
+        assert_eq!(stream.peer_addr().expect("peer_addr"), addr);
+    });
+}
+
+/// Test accept_with_addr of unix listener, the client address is unnamed without bind
+#[logfn]
+pub fn test_unix_accept_with_addr<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let path = "/tmp/test_unix_accept_with_addr";
+    let _ = std::fs::remove_file(path);
+    rt.block_on(async {
+        let mut listener = UnixListener::<RT>::bind(path).expect("Failed to create listener");
+        let _client = UnixStream::<RT>::connect(path).await.expect("Failed to connect");
+        let (_stream, addr) = listener.accept_with_addr().await.expect("Failed to accept");
+        assert!(addr.is_unnamed());
+    });
+    let _ = std::fs::remove_file(path);
+}
+
+#[derive(Default)]
+struct MemPipe {
+    buf: VecDeque<u8>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl MemPipe {
+    fn close(&mut self) {
+        self.closed = true;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// An in-memory stream implements [Transport], for testing helpers which generic over transport.
+pub struct MemStream {
+    rx: Arc<Mutex<MemPipe>>,
+    tx: Arc<Mutex<MemPipe>>,
+}
+
+impl MemStream {
+    fn pair() -> (Self, Self) {
+        let a = Arc::new(Mutex::new(MemPipe::default()));
+        let b = Arc::new(Mutex::new(MemPipe::default()));
+        (Self { rx: a.clone(), tx: b.clone() }, Self { rx: b, tx: a })
+    }
+}
+
+impl Drop for MemStream {
+    fn drop(&mut self) {
+        self.tx.lock().unwrap().close();
+        self.rx.lock().unwrap().close();
+    }
+}
+
+impl AsyncRead for MemStream {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        poll_fn(|cx| {
+            let mut pipe = self.rx.lock().unwrap();
+            if !pipe.buf.is_empty() {
+                let n = std::cmp::min(buf.len(), pipe.buf.len());
+                for (dst, src) in buf.iter_mut().zip(pipe.buf.drain(..n)) {
+                    *dst = src;
+                }
+                Poll::Ready(Ok(n))
+            } else if pipe.closed {
+                Poll::Ready(Ok(0))
+            } else {
+                pipe.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl AsyncWrite for MemStream {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut pipe = self.tx.lock().unwrap();
+        if pipe.closed {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        pipe.buf.extend(buf);
+        if let Some(waker) = pipe.waker.take() {
+            waker.wake();
+        }
+        Ok(buf.len())
+    }
+}
+
+impl StreamStats for MemStream {
+    /// Only the buffer fill is available
+    fn stats(&self) -> io::Result<StreamStatsSnapshot> {
+        Ok(StreamStatsSnapshot {
+            recv_queued: Some(self.rx.lock().unwrap().buf.len()),
+            send_queued: Some(self.tx.lock().unwrap().buf.len()),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Default)]
+struct MemBacklog {
+    conns: VecDeque<MemStream>,
+    waker: Option<Waker>,
+}
+
+static MEM_LISTENERS: LazyLock<Mutex<HashMap<String, Arc<Mutex<MemBacklog>>>>> =
+    LazyLock::new(Default::default);
+
+/// The listener of [MemStream], the address is an arbitrary name.
+pub struct MemListener {
+    name: String,
+    backlog: Arc<Mutex<MemBacklog>>,
+}
+
+impl fmt::Debug for MemListener {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MemListener({})", self.name)
+    }
+}
+
+impl Drop for MemListener {
+    fn drop(&mut self) {
+        MEM_LISTENERS.lock().unwrap().remove(&self.name);
+    }
+}
+
+impl AsyncListener for MemListener {
+    type Conn = MemStream;
+
+    async fn bind(addr: &str) -> io::Result<Self> {
+        let mut listeners = MEM_LISTENERS.lock().unwrap();
+        if listeners.contains_key(addr) {
+            return Err(io::ErrorKind::AddrInUse.into());
+        }
+        let backlog = Arc::new(Mutex::new(MemBacklog::default()));
+        listeners.insert(addr.to_string(), backlog.clone());
+        Ok(Self { name: addr.to_string(), backlog })
+    }
+
+    async fn accept(&mut self) -> io::Result<MemStream> {
+        poll_fn(|cx| {
+            let mut backlog = self.backlog.lock().unwrap();
+            if let Some(conn) = backlog.conns.pop_front() {
+                Poll::Ready(Ok(conn))
+            } else {
+                backlog.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    fn local_addr(&self) -> io::Result<String> {
+        Ok(self.name.clone())
+    }
+
+    unsafe fn try_from_raw_fd(_addr: &str, _raw_fd: RawFd) -> io::Result<Self> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+impl Transport for MemStream {
+    type Listener = MemListener;
+
+    async fn connect(addr: &str) -> io::Result<Self> {
+        let backlog = match MEM_LISTENERS.lock().unwrap().get(addr) {
+            Some(backlog) => backlog.clone(),
+            None => return Err(io::ErrorKind::ConnectionRefused.into()),
+        };
+        let (client, server) = MemStream::pair();
+        let mut backlog = backlog.lock().unwrap();
+        backlog.conns.push_back(server);
+        if let Some(waker) = backlog.waker.take() {
+            waker.wake();
+        }
+        Ok(client)
+    }
+}
+
+/// Count the bytes read and written
+struct Metered<S> {
+    inner: S,
+    read: Arc<AtomicUsize>,
+    written: Arc<AtomicUsize>,
+}
+
+impl<S: AsyncRead> AsyncRead for Metered<S> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf).await?;
+        self.read.fetch_add(n, Ordering::SeqCst);
+        Ok(n)
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for Metered<S> {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf).await?;
+        self.written.fetch_add(n, Ordering::SeqCst);
+        Ok(n)
+    }
+}
+
+#[derive(Default)]
+struct MeteredLayer {
+    read: Arc<AtomicUsize>,
+    written: Arc<AtomicUsize>,
+}
+
+impl StreamLayer for MeteredLayer {
+    fn wrap<S: AsyncRead + AsyncWrite + 'static>(
+        &self, inner: S,
+    ) -> impl AsyncRead + AsyncWrite + 'static {
+        Metered { inner, read: self.read.clone(), written: self.written.clone() }
+    }
+}
+
+/// Fail the read which takes longer than `timeout`
+struct ReadTimeout<S, RT> {
+    inner: S,
+    timeout: Duration,
+    _phan: PhantomData<fn() -> RT>,
+}
+
+impl<S: AsyncRead, RT: AsyncTime> AsyncRead for ReadTimeout<S, RT> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match RT::timeout(self.timeout, self.inner.read(buf)).await {
+            Ok(r) => r,
+            Err(_) => Err(io::ErrorKind::TimedOut.into()),
+        }
+    }
+}
+
+impl<S: AsyncWrite, RT: AsyncTime> AsyncWrite for ReadTimeout<S, RT> {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf).await
+    }
+}
+
+struct ReadTimeoutLayer<RT>(Duration, PhantomData<fn() -> RT>);
+
+impl<RT: AsyncTime + 'static> StreamLayer for ReadTimeoutLayer<RT> {
+    fn wrap<S: AsyncRead + AsyncWrite + 'static>(
+        &self, inner: S,
+    ) -> impl AsyncRead + AsyncWrite + 'static {
+        ReadTimeout::<S, RT> { inner, timeout: self.0, _phan: PhantomData }
+    }
+}
+
+/// Test both the layers of a StreamStack apply to the composed stream
+#[logfn]
+pub fn test_stream_stack<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let timeout = Duration::from_millis(100);
+    rt.block_on(async {
+        let metered = MeteredLayer::default();
+        let (read, written) = (metered.read.clone(), metered.written.clone());
+        let stack =
+            StreamStack::new().layer(metered).layer(ReadTimeoutLayer::<RT>(timeout, PhantomData));
+        let (local, mut remote) = MemStream::pair();
+        let mut stream = stack.wrap(local);
+
+        stream.write_all(b"hello").await.expect("write");
+        let mut buf = [0u8; 5];
+        remote.read_exact(&mut buf).await.expect("read");
+        remote.write_all(b"world!").await.expect("write");
+        let mut buf = [0u8; 6];
+        stream.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"world!");
+        assert_eq!(written.load(Ordering::SeqCst), 5);
+        assert_eq!(read.load(Ordering::SeqCst), 6);
+
+        // The peer is silent
+        let start = std::time::Instant::now();
+        let e = stream.read(&mut buf).await.expect_err("timeout");
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() >= timeout);
+        assert_eq!(read.load(Ordering::SeqCst), 6);
+    });
+}
+
+/// Test serve() with an echo handler over any Transport
+#[logfn]
+pub fn test_transport_serve<RT, T>(rt: &RT, addr: &str)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+    T: Transport,
+{
+    rt.block_on(async {
+        let listener = T::bind(addr).await.expect("Failed to bind");
+        let server_addr = listener.local_addr().expect("Failed to get local address");
+        let server = async {
+            serve(rt, listener, |mut conn: T| async move {
+                let mut buf = [0u8; 64];
+                loop {
+                    match conn.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => {
+                            if conn.write_all(&buf[..n]).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+            })
+            .await
+            .expect("serve failed");
+            unreachable!();
+        };
+        let client = async {
+            for i in 0..3 {
+                let mut conn = T::connect(&server_addr).await.expect("Failed to connect");
+                let msg = format!("hello {}", i);
+                conn.write_all(msg.as_bytes()).await.expect("Failed to write");
+                let mut buf = vec![0u8; msg.len()];
+                conn.read_exact(&mut buf).await.expect("Failed to read");
+                assert_eq!(buf, msg.as_bytes());
+            }
+        };
+        futures_lite::future::or(server, client).await;
+    });
+}
+
+/// Test concurrent rpc calls get their own responses, replied out of order
+#[logfn]
+pub fn test_rpc_client<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::net::rpc::{read_frame, write_frame, Client};
+
+    let calls = 5;
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let server = rt.spawn(async move {
+            let mut stream = listener.accept().await.expect("accept");
+            let mut requests = Vec::new();
+            for _ in 0..calls {
+                requests.push(read_frame(&mut stream).await.expect("read_frame"));
+            }
+            // Echo in the reverse order
+            for (id, payload) in requests.iter().rev() {
+                write_frame(&mut stream, *id, payload).await.expect("write_frame");
+            }
+            // A response of unknown id is ignored
+            write_frame(&mut stream, u64::MAX, b"unknown").await.expect("write_frame");
+            // The next request and close
+            let (id, payload) = read_frame(&mut stream).await.expect("read_frame");
+            write_frame(&mut stream, id, &payload).await.expect("write_frame");
+        });
+        let stream = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+        let client = Arc::new(Client::new(rt, stream));
+        let handles: Vec<_> = (0..calls)
+            .map(|i| {
+                let client = client.clone();
+                rt.spawn(async move {
+                    let payload = format!("request {}", i).into_bytes();
+                    let resp = client.call(payload.clone()).await.expect("call");
+                    assert_eq!(resp, payload);
+                })
+            })
+            .collect();
+        for h in handles {
+            h.await.expect("call task");
+        }
+        assert_eq!(client.call(b"last".to_vec()).await.expect("call"), b"last");
+        server.await.expect("server");
+        // The connection is closed by the server
+        let e = client.call(b"closed".to_vec()).await.expect_err("closed");
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        let e = client.call(b"closed".to_vec()).await.expect_err("closed");
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+    });
+}
+
+/// Test serve_graceful() drains the quick handler and aborts the hung one after grace
+#[logfn]
+pub fn test_serve_graceful<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let grace = Duration::from_millis(500);
+    rt.block_on(async {
+        let listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let (trigger, token) = ShutdownToken::new();
+        let started = Arc::new(AtomicUsize::new(0));
+        let quick_done = Arc::new(AtomicBool::new(false));
+        let (_started, _quick_done) = (started.clone(), quick_done.clone());
+        let server = serve_graceful(
+            rt,
+            listener,
+            move |mut conn: TcpStream<RT>| {
+                let started = _started.clone();
+                let quick_done = _quick_done.clone();
+                async move {
+                    let mut buf = [0u8; 1];
+                    conn.read_exact(&mut buf).await.expect("read");
+                    started.fetch_add(1, Ordering::SeqCst);
+                    match buf[0] {
+                        // Finished long before shutdown, not counted as aborted
+                        b's' => {}
+                        b'q' => {
+                            // Still busy on shutdown, but finish within grace
+                            RT::sleep(Duration::from_millis(100)).await;
+                            quick_done.store(true, Ordering::SeqCst);
+                        }
+                        _ => {
+                            RT::sleep(Duration::from_secs(3600)).await;
+                        }
+                    }
+                }
+            },
+            token,
+            grace,
+        );
+        let client = async {
+            for _ in 0..10 {
+                let mut short = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+                short.write_all(b"s").await.expect("write");
+            }
+            let mut quick = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+            quick.write_all(b"q").await.expect("write");
+            let mut hung = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+            hung.write_all(b"h").await.expect("write");
+            while started.load(Ordering::SeqCst) < 12 {
+                RT::sleep(Duration::from_millis(10)).await;
+            }
+            trigger.shutdown();
+            let start = std::time::Instant::now();
+            (quick, hung, start)
+        };
+        let (aborted, (_quick, _hung, start)) = futures_lite::future::zip(server, client).await;
+        assert_eq!(aborted.expect("serve_graceful"), 1);
+        assert!(start.elapsed() >= grace, "elapsed {:?}", start.elapsed());
+        assert!(quick_done.load(Ordering::SeqCst));
+        // Stopped accepting
+        assert!(TcpStream::<RT>::connect(&server_addr).await.is_err());
+    });
+}
+
+/// Test multiple listeners bind to the same port with SO_REUSEPORT
+#[logfn]
+pub fn test_tcp_bind_reuse_port<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener1 = TcpListener::<RT>::bind_reuse("127.0.0.1:0", true, true)
+            .await
+            .expect("Failed to create TCP listener");
+        let server_addr = listener1.local_addr().expect("Failed to get local address");
+        // without SO_REUSEPORT, will conflict
+        let e = TcpListener::<RT>::bind_reuse(&server_addr, true, false).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::AddrInUse);
+        let mut listener2 = TcpListener::<RT>::bind_reuse(&server_addr, true, true)
+            .await
+            .expect("Failed to create TCP listener with reuse_port");
+        assert_eq!(listener2.local_addr().unwrap(), server_addr);
+
+        // the kernel distributes the connections, make sure both listeners are working
+        let mut clients = Vec::new();
+        for _ in 0..32 {
+            clients.push(TcpStream::<RT>::connect(&server_addr).await.expect("Failed to connect"));
+        }
+        let mut count = 0;
+        for listener in [&mut listener1, &mut listener2] {
+            while RT::timeout(Duration::from_millis(100), listener.accept()).await.is_ok() {
+                count += 1;
+            }
+        }
+        assert_eq!(count, 32);
+    });
+}
+
+/// Test each transport populates the stats fields it supports
+#[logfn]
+pub fn test_stream_stats<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let msg = [1u8; 100];
+    let path = "/tmp/test_stream_stats";
+    let _ = std::fs::remove_file(path);
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind tcp");
+        let server_addr = listener.local_addr().expect("Failed to get local address");
+        let mut client = TcpStream::<RT>::connect(&server_addr).await.expect("Failed to connect");
+        let server = listener.accept().await.expect("Failed to accept");
+        client.write_all(&msg).await.expect("write");
+        RT::sleep(Duration::from_millis(50)).await;
+        let stats = server.stats().expect("tcp stats");
+        assert_eq!(stats.recv_queued, Some(msg.len()));
+        assert!(stats.send_queued.is_some());
+        assert!(stats.rtt.is_some());
+        assert!(stats.rtt_var.is_some());
+        assert!(stats.retransmits.is_some());
+        let stats = client.stats().expect("tcp stats");
+        assert_eq!(stats.recv_queued, Some(0));
+        assert!(stats.rtt.is_some());
+
+        let mut listener = UnixListener::<RT>::bind(path).expect("bind unix");
+        let mut client = UnixStream::<RT>::connect(path).await.expect("Failed to connect");
+        let server = listener.accept().await.expect("Failed to accept");
+        client.write_all(&msg).await.expect("write");
+        let stats = server.stats().expect("unix stats");
+        assert_eq!(stats.recv_queued, Some(msg.len()));
+        assert!(stats.send_queued.is_some());
+        assert_eq!(stats.rtt, None);
+        assert_eq!(stats.rtt_var, None);
+        assert_eq!(stats.retransmits, None);
+
+        let (mut client, server) = MemStream::pair();
+        client.write_all(&msg).await.expect("write");
+        let expected = StreamStatsSnapshot {
+            send_queued: Some(msg.len()),
+            recv_queued: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(client.stats().expect("mem stats"), expected);
+        let expected = StreamStatsSnapshot {
+            send_queued: Some(0),
+            recv_queued: Some(msg.len()),
+            ..Default::default()
+        };
+        assert_eq!(server.stats().expect("mem stats"), expected);
+    });
+    let _ = std::fs::remove_file(path);
+}
+
+/// Test bind and connect unix socket in abstract namespace, without filesystem entry
+#[cfg(target_os = "linux")]
+#[logfn]
+pub fn test_unix_abstract<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let addr = format!("@{}", temp_name("test_unix_abstract"));
+    rt.block_on(async {
+        let mut listener = UnifyListener::<RT>::bind(addr.as_str()).await.expect("bind");
+        assert_eq!(listener.local_addr().expect("local_addr"), addr);
+        // Bind again fails while the name is in use
+        assert!(UnixListener::<RT>::bind_abstract(&addr[1..]).is_err());
+        let server = rt.spawn(async move {
+            let mut stream = listener.accept().await.expect("accept");
+            let mut buf = [0u8; 5];
+            stream.read_exact(&mut buf).await.expect("read");
+            stream.write_all(&buf).await.expect("write");
+        });
+        let mut stream = UnifyStream::<RT>::connect(addr.as_str()).await.expect("connect");
+        stream.write_all(b"hello").await.expect("write");
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"hello");
+        server.await.expect("server");
+        assert!(!std::path::Path::new(&addr).exists());
+        // The name is released after the listener closed
+        let e = UnixStream::<RT>::connect_abstract(&addr[1..]).await.expect_err("closed");
+        assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+    });
+}
+
+/// Test peer_cred and peer_exe of unix stream, the peer is the test process itself
+#[cfg(target_os = "linux")]
+#[logfn]
+pub fn test_unix_peer_exe<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let path = "/tmp/test_unix_peer_exe";
+    let _ = std::fs::remove_file(path);
+    rt.block_on(async {
+        let mut listener = UnixListener::<RT>::bind(path).expect("Failed to create listener");
+        let _client = UnixStream::<RT>::connect(path).await.expect("Failed to connect");
+        let stream = listener.accept().await.expect("Failed to accept");
+        let cred = stream.peer_cred().expect("peer_cred");
+        assert_eq!(cred.pid, std::process::id() as i32);
+        let exe = stream.peer_exe().expect("peer_exe");
+        assert_eq!(exe, std::env::current_exe().expect("current_exe"));
+    });
+    let _ = std::fs::remove_file(path);
+}
+
+/// Test connect with a socket configured by the caller, the option should survive
+#[logfn]
+pub fn test_tcp_connect_with_socket<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use socket2::{Domain, SockRef, Socket, Type};
+    use std::os::fd::{AsRawFd, BorrowedFd};
+
+    rt.block_on(async {
+        let mut listener =
+            TcpListener::<RT>::bind("127.0.0.1:0").await.expect("Failed to create TCP listener");
+        let server_addr: std::net::SocketAddr =
+            listener.local_addr().expect("local_addr").parse().expect("parse");
+        let socket = Socket::new(Domain::IPV4, Type::STREAM, None).expect("socket");
+        socket.set_reuse_address(true).expect("set_reuse_address");
+        socket.set_nonblocking(true).expect("set_nonblocking");
+        let mut client =
+            TcpStream::<RT>::connect_with_socket(socket, &server_addr).await.expect("connect");
+        let mut server = listener.accept().await.expect("accept");
+        assert_eq!(client.peer_addr().expect("peer_addr"), server_addr);
+        let fd = unsafe { BorrowedFd::borrow_raw(client.as_raw_fd()) };
+        assert!(SockRef::from(&fd).reuse_address().expect("reuse_address"));
+
+        client.write_all(b"ping").await.expect("write");
+        let mut buf = [0u8; 4];
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"ping");
+
+        // Connection refused should be reported
+        drop(listener);
+        let socket = Socket::new(Domain::IPV4, Type::STREAM, None).expect("socket");
+        assert!(TcpStream::<RT>::connect_with_socket(socket, &server_addr).await.is_err());
+    });
+}
+
+/// Test connect from a fixed loopback source port
+#[logfn]
+pub fn test_tcp_connect_from<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        // Pick a free port for the source
+        let local = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("free port");
+        let mut client =
+            TcpStream::<RT>::connect_from(local, &server_addr).await.expect("connect_from");
+        let mut server = listener.accept().await.expect("accept");
+        assert_eq!(client.local_addr().expect("local_addr"), local);
+        assert_eq!(server.peer_addr().expect("peer_addr"), local);
+
+        client.write_all(b"ping").await.expect("write");
+        let mut buf = [0u8; 4];
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"ping");
+
+        // The family mismatch is rejected
+        let local_v6: std::net::SocketAddr = "[::1]:0".parse().unwrap();
+        let e = TcpStream::<RT>::connect_from(local_v6, &server_addr).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    });
+}
+
+/// Test connect_with() combining the options
+#[logfn]
+pub fn test_connect_options<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::net::{ConnectOptions, ResolveAddr, ResolveError, UnifyAddr};
+    use socket2::SockRef;
+    use std::net::SocketAddr;
+    use std::os::fd::{AsRawFd, BorrowedFd};
+    // A resolver returns no address, should not panic
+    #[derive(Debug)]
+    struct NoAddr;
+    impl ResolveAddr for NoAddr {
+        async fn resolve<E: AsyncExec>(&self) -> Result<UnifyAddr, ResolveError> {
+            Err(ResolveError::NoAddresses)
+        }
+
+        async fn resolve_all<E: AsyncExec>(&self) -> io::Result<Vec<UnifyAddr>> {
+            Ok(Vec::new())
+        }
+    }
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let port = listener.local_addr().expect("local_addr").parse::<SocketAddr>().unwrap().port();
+        let local: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let opts = ConnectOptions::new()
+            .timeout(Duration::from_secs(5))
+            .nodelay(true)
+            .keepalive(Duration::from_secs(30))
+            .local_addr(local)
+            .happy_eyeballs_delay(Duration::from_millis(100));
+        // localhost might resolve to ::1 as well, which is skipped for the v4 local address
+        let mut client = TcpStream::<RT>::connect_with(&format!("localhost:{}", port), &opts)
+            .await
+            .expect("connect_with");
+        let mut server = listener.accept().await.expect("accept");
+        assert_eq!(client.peer_addr().expect("peer_addr").port(), port);
+        let fd = unsafe { BorrowedFd::borrow_raw(client.as_raw_fd()) };
+        let socket = SockRef::from(&fd);
+        assert!(socket.tcp_nodelay().expect("nodelay"));
+        assert!(socket.keepalive().expect("keepalive"));
+        #[cfg(target_os = "linux")]
+        assert_eq!(socket.tcp_keepalive_time().expect("keepalive_time"), Duration::from_secs(30));
+        client.write_all(b"ping").await.expect("write");
+        let mut buf = [0u8; 4];
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"ping");
+
+        // No address of the local family
+        let opts = ConnectOptions::new().local_addr("[::1]:0".parse().unwrap());
+        let e =
+            TcpStream::<RT>::connect_with(&format!("127.0.0.1:{}", port), &opts).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+
+        // The tcp options are ignored for unix socket
+        let path = "/tmp/orb_test_connect_options.sock";
+        let _ = std::fs::remove_file(path);
+        let mut unix_listener = UnifyListener::<RT>::bind(path).await.expect("bind unix");
+        let opts = ConnectOptions::new().nodelay(true).timeout(Duration::from_secs(5));
+        let client = UnifyStream::<RT>::connect_with(path, &opts).await.expect("connect unix");
+        assert!(matches!(client, UnifyStream::Unix(_)));
+        unix_listener.accept().await.expect("accept unix");
+        let _ = std::fs::remove_file(path);
+
+        // The timeout when the accept queue is full
+        let stuck = TcpListener::<RT>::bind_with_backlog("127.0.0.1:0", 0).await.expect("bind");
+        let stuck_addr = stuck.local_addr().expect("local_addr");
+        let _filled = TcpStream::<RT>::connect(&stuck_addr).await.expect("connect");
+        let opts = ConnectOptions::new().timeout(Duration::from_millis(200));
+        let e = UnifyStream::<RT>::connect_with(&stuck_addr, &opts).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+
+        let opts = ConnectOptions::new().happy_eyeballs_delay(Duration::from_millis(50));
+        let e = UnifyStream::<RT>::connect_with(&NoAddr, &opts).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+        let cause = e.get_ref().and_then(|e| e.source()).expect("source");
+        assert!(matches!(cause.downcast_ref(), Some(ResolveError::NoAddresses)));
+        let e = TcpStream::<RT>::connect_with(&NoAddr, &opts).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    });
+}
+
+/// Test connect_retry() succeeds when the listener starts after the failed attempts, and returns
+/// the last error when the retries are exhausted
+#[logfn]
+pub fn test_connect_retry<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::net::{connect_retry, BackoffPolicy};
+    use std::time::Instant;
+    rt.block_on(async {
+        // A port nobody listens on
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("free port")
+            .to_string();
+        let policy = BackoffPolicy {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+            multiplier: 2.0,
+            max_retries: 5,
+            jitter: 0.0,
+        };
+        // The attempts at 0, 100ms, 300ms, the listener starts in between the 2nd and 3rd
+        let start = Instant::now();
+        let (client, server) =
+            futures_lite::future::zip(connect_retry::<RT, _>(&addr, policy.clone()), async {
+                RT::sleep(Duration::from_millis(200)).await;
+                let mut listener = TcpListener::<RT>::bind(&addr).await.expect("bind");
+                listener.accept().await.expect("accept")
+            })
+            .await;
+        let mut client = client.expect("connect_retry");
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(300), "elapsed {:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(700), "elapsed {:?}", elapsed);
+        let mut server = server;
+        client.write_all(b"ping").await.expect("write");
+        let mut buf = [0u8; 4];
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"ping");
+        drop(server);
+
+        // Give up after 2 retries: 100ms + 200ms
+        let policy = BackoffPolicy { max_retries: 2, ..policy };
+        let start = Instant::now();
+        let e = connect_retry::<RT, _>(&addr, policy.clone()).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(300), "elapsed {:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(700), "elapsed {:?}", elapsed);
+
+        // No retry for the malformed address
+        let start = Instant::now();
+        let e = connect_retry::<RT, _>("no_port", policy.clone()).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        // The jitter does not push the delay over max
+        let jittered = BackoffPolicy { jitter: 1.0, max_retries: 20, ..policy.clone() };
+        for retry in 0..20 {
+            assert!(jittered.delay(retry) <= jittered.max, "retry {}", retry);
+        }
+        assert!(jittered.delay(0) <= Duration::from_millis(200));
+
+        // The delay beyond Duration::MAX does not panic, keeps sleeping
+        let policy = BackoffPolicy {
+            initial: Duration::MAX,
+            max: Duration::MAX,
+            multiplier: 2.0,
+            max_retries: 1,
+            jitter: 0.5,
+        };
+        let r = RT::timeout(Duration::from_millis(100), connect_retry::<RT, _>(&addr, policy));
+        assert!(r.await.is_err());
+    });
+}
+
+/// Test the v6-only listener refuses the v4 clients while the dual-stack one accepts, skipped
+/// without IPv6
+#[cfg(target_os = "linux")]
+#[logfn]
+pub fn test_tcp_bind_v6_only<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use std::net::SocketAddr;
+    rt.block_on(async {
+        let e = TcpListener::<RT>::bind_v6_only("127.0.0.1:0", true).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+
+        let listener = match TcpListener::<RT>::bind_v6_only("[::]:0", true).await {
+            Ok(l) => l,
+            Err(e) => {
+                log::warn!("skip test_tcp_bind_v6_only without IPv6: {}", e);
+                return;
+            }
+        };
+        let port = listener.local_addr().expect("local_addr").parse::<SocketAddr>().unwrap().port();
+        let v4: SocketAddr = format!("127.0.0.1:{}", port).parse().unwrap();
+        let e = TcpStream::<RT>::connect(&v4).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+        drop(listener);
+
+        let mut listener = TcpListener::<RT>::bind_v6_only("[::]:0", false).await.expect("bind");
+        let port = listener.local_addr().expect("local_addr").parse::<SocketAddr>().unwrap().port();
+        let v4: SocketAddr = format!("127.0.0.1:{}", port).parse().unwrap();
+        let mut client = TcpStream::<RT>::connect(&v4).await.expect("connect dual-stack");
+        let mut server = listener.accept().await.expect("accept");
+        client.write_all(b"v4").await.expect("write");
+        let mut buf = [0u8; 2];
+        server.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"v4");
+    });
+}
+
+/// Test bind a listener inside a fresh netns, skipped without privilege
+#[cfg(target_os = "linux")]
+#[logfn]
+pub fn test_in_netns<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use futures_lite::FutureExt;
+    use orb::net::in_netns;
+    use std::os::fd::AsRawFd;
+    use std::os::unix::fs::MetadataExt;
+
+    fn netns_of_thread() -> u64 {
+        std::fs::metadata("/proc/thread-self/ns/net").expect("netns").ino()
+    }
+
+    // Create a netns on a temporary thread, and keep the fd
+    let ns_file = std::thread::spawn(|| {
+        if unsafe { libc::unshare(libc::CLONE_NEWNET) } < 0 {
+            // The errno of this thread
+            return Err(io::Error::last_os_error());
+        }
+        Ok(std::fs::File::open("/proc/thread-self/ns/net").expect("open netns"))
+    })
+    .join()
+    .unwrap();
+    let ns_file = match ns_file {
+        Ok(f) => f,
+        Err(e) => {
+            log::warn!("skip test_in_netns: {}", e);
+            return;
+        }
+    };
+    let target = ns_file.metadata().expect("metadata").ino();
+    let origin = netns_of_thread();
+    assert_ne!(target, origin);
+    rt.block_on(async {
+        let (listener, ns) = in_netns(ns_file.as_raw_fd(), || {
+            let listener = std::net::TcpListener::bind("0.0.0.0:0");
+            (listener, netns_of_thread())
+        })
+        .await
+        .expect("in_netns");
+        assert_eq!(ns, target);
+        let listener = TcpListener::<RT>::from_std(listener.expect("bind")).expect("from_std");
+        listener.local_addr().expect("local_addr");
+        // The namespace of current thread is not changed
+        assert_eq!(netns_of_thread(), origin);
+
+        let r = in_netns(-1, || ()).await;
+        assert!(r.is_err());
+
+        // The panic is resumed on the caller, with the payload
+        let ns_fd = ns_file.as_raw_fd();
+        let r = std::panic::AssertUnwindSafe(in_netns(ns_fd, || panic!("in netns")))
+            .catch_unwind()
+            .await;
+        let payload = r.expect_err("panic");
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"in netns"));
+    });
+}
+
+/// Test accept_for() stops accepting after the duration, with the count of connections
+#[logfn]
+pub fn test_accept_for<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let total = Duration::from_millis(200);
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let client = async {
+            let mut clients = Vec::new();
+            for _ in 0..3 {
+                clients.push(TcpStream::<RT>::connect(&server_addr).await.expect("connect"));
+                RT::sleep(Duration::from_millis(20)).await;
+            }
+            clients
+        };
+        let start = std::time::Instant::now();
+        let mut conns = Vec::new();
+        let server = accept_for::<_, RT>(&mut listener, total, |conn| conns.push(conn));
+        let (count, _clients) = futures_lite::future::zip(server, client).await;
+        let elapsed = start.elapsed();
+        assert_eq!(count.expect("accept_for"), 3);
+        assert_eq!(conns.len(), 3);
+        assert!(elapsed >= total, "elapsed {:?}", elapsed);
+        assert!(elapsed < total + Duration::from_millis(150), "elapsed {:?}", elapsed);
+        // The listener is still usable
+        let _client = TcpStream::<RT>::connect(&server_addr).await.expect("connect");
+        listener.accept().await.expect("accept");
+    });
+}
+
+/// Test the pool reuses the idle connections, waits on max_per_host, and closes the idle ones
+/// after idle_timeout
+#[logfn]
+pub fn test_pool<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::net::pool::{Pool, PoolConfig};
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let _accepted = accepted.clone();
+        // Echo, serving one connection at a time
+        rt.spawn_detach(async move {
+            while let Ok(mut conn) = listener.accept().await {
+                _accepted.fetch_add(1, Ordering::SeqCst);
+                let mut buf = [0u8; 64];
+                while let Ok(n) = conn.read(&mut buf).await {
+                    if n == 0 || conn.write_all(&buf[..n]).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        let config = PoolConfig {
+            max_per_host: 1,
+            idle_timeout: Duration::from_millis(200),
+            ..Default::default()
+        };
+        let pool = Pool::<RT>::new(rt, config);
+        let mut buf = [0u8; 4];
+
+        // Reuse
+        let mut conn = pool.get(&server_addr).await.expect("get");
+        assert!(!conn.is_reused());
+        conn.write_all(b"ping").await.expect("write");
+        conn.read_exact(&mut buf).await.expect("read");
+        drop(conn);
+        assert_eq!(pool.idle_count(), 1);
+        let mut conn = pool.get(&server_addr).await.expect("get");
+        assert!(conn.is_reused());
+        assert_eq!(pool.idle_count(), 0);
+        conn.write_all(b"pong").await.expect("write");
+        conn.read_exact(&mut buf).await.expect("read");
+        assert_eq!(&buf, b"pong");
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+        // Blocked by max_per_host
+        let r = RT::timeout(Duration::from_millis(50), pool.get(&server_addr)).await;
+        assert!(r.is_err());
+        let (_pool, _addr) = (pool.clone(), server_addr.clone());
+        let waiter = rt.spawn(async move { _pool.get(&_addr).await.map(|c| c.is_reused()) });
+        RT::sleep(Duration::from_millis(20)).await;
+        drop(conn);
+        let reused = RT::timeout(Duration::from_secs(1), waiter).await.expect("timeout");
+        assert!(reused.expect("join").expect("get"));
+
+        // Discarded
+        let conn = pool.get(&server_addr).await.expect("get");
+        conn.discard();
+        assert_eq!(pool.idle_count(), 0);
+        let mut conn = pool.get(&server_addr).await.expect("get");
+        assert!(!conn.is_reused());
+        conn.write_all(b"ping").await.expect("write");
+        conn.read_exact(&mut buf).await.expect("read");
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+
+        // Idle eviction
+        drop(conn);
+        assert_eq!(pool.idle_count(), 1);
+        RT::sleep(Duration::from_millis(400)).await;
+        assert_eq!(pool.idle_count(), 0);
+        let conn = pool.get(&server_addr).await.expect("get");
+        assert!(!conn.is_reused());
+    });
+}
+
+/// Test connect_any() falls back to the next address after the stagger delay when the first
+/// hangs, and connect_happy_eyeballs() with the resolved addresses
+#[logfn]
+pub fn test_connect_happy_eyeballs<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use std::net::SocketAddr;
+    use std::time::Instant;
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let good: SocketAddr = listener.local_addr().expect("local_addr").parse().unwrap();
+        // Connecting hangs when the accept queue is full, prefer v6 if available
+        let stuck = match TcpListener::<RT>::bind_with_backlog("[::1]:0", 0).await {
+            Ok(l) => l,
+            Err(_) => TcpListener::<RT>::bind_with_backlog("127.0.0.1:0", 0).await.expect("bind"),
+        };
+        let stuck_addr: SocketAddr = stuck.local_addr().expect("local_addr").parse().unwrap();
+        let _filled = TcpStream::<RT>::connect(&stuck_addr).await.expect("connect");
+
+        let delay = Duration::from_millis(100);
+        let start = Instant::now();
+        let stream =
+            TcpStream::<RT>::connect_any(&[stuck_addr, good], delay).await.expect("connect_any");
+        let elapsed = start.elapsed();
+        assert_eq!(stream.peer_addr().expect("peer_addr"), good);
+        assert!(elapsed >= delay, "elapsed {:?}", elapsed);
+        assert!(elapsed < delay + Duration::from_millis(500), "elapsed {:?}", elapsed);
+        listener.accept().await.expect("accept");
+
+        // No waiting for the stagger when the first one connects
+        let start = Instant::now();
+        let stream = TcpStream::<RT>::connect_any(&[good, stuck_addr], Duration::from_secs(5))
+            .await
+            .expect("connect_any");
+        assert_eq!(stream.peer_addr().expect("peer_addr"), good);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        listener.accept().await.expect("accept");
+
+        // All failed
+        let refused: SocketAddr = {
+            let l = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+            l.local_addr().expect("local_addr").parse().unwrap()
+        };
+        assert!(TcpStream::<RT>::connect_any(&[refused], delay).await.is_err());
+        assert!(TcpStream::<RT>::connect_any(&[], delay).await.is_err());
+
+        // localhost might resolve to ::1 which is refused, then falls back to 127.0.0.1
+        let addr = format!("localhost:{}", good.port());
+        let stream =
+            TcpStream::<RT>::connect_happy_eyeballs(&addr, delay).await.expect("happy_eyeballs");
+        assert_eq!(stream.peer_addr().expect("peer_addr"), good);
+        drop(stuck);
+    });
+}
+
+/// Test accept_timeout of the listeners, no client connects so it times out
+#[logfn]
+pub fn test_accept_timeout<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let path = temp_path("test_accept_timeout").display().to_string();
+    let timeout = Duration::from_millis(100);
+    rt.block_on(async {
+        let mut listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let start = std::time::Instant::now();
+        let e = listener.accept_timeout(timeout).await.expect_err("no client");
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() >= timeout);
+        // Still able to accept after the timeout
+        let addr = listener.local_addr().expect("addr");
+        let _client = TcpStream::<RT>::connect(&addr).await.expect("connect");
+        listener.accept_timeout(Duration::from_secs(5)).await.expect("accept");
+
+        let mut listener = UnixListener::<RT>::bind(&path).expect("bind");
+        let e = listener.accept_timeout(timeout).await.expect_err("no client");
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+        drop(listener);
+        let _ = std::fs::remove_file(&path);
+
+        let mut listener = UnifyListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let e = listener.accept_timeout(timeout).await.expect_err("no client");
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+    });
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Test the typed local address of the listeners
+#[logfn]
+pub fn test_listener_typed_local_addr<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::net::UnifyAddr;
+    let path = temp_path("test_typed_local_addr").display().to_string();
+    rt.block_on(async {
+        let listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_socket_addr().expect("addr");
+        let port: u16 = addr.port();
+        assert_ne!(port, 0);
+        assert_eq!(addr.to_string(), listener.local_addr().expect("addr"));
+
+        let listener = UnixListener::<RT>::bind(&path).expect("bind");
+        assert_eq!(listener.local_path().expect("path"), std::path::PathBuf::from(&path));
+        assert_eq!(listener.local_addr().expect("addr"), path);
+        drop(listener);
+        let _ = std::fs::remove_file(&path);
+
+        // No path in the abstract namespace
+        #[cfg(target_os = "linux")]
+        {
+            use orb::net::AddrError;
+            let name = temp_name("test_typed_local_addr");
+            let listener = UnixListener::<RT>::bind_abstract(&name).expect("bind");
+            let e = listener.local_path().expect_err("abstract");
+            assert_eq!(e.kind(), io::ErrorKind::AddrNotAvailable);
+            let source = e.get_ref().and_then(|e| e.downcast_ref::<AddrError>());
+            assert!(matches!(source, Some(AddrError::NoPathname(_))), "{:?}", e);
+        }
+
+        let listener = UnifyListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        match listener.local_unify_addr().expect("addr") {
+            UnifyAddr::Socket(addr) => assert_ne!(addr.port(), 0),
+            addr => panic!("unexpected {:?}", addr),
+        }
+        let listener = UnifyListener::<RT>::bind(&path).await.expect("bind");
+        assert_eq!(
+            listener.local_unify_addr().expect("addr"),
+            UnifyAddr::Path(path.clone().into())
+        );
+        drop(listener);
+    });
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Test peer_unify_addr of tcp and unix stream
+#[logfn]
+pub fn test_peer_unify_addr<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::net::UnifyAddr;
+    let path = temp_path("test_peer_unify_addr").display().to_string();
+    rt.block_on(async {
+        let mut listener = UnifyListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_unify_addr().expect("addr");
+        let client = UnifyStream::<RT>::connect(&addr).await.expect("connect");
+        let server = listener.accept().await.expect("accept");
+        assert_eq!(client.peer_unify_addr().expect("peer"), addr);
+        match server.peer_unify_addr().expect("peer") {
+            UnifyAddr::Socket(peer) => assert_eq!(peer.ip().to_string(), "127.0.0.1"),
+            peer => panic!("unexpected {:?}", peer),
+        }
+
+        let mut listener = UnifyListener::<RT>::bind(&path).await.expect("bind");
+        let client = UnifyStream::<RT>::connect(&path).await.expect("connect");
+        let server = listener.accept().await.expect("accept");
+        assert_eq!(client.peer_unify_addr().expect("peer"), UnifyAddr::Path(path.clone().into()));
+        // The client did not bind to a path
+        assert_eq!(server.peer_unify_addr().expect("peer"), UnifyAddr::Path(Default::default()));
+    });
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Test setting the socket option not wrapped, through AsyncFd::with_socket()
+#[logfn]
+pub fn test_async_fd_with_socket<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::io::AsyncFd;
+    rt.block_on(async {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let fd = RT::connect_tcp(&addr).await.expect("connect");
+        fd.with_socket(|sock| sock.set_tos_v4(0x10)).expect("set IP_TOS");
+        assert_eq!(fd.with_socket(|sock| sock.tos_v4()).expect("get IP_TOS"), 0x10);
+    });
+}
+
+/// Test copy_bidirectional by proxying the client to an echo server, the half-close of the client
+/// passes through to the server. The client streams MBs while reading the echo concurrently, so
+/// that the proxy deadlocks if one direction blocks the other.
+#[logfn]
+pub fn test_copy_bidirectional<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use futures_lite::future::zip;
+    use orb::io::copy_bidirectional;
+    use std::os::fd::{AsRawFd, BorrowedFd};
+    rt.block_on(async {
+        let mut echo_listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let echo_addr = echo_listener.local_addr().expect("addr");
+        let mut proxy_listener = TcpListener::<RT>::bind("127.0.0.1:0").await.expect("bind");
+        let proxy_addr = proxy_listener.local_addr().expect("addr");
+        // Much larger than the socket buffers, the echo blocks on write unless the proxy keeps reading
+        // Shrink the buffers between the proxy and the echo, as the loopback ones grow to MBs
+        let small_buf = |fd: std::os::fd::RawFd| {
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+            let socket = socket2::SockRef::from(&fd);
+            socket.set_recv_buffer_size(65536).expect("rcvbuf");
+            socket.set_send_buffer_size(65536).expect("sndbuf");
+        };
+        let data: Vec<u8> = (0..(8 << 20) as u32).map(|i| (i % 251) as u8).collect();
+
+        let echo = async {
+            let mut stream = echo_listener.accept().await.expect("accept");
+            small_buf(stream.as_raw_fd());
+            let mut buf = [0u8; 4096];
+            let mut total = 0;
+            loop {
+                let n = stream.read(&mut buf).await.expect("read");
+                if n == 0 {
+                    break;
+                }
+                stream.write_all(&buf[..n]).await.expect("write");
+                total += n;
+            }
+            stream.shutdown().await.expect("shutdown");
+            total
+        };
+        let proxy = async {
+            let inbound = proxy_listener.accept().await.expect("accept");
+            let outbound = TcpStream::<RT>::connect(&echo_addr).await.expect("connect");
+            copy_bidirectional(&inbound, &outbound).await.expect("copy")
+        };
+        let client = async {
+            let stream = TcpStream::<RT>::connect(&proxy_addr).await.expect("connect");
+            // Read and write concurrently through the shared reference
+            let write = async {
+                let mut w = &stream;
+                for chunk in data.chunks(65536) {
+                    w.write_all(chunk).await.expect("write");
+                }
+                w.shutdown().await.expect("shutdown");
+            };
+            let read = async {
+                let mut r = &stream;
+                let mut echoed = Vec::with_capacity(data.len());
+                let mut buf = vec![0u8; 65536];
+                loop {
+                    let n = r.read(&mut buf).await.expect("read");
+                    if n == 0 {
+                        break;
+                    }
+                    echoed.extend_from_slice(&buf[..n]);
+                }
+                echoed
+            };
+            zip(write, read).await.1
+        };
+        let ((echoed_len, copied), echoed) = zip(zip(echo, proxy), client).await;
+        assert_eq!(echoed_len, data.len());
+        assert_eq!(copied, (data.len() as u64, data.len() as u64));
+        assert!(echoed == data, "echoed data mismatch");
+    });
+}
+
+/// Test IdleTimeout, a slow trickle within the idle window goes on, while a stall times out
+#[logfn]
+pub fn test_idle_timeout<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::io::AsyncBufStream;
+    use orb::net::IdleTimeout;
+    let idle = Duration::from_millis(200);
+    rt.block_on(async {
+        let (local, mut remote) = MemStream::pair();
+        let mut stream = AsyncBufStream::new(IdleTimeout::<_, RT>::new(local, idle), 64);
+        let trickle = async {
+            for b in b"hello" {
+                RT::sleep(idle / 4).await;
+                remote.write_all(&[*b]).await.expect("write");
+            }
+            remote
+        };
+        let read = async {
+            let mut buf = [0u8; 5];
+            stream.read_exact(&mut buf).await.expect("read");
+            assert_eq!(&buf, b"hello");
+        };
+        let (_remote, _) = futures_lite::future::zip(trickle, read).await;
+
+        // The peer stalls
+        let start = std::time::Instant::now();
+        let mut buf = [0u8; 1];
+        let e = stream.read(&mut buf).await.expect_err("stall");
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() >= idle);
+
+        // Zero idle disables the timeout
+        let (local, mut remote) = MemStream::pair();
+        let mut stream = IdleTimeout::<_, RT>::new(local, Duration::ZERO);
+        let write = async {
+            RT::sleep(idle / 4).await;
+            remote.write_all(b"x").await.expect("write");
+            remote
+        };
+        let (_remote, r) = futures_lite::future::zip(write, stream.read(&mut buf)).await;
+        assert_eq!(r.expect("read"), 1);
+        assert_eq!(&buf, b"x");
+    });
+}
+
+/// Test Throttle, transferring N bytes at the rate R takes about N/R seconds
+#[logfn]
+pub fn test_throttle<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::io::Throttle;
+    let rate = 100_000u64;
+    rt.block_on(async {
+        let (local, mut remote) = MemStream::pair();
+        let mut stream = Throttle::<_, RT>::new(local, rate);
+        let data = vec![1u8; 50_000];
+        let start = std::time::Instant::now();
+        stream.write_all(&data).await.expect("write");
+        let elapsed = start.elapsed();
+        // The burst of 1/20 second is sent at once
+        assert!(elapsed >= Duration::from_millis(400), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(1000), "{:?}", elapsed);
+        let mut buf = vec![0u8; data.len()];
+        remote.read_exact(&mut buf).await.expect("read");
+
+        remote.write_all(&data[..20_000]).await.expect("write");
+        let start = std::time::Instant::now();
+        let mut buf = vec![0u8; 20_000];
+        stream.read_exact(&mut buf).await.expect("read");
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(100), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(600), "{:?}", elapsed);
+    });
+}
@@ -65,6 +65,166 @@ where
     assert_eq!(result, 42);
 }
 
+/// Test the counters of Metered runtime after a batch of spawns
+#[logfn]
+pub fn test_metered<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + Clone + std::fmt::Debug,
+{
+    use orb::runtime::{Metered, Metrics, MetricsSnapshot};
+
+    let metrics = Metrics::new();
+    rt.block_on(async {
+        // NOTE: the cloned TokioRT is a handle, which can not block_on
+        let rt = Metered::new(rt.clone(), metrics.clone());
+        let handles: Vec<_> = (0..10)
+            .map(|i| {
+                rt.spawn(async move {
+                    RT::sleep(Duration::from_millis(10)).await;
+                    i
+                })
+            })
+            .collect();
+        let done = Arc::new(AtomicUsize::new(0));
+        for _ in 0..5 {
+            let done = done.clone();
+            rt.spawn_detach(async move {
+                done.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        let hung = rt.spawn_named("hung", async {
+            RT::sleep(Duration::from_secs(3600)).await;
+        });
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.spawned, 16);
+        assert!(snapshot.active > 0);
+        for (i, h) in handles.into_iter().enumerate() {
+            assert_eq!(h.await.unwrap(), i);
+        }
+        while done.load(Ordering::SeqCst) < 5 {
+            RT::sleep(Duration::from_millis(10)).await;
+        }
+        // Update of the counter after the detached task returns
+        RT::sleep(Duration::from_millis(10)).await;
+        assert_eq!(
+            metrics.snapshot(),
+            MetricsSnapshot { spawned: 16, completed: 15, panicked: 0, active: 1 }
+        );
+        drop(hung);
+    });
+}
+
+/// Test spawn_profiled() accounts the sleep as idle, and the computation as poll time
+#[logfn]
+pub fn test_spawn_profiled<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let idle = Duration::from_millis(200);
+    let busy = Duration::from_millis(100);
+    rt.block_on(async move {
+        let handle = rt.spawn_profiled(async move {
+            RT::sleep(idle).await;
+            let start = Instant::now();
+            let mut n: u64 = 0;
+            while start.elapsed() < busy {
+                n = std::hint::black_box(n.wrapping_add(1));
+            }
+            n
+        });
+        while !handle.is_finished() {
+            RT::sleep(Duration::from_millis(50)).await;
+        }
+        let profile = handle.profile();
+        assert!(handle.await.unwrap() > 0);
+        assert!(profile.polls >= 2, "{:?}", profile);
+        assert!(profile.poll_time >= busy, "{:?}", profile);
+        assert!(profile.poll_time < busy + idle / 2, "{:?}", profile);
+        assert!(profile.idle_time >= idle - Duration::from_millis(10), "{:?}", profile);
+    });
+}
+
+/// Test join_timeout() returns the result in time, or the running handle on timeout
+#[logfn]
+pub fn test_join_timeout<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+    RT::AsyncHandle<i32>: Unpin,
+    RT::AsyncHandle<()>: Unpin,
+{
+    rt.block_on(async move {
+        let handle = rt.spawn(async {
+            RT::sleep(Duration::from_millis(300)).await;
+            1
+        });
+        let start = Instant::now();
+        let handle = match handle.join_timeout::<RT>(Duration::from_millis(100)).await {
+            Ok(_) => panic!("should timeout"),
+            Err(handle) => handle,
+        };
+        assert!(start.elapsed() < Duration::from_millis(300));
+        assert!(!handle.is_finished());
+        // Keep waiting
+        let r = handle.join_timeout::<RT>(Duration::from_secs(1)).await;
+        assert_eq!(r.ok().expect("should finish"), Ok(1));
+
+        // Abort the straggler
+        let exited = Arc::new(AtomicBool::new(false));
+        let _exited = exited.clone();
+        let hung = rt.spawn(async move {
+            RT::sleep(Duration::from_secs(3600)).await;
+            _exited.store(true, Ordering::SeqCst);
+        });
+        hung.join_timeout::<RT>(Duration::from_millis(50)).await.expect_err("timeout").abort();
+        assert!(!exited.load(Ordering::SeqCst));
+    });
+}
+
+/// Test try_join() before and after the task finished
+#[logfn]
+pub fn test_try_join<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+    RT::AsyncHandle<i32>: Unpin,
+{
+    rt.block_on(async move {
+        let mut handle = rt.spawn(async {
+            RT::sleep(Duration::from_millis(100)).await;
+            2
+        });
+        handle = handle.try_join().expect_err("not finished");
+        while !handle.is_finished() {
+            RT::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(handle.try_join().ok().expect("finished"), Ok(2));
+    });
+}
+
+/// Test spawn_named() behaves the same as spawn()
+#[logfn]
+pub fn test_spawn_named<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async move {
+        let handle = rt.spawn_named("orb-named", async {
+            RT::sleep(Duration::from_millis(100)).await;
+            7
+        });
+        assert!(!handle.is_finished());
+        assert_eq!(handle.await.unwrap(), 7);
+        // Drop is detach
+        let exited = Arc::new(AtomicBool::new(false));
+        let _exited = exited.clone();
+        drop(rt.spawn_named("orb-detached", async move {
+            RT::sleep(Duration::from_millis(100)).await;
+            _exited.store(true, Ordering::SeqCst);
+        }));
+        RT::sleep(Duration::from_millis(300)).await;
+        assert!(exited.load(Ordering::SeqCst));
+    });
+}
+
 #[logfn]
 pub fn test_spawn_blocking<RT: AsyncRuntime + std::fmt::Debug>(rt: &RT) {
     let result = rt.block_on(async {
@@ -87,7 +247,7 @@ pub fn test_spawn_blocking<RT: AsyncRuntime + std::fmt::Debug>(rt: &RT) {
         }
         let (r1, r2) = zip(async_f, handle).await;
         assert_eq!(r1, 41);
-        assert_eq!(r2, Ok(42));
+        assert_eq!(r2.unwrap(), 42);
         let elapsed = start_ts.elapsed();
         assert!(
             elapsed < Duration::from_secs(4) && elapsed >= Duration::from_secs(3),
@@ -127,3 +287,139 @@ pub fn test_spawn_blocking<RT: AsyncRuntime + std::fmt::Debug>(rt: &RT) {
     });
     assert_eq!(result, 1);
 }
+
+#[logfn]
+pub fn test_spawn_blocking_panic<RT: AsyncRuntime + std::fmt::Debug>(rt: &RT) {
+    rt.block_on(async {
+        let handle = RT::spawn_blocking(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            panic!("blocking task panic");
+        });
+        let e = handle.await.unwrap_err();
+        assert!(e.is_panic());
+        assert_eq!(e.panic_message(), Some("blocking task panic"));
+
+        let handle = RT::spawn_blocking(|| -> usize { panic!("{} panic", "formatted") });
+        let e = handle.await.unwrap_err();
+        assert_eq!(e.panic_message(), Some("formatted panic"));
+    });
+}
+
+/// Test dropping the handle of spawn_abortable() stops the task, and disarm() detaches it
+#[logfn]
+pub fn test_spawn_abortable<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+    RT::AsyncHandle<i32>: Unpin,
+    RT::AsyncHandle<()>: Unpin,
+{
+    rt.block_on(async move {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let _counter = counter.clone();
+        let handle = rt.spawn_abortable::<_, ()>(async move {
+            loop {
+                _counter.fetch_add(1, Ordering::SeqCst);
+                RT::sleep(Duration::from_millis(10)).await;
+            }
+        });
+        RT::sleep(Duration::from_millis(100)).await;
+        assert!(!handle.is_finished());
+        drop(handle);
+        RT::sleep(Duration::from_millis(50)).await;
+        let count = counter.load(Ordering::SeqCst);
+        assert!(count > 0);
+        RT::sleep(Duration::from_millis(100)).await;
+        assert_eq!(counter.load(Ordering::SeqCst), count);
+
+        // Disarmed handle detaches on drop
+        let exited = Arc::new(AtomicBool::new(false));
+        let _exited = exited.clone();
+        let handle = rt.spawn_abortable(async move {
+            RT::sleep(Duration::from_millis(100)).await;
+            _exited.store(true, Ordering::SeqCst);
+        });
+        drop(handle.disarm());
+        RT::sleep(Duration::from_millis(300)).await;
+        assert!(exited.load(Ordering::SeqCst));
+        // Await as a normal handle
+        let handle = rt.spawn_abortable(async { 3 });
+        assert_eq!(handle.await, Ok(3));
+    });
+}
+
+/// Test the tasks in separate BlockingPool do not queue behind each other
+#[logfn]
+pub fn test_blocking_pool<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use orb::runtime::BlockingPool;
+    let slow = BlockingPool::new("orb-slow", 1);
+    let fast = BlockingPool::new("orb-fast", 1);
+    rt.block_on(async move {
+        let start = Instant::now();
+        let h_slow = rt.spawn_blocking_on(&slow, || {
+            std::thread::sleep(Duration::from_millis(500));
+            std::thread::current().name().map(|s| s.to_string())
+        });
+        // Queued behind the first one in the same pool
+        let h_slow2 = rt.spawn_blocking_on(&slow, || 1);
+        let h_fast =
+            rt.spawn_blocking_on(&fast, || std::thread::current().name().map(|s| s.to_string()));
+        assert_eq!(h_fast.await.unwrap().as_deref(), Some("orb-fast-1"));
+        assert!(start.elapsed() < Duration::from_millis(300));
+        assert!(!h_slow2.is_finished());
+        assert_eq!(h_slow.await.unwrap().as_deref(), Some("orb-slow-1"));
+        assert_eq!(h_slow2.await.unwrap(), 1);
+        assert!(start.elapsed() >= Duration::from_millis(500));
+
+        let h = rt.spawn_blocking_on(&fast, || panic!("pool panic"));
+        assert_eq!(h.await.unwrap_err().panic_message(), Some("pool panic"));
+        // The thread survives the panic
+        assert_eq!(rt.spawn_blocking_on(&fast, || 2).await.unwrap(), 2);
+
+        let h = RT::spawn_blocking_named("orb-named-blocking", || 3);
+        assert_eq!(h.await.unwrap(), 3);
+    });
+}
+
+/// Test the StartedSignal of spawn_started() returns after the task begins, before it completes
+#[logfn]
+pub fn test_spawn_started<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async move {
+        let start = Instant::now();
+        let (handle, signal) = rt.spawn_started(async move {
+            RT::sleep(Duration::from_millis(200)).await;
+            5
+        });
+        signal.wait().await;
+        assert!(signal.is_started());
+        assert!(!handle.is_finished());
+        assert!(start.elapsed() < Duration::from_millis(150));
+        // Returns immediately once started
+        signal.wait().await;
+        assert_eq!(handle.await.unwrap(), 5);
+    });
+}
+
+/// Test block_on_local() runs a `!Send` future
+#[logfn]
+pub fn test_block_on_local<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let shared = std::rc::Rc::new(std::cell::Cell::new(0));
+    let _shared = shared.clone();
+    let r = rt.block_on_local(async move {
+        // The Rc is held across the await
+        RT::sleep(Duration::from_millis(10)).await;
+        _shared.set(_shared.get() + 1);
+        let handle = rt.spawn(async { 1 });
+        _shared.get() + handle.await.unwrap()
+    });
+    assert_eq!(r, 2);
+    assert_eq!(shared.get(), 1);
+}
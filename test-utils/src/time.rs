@@ -16,6 +16,62 @@ where
     assert!(elapsed >= Duration::from_millis(50));
 }
 
+/// Test RT::now() moves along with RT::sleep()
+#[logfn]
+pub fn test_now<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let start = RT::now();
+        RT::sleep(Duration::from_millis(50)).await;
+        let elapsed = RT::now() - start;
+        assert!(elapsed >= Duration::from_millis(50), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(1), "{:?}", elapsed);
+    });
+}
+
+/// Test the timing of RT::timeout(), and the concurrent sleeps wake up in the order of deadline
+#[logfn]
+pub fn test_timeout<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use std::sync::{Arc, Mutex};
+    rt.block_on(async {
+        // The future finished before the deadline
+        let start = RT::now();
+        let r = RT::timeout(Duration::from_millis(300), async {
+            RT::sleep(Duration::from_millis(30)).await;
+        })
+        .await;
+        assert!(r.is_ok());
+        let elapsed = RT::now() - start;
+        assert!(elapsed >= Duration::from_millis(30), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(300), "{:?}", elapsed);
+
+        // Never fires before the deadline
+        let start = RT::now();
+        let r = RT::timeout(Duration::from_millis(50), std::future::pending::<()>()).await;
+        assert!(r.is_err());
+        let elapsed = RT::now() - start;
+        assert!(elapsed >= Duration::from_millis(50), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(250), "{:?}", elapsed);
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let sleep = |ms: u64| {
+            let order = order.clone();
+            async move {
+                RT::sleep(Duration::from_millis(ms)).await;
+                order.lock().unwrap().push(ms);
+            }
+        };
+        futures_lite::future::zip(sleep(160), futures_lite::future::zip(sleep(20), sleep(90)))
+            .await;
+        assert_eq!(*order.lock().unwrap(), vec![20, 90, 160]);
+    });
+}
+
 #[logfn]
 pub fn test_tick<RT>(rt: &RT)
 where
@@ -58,3 +114,111 @@ where
         assert!(elapsed2 >= Duration::from_millis(100));
     });
 }
+
+#[logfn]
+pub fn test_tick_take<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        let start = Instant::now();
+        let mut stream = RT::tick(Duration::from_millis(50)).take(3);
+        let mut count = 0;
+        while let Some(i) = stream.next().await {
+            count += 1;
+            assert!(i.duration_since(start) >= Duration::from_millis(50 * count));
+        }
+        assert_eq!(count, 3);
+        assert!(stream.next().await.is_none());
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(150) && elapsed < Duration::from_millis(250),
+            "{:?}",
+            elapsed
+        );
+    });
+}
+
+#[logfn]
+pub fn test_tick_for_duration<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    rt.block_on(async {
+        // The 4th tick at 200ms, with margin for the loaded machine
+        let total = Duration::from_millis(240);
+        let start = RT::now();
+        let mut stream = RT::tick(Duration::from_millis(50)).for_duration::<RT>(total);
+        let mut count = 0;
+        while stream.next().await.is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 4);
+        let elapsed = RT::now() - start;
+        assert!(elapsed >= total && elapsed < total + Duration::from_millis(200), "{:?}", elapsed);
+
+        // Ends at the deadline, not on the next tick
+        let total = Duration::from_millis(100);
+        let start = RT::now();
+        let mut stream = RT::tick(Duration::from_secs(3600)).for_duration::<RT>(total);
+        while stream.next().await.is_some() {}
+        let elapsed = RT::now() - start;
+        assert!(elapsed >= total && elapsed < total + Duration::from_millis(400), "{:?}", elapsed);
+    });
+}
+
+/// Test missed_ticks() reports the periods elapsed after the deadline of the tick
+#[logfn]
+pub fn test_tick_missed<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    use futures_lite::future::poll_fn;
+    use std::pin::Pin;
+    let period = Duration::from_millis(100);
+    rt.block_on(async {
+        let mut interval = RT::tick(period);
+        poll_fn(|cx| Pin::new(&mut interval).poll_tick(cx)).await;
+        assert_eq!(interval.missed_ticks(), 0);
+        // Busy through 3 periods after the next deadline
+        RT::sleep(period * 4 + period / 2).await;
+        poll_fn(|cx| Pin::new(&mut interval).poll_tick(cx)).await;
+        let missed = interval.missed_ticks();
+        assert!((3..=4).contains(&missed), "missed {}", missed);
+        // The missed ticks are due immediately
+        poll_fn(|cx| Pin::new(&mut interval).poll_tick(cx)).await;
+        assert_eq!(interval.missed_ticks(), missed - 1);
+    });
+}
+
+/// Test the delay is extended by reset(), and never completes after cancel()
+#[logfn]
+pub fn test_delay_reset<RT>(rt: &RT)
+where
+    RT: AsyncRuntime + std::fmt::Debug,
+{
+    let d = Duration::from_millis(100);
+    rt.block_on(async {
+        let start = Instant::now();
+        let mut delay = RT::delay(d);
+        RT::sleep(d / 2).await;
+        delay.reset(d);
+        (&mut delay).await;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= d + d / 2, "{:?}", elapsed);
+        assert!(elapsed < d * 3, "{:?}", elapsed);
+
+        // Reset after completion to wait again
+        let start = Instant::now();
+        delay.reset(d);
+        (&mut delay).await;
+        assert!(start.elapsed() >= d);
+
+        delay.reset(d / 2);
+        delay.cancel();
+        assert!(RT::timeout(d, &mut delay).await.is_err());
+        // Revived by reset
+        delay.reset(d / 2);
+        assert!(RT::timeout(d, &mut delay).await.is_ok());
+    });
+}
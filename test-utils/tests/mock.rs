@@ -0,0 +1,114 @@
+use futures_lite::future::yield_now;
+use futures_lite::StreamExt;
+use orb::prelude::*;
+use orb_test_utils::mock::MockRT;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_mock_timeout() {
+    let rt = MockRT::new();
+    let start = rt.now();
+    let real_start = Instant::now();
+    rt.block_on(async {
+        // Nothing else to run, the clock jumps to the deadline
+        let r = MockRT::timeout(Duration::from_secs(3), std::future::pending::<()>()).await;
+        assert!(r.is_err());
+        let r =
+            MockRT::timeout(Duration::from_secs(3), MockRT::sleep(Duration::from_secs(1))).await;
+        assert!(r.is_ok());
+    });
+    assert_eq!(rt.now() - start, Duration::from_secs(4));
+    assert!(real_start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_mock_advance() {
+    let rt = MockRT::new();
+    let start = rt.now();
+    let done = Arc::new(AtomicBool::new(false));
+    let _done = done.clone();
+    let _rt = rt.clone();
+    rt.block_on(async move {
+        let handle = _rt.spawn(async move {
+            let r = MockRT::timeout(Duration::from_secs(10), std::future::pending::<()>()).await;
+            _done.store(true, Ordering::SeqCst);
+            r
+        });
+        yield_now().await;
+        _rt.advance(Duration::from_secs(5));
+        yield_now().await;
+        assert!(!done.load(Ordering::SeqCst));
+        _rt.advance(Duration::from_secs(5));
+        yield_now().await;
+        assert!(done.load(Ordering::SeqCst));
+        assert!(handle.is_finished());
+        assert_eq!(handle.await, Ok(Err(())));
+    });
+    assert_eq!(rt.now() - start, Duration::from_secs(10));
+}
+
+#[test]
+fn test_mock_tick() {
+    let rt = MockRT::new();
+    let start = rt.now();
+    let ticks = rt.block_on(async {
+        MockRT::tick(Duration::from_millis(100)).take(3).collect::<Vec<_>>().await
+    });
+    let expected: Vec<Instant> = (1..=3).map(|i| start + Duration::from_millis(100 * i)).collect();
+    assert_eq!(ticks, expected);
+}
+
+#[test]
+fn test_mock_delay() {
+    let rt = MockRT::new();
+    let start = rt.now();
+    rt.block_on(async {
+        let mut delay = MockRT::delay(Duration::from_secs(2));
+        MockRT::sleep(Duration::from_secs(1)).await;
+        delay.reset(Duration::from_secs(2));
+        (&mut delay).await;
+        delay.cancel();
+        assert!(MockRT::timeout(Duration::from_secs(5), &mut delay).await.is_err());
+    });
+    assert_eq!(rt.now() - start, Duration::from_secs(8));
+}
+
+#[test]
+fn test_mock_now() {
+    let rt = MockRT::new();
+    let (before, after) = rt.block_on(async {
+        let before = <MockRT as AsyncTime>::now();
+        MockRT::sleep(Duration::from_secs(60)).await;
+        (before, <MockRT as AsyncTime>::now())
+    });
+    assert_eq!(after - before, Duration::from_secs(60));
+    assert_eq!(after, rt.now());
+}
+
+#[test]
+fn test_mock_spawn() {
+    let rt = MockRT::new();
+    let _rt = rt.clone();
+    rt.block_on(async move {
+        let handles: Vec<_> = (0..3u64)
+            .map(|i| {
+                _rt.spawn(async move {
+                    MockRT::sleep(Duration::from_secs(3 - i)).await;
+                    i
+                })
+            })
+            .collect();
+        for (i, h) in handles.into_iter().enumerate() {
+            assert_eq!(h.await, Ok(i as u64));
+        }
+        let h = _rt.spawn(async { panic!("mock panic") });
+        assert!(h.await.is_err());
+        let h = _rt.spawn(async {
+            MockRT::sleep(Duration::from_secs(1)).await;
+        });
+        h.abort();
+        assert_eq!(MockRT::spawn_blocking(|| 1).await.unwrap(), 1);
+    });
+}
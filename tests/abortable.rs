@@ -0,0 +1,40 @@
+use orb::utils::{Aborted, abortable};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_abortable_complete() {
+    let (fut, handle) = abortable(async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        1u32
+    });
+    assert_eq!(fut.await, Ok(1));
+    // Abort after completion is harmless
+    handle.abort();
+    assert!(handle.is_aborted());
+}
+
+#[tokio::test]
+async fn test_abortable_abort_before_poll() {
+    // Aborted even if the inner future is ready
+    let (fut, handle) = abortable(async { 1u32 });
+    handle.abort();
+    assert!(fut.is_aborted());
+    assert_eq!(fut.await, Err(Aborted));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_abortable_abort_pending() {
+    let (fut, handle) = abortable(async {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        1u32
+    });
+    let task = tokio::spawn(fut);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!handle.is_aborted());
+    // Abort from another task wakes it up
+    let h = handle.clone();
+    tokio::spawn(async move { h.abort() }).await.unwrap();
+    let r = tokio::time::timeout(Duration::from_secs(1), task).await.expect("timeout").unwrap();
+    assert_eq!(r, Err(Aborted));
+    assert_eq!(Aborted.to_string(), "future aborted");
+}
@@ -1,4 +1,4 @@
-use orb::io::AsyncBufStream;
+use orb::io::{AsyncBufStream, LineChunk};
 use orb::prelude::*;
 use rand::{Rng, RngCore};
 use std::future::Future;
@@ -226,6 +226,60 @@ async fn test_async_write_bypass_deterministic() {
     }
 }
 
+#[tokio::test]
+async fn test_async_write_flush_threshold() {
+    let data_handle = Arc::new(Mutex::new(Vec::new()));
+    let mock_stream = MockWriteStream::new(data_handle.clone(), true);
+    let mut writer = AsyncBufStream::new(mock_stream, 16);
+    writer.set_flush_threshold(6);
+
+    writer.write_all(b"abc").await.unwrap();
+    assert!(data_handle.lock().unwrap().is_empty()); // below the threshold
+    writer.flush_if_needed().await.unwrap();
+    assert!(data_handle.lock().unwrap().is_empty());
+    // Reaching the threshold flushes, though there's space remains
+    writer.write_all(b"def").await.unwrap();
+    assert_eq!(*data_handle.lock().unwrap(), b"abcdef");
+    writer.write_all(b"ghijk").await.unwrap();
+    assert_eq!(*data_handle.lock().unwrap(), b"abcdef");
+    writer.write_all(b"l").await.unwrap();
+    assert_eq!(*data_handle.lock().unwrap(), b"abcdefghijkl");
+
+    // Disabled, flush only when full
+    writer.set_flush_threshold(0);
+    writer.write_all(b"0123456789").await.unwrap();
+    assert_eq!(data_handle.lock().unwrap().len(), 12);
+    writer.flush().await.unwrap();
+    assert_eq!(*data_handle.lock().unwrap(), b"abcdefghijkl0123456789");
+}
+
+#[tokio::test]
+async fn test_async_write_flush_by_trait() {
+    // Generic code only knows AsyncWrite
+    async fn send_frame<W: AsyncWrite>(w: &mut W, frame: &[u8]) -> io::Result<()> {
+        w.write_all(frame).await?;
+        w.flush().await
+    }
+
+    let data_handle = Arc::new(Mutex::new(Vec::new()));
+    let mock_stream = MockWriteStream::new(data_handle.clone(), true);
+    let mut writer = AsyncBufStream::new(mock_stream, 16);
+    writer.write_all(b"abc").await.unwrap();
+    assert!(data_handle.lock().unwrap().is_empty());
+    send_frame(&mut writer, b"def").await.unwrap();
+    assert_eq!(*data_handle.lock().unwrap(), b"abcdef");
+
+    // Flush through the nested buffers
+    let mut writer = AsyncBufStream::new(writer, 16);
+    send_frame(&mut writer, b"ghi").await.unwrap();
+    assert_eq!(*data_handle.lock().unwrap(), b"abcdefghi");
+
+    // No-op for the unbuffered stream
+    let mut mock_stream = MockWriteStream::new(data_handle.clone(), true);
+    send_frame(&mut mock_stream, b"jkl").await.unwrap();
+    assert_eq!(*data_handle.lock().unwrap(), b"abcdefghijkl");
+}
+
 // ==================== RANDOMIZED TESTS ====================
 
 #[tokio::test]
@@ -359,3 +413,219 @@ async fn test_async_write_bypass_random() {
         assert_eq!(*data_handle.lock().unwrap(), b"abcthis is a long line");
     }
 }
+
+#[tokio::test]
+async fn test_buf_stream_into_inner() {
+    let data_handle = Arc::new(Mutex::new(Vec::new()));
+    let mock_stream = MockWriteStream::new(data_handle.clone(), true);
+    let mut writer = AsyncBufStream::new(mock_stream, 8);
+    assert!(writer.get_ref().deterministic);
+
+    writer.write_all(b"abc").await.unwrap();
+    // pending writes are lost without flush
+    let inner = writer.into_inner();
+    assert!(data_handle.lock().unwrap().is_empty());
+    assert!(Arc::ptr_eq(&inner.write_buffer, &data_handle));
+}
+
+#[tokio::test]
+async fn test_buf_stream_into_inner_flushed() {
+    let data_handle = Arc::new(Mutex::new(Vec::new()));
+    let mock_stream = MockWriteStream::new(data_handle.clone(), true);
+    let mut writer = AsyncBufStream::new(mock_stream, 8);
+
+    writer.write_all(b"abc").await.unwrap();
+    assert!(data_handle.lock().unwrap().is_empty());
+    let inner = writer.into_inner_flushed().await.unwrap();
+    assert_eq!(*data_handle.lock().unwrap(), b"abc");
+    assert!(Arc::ptr_eq(&inner.write_buffer, &data_handle));
+}
+
+#[tokio::test]
+async fn test_buf_read_fill_buf_consume() {
+    use orb::io::AsyncBufRead;
+    let chunks = vec![b"GET /index".to_vec(), b" HTTP/1.1".to_vec()];
+    let mut read_stream = MockReadStream::new_chunked_reader_deterministic(chunks);
+    let mut reader = AsyncBufRead::new(64);
+
+    let buf = reader.fill_buf(&mut read_stream).await.unwrap();
+    assert_eq!(buf, b"GET /index");
+    // parse in place
+    let n = buf.iter().position(|&c| c == b' ').unwrap();
+    assert_eq!(&buf[..n], b"GET");
+    reader.consume(n + 1);
+    // do not refill while there's unread data
+    assert_eq!(reader.fill_buf(&mut read_stream).await.unwrap(), b"/index");
+    // consume past the end is clamped
+    reader.consume(100);
+    assert_eq!(reader.fill_buf(&mut read_stream).await.unwrap(), b" HTTP/1.1");
+    reader.consume(9);
+    // EOF
+    assert!(reader.fill_buf(&mut read_stream).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_buf_read_chunk() {
+    use orb::io::AsyncBufRead;
+    let chunks = vec![b"3:abc5:he".to_vec(), b"llo".to_vec(), b"2:".to_vec()];
+    let mut read_stream = MockReadStream::new_chunked_reader_deterministic(chunks);
+    let mut reader = AsyncBufRead::new(16);
+
+    // Parse netstring-like frames in place
+    let mut frames = Vec::new();
+    loop {
+        let chunk = reader.read_chunk(&mut read_stream).await.unwrap();
+        let mut parsed = 0;
+        while let Some(colon) = chunk[parsed..].iter().position(|&c| c == b':') {
+            let len: usize =
+                std::str::from_utf8(&chunk[parsed..parsed + colon]).unwrap().parse().unwrap();
+            let start = parsed + colon + 1;
+            if chunk.len() < start + len {
+                break;
+            }
+            // The frame is borrowed from the internal buffer
+            frames.push(String::from_utf8_lossy(&chunk[start..start + len]).into_owned());
+            parsed = start + len;
+        }
+        if parsed == 0 && frames.len() == 2 {
+            // Incomplete frame at EOF
+            assert_eq!(chunk, b"2:");
+            break;
+        }
+        reader.consume(parsed);
+    }
+    assert_eq!(frames, vec!["abc", "hello"]);
+    assert_eq!(reader.read_chunk(&mut read_stream).await.unwrap(), b"2:");
+    reader.consume(2);
+    assert!(reader.read_chunk(&mut read_stream).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_buf_read_line_chunked() {
+    use orb::io::AsyncBufRead;
+    let mut data = vec![b'x'; 100];
+    data.extend_from_slice(b"\nshort\ntail");
+    let chunks = data.chunks(7).map(|c| c.to_vec()).collect();
+    let mut read_stream = MockReadStream::new_chunked_reader_deterministic(chunks);
+    let mut reader = AsyncBufRead::new(16);
+
+    let mut line = Vec::new();
+    let mut partial = 0;
+    loop {
+        let chunk = reader.read_line_chunked(&mut read_stream, 30).await.unwrap();
+        assert!(chunk.data.len() <= 30);
+        line.extend_from_slice(&chunk.data);
+        if chunk.is_final {
+            break;
+        }
+        partial += 1;
+    }
+    assert_eq!(partial, 3);
+    assert_eq!(line.len(), 101);
+    assert_eq!(line.last(), Some(&b'\n'));
+
+    let chunk = reader.read_line_chunked(&mut read_stream, 30).await.unwrap();
+    assert_eq!(chunk, LineChunk { data: b"short\n".to_vec(), is_final: true });
+    // The last line without newline
+    let chunk = reader.read_line_chunked(&mut read_stream, 30).await.unwrap();
+    assert_eq!(chunk, LineChunk { data: b"tail".to_vec(), is_final: true });
+    // EOF
+    let chunk = reader.read_line_chunked(&mut read_stream, 30).await.unwrap();
+    assert_eq!(chunk, LineChunk { data: Vec::new(), is_final: true });
+}
+
+// A seekable in-memory stream
+struct MockCursor(std::io::Cursor<Vec<u8>>);
+
+impl AsyncRead for MockCursor {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        std::io::Read::read(&mut self.0, buf)
+    }
+}
+
+impl AsyncWrite for MockCursor {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        std::io::Write::write(&mut self.0, buf)
+    }
+}
+
+impl orb::io::AsyncSeek for MockCursor {
+    async fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        std::io::Seek::seek(&mut self.0, pos)
+    }
+}
+
+#[tokio::test]
+async fn test_buf_stream_seek() {
+    use orb::io::AsyncSeek;
+
+    let data: Vec<u8> = (0..100u8).collect();
+    let mut stream = AsyncBufStream::new(MockCursor(std::io::Cursor::new(data)), 32);
+    let mut buf = [0u8; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, [0, 1, 2, 3]);
+    // The read buffer is ahead, Current is relative to the data consumed
+    assert_eq!(stream.stream_position().await.unwrap(), 4);
+    assert_eq!(stream.seek(io::SeekFrom::Current(6)).await.unwrap(), 10);
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, [10, 11, 12, 13]);
+    assert_eq!(stream.seek(io::SeekFrom::End(-2)).await.unwrap(), 98);
+    stream.read_exact(&mut buf[..2]).await.unwrap();
+    assert_eq!(&buf[..2], [98, 99]);
+
+    // Pending writes are flushed before seek
+    stream.rewind().await.unwrap();
+    stream.write_all(b"ab").await.unwrap();
+    assert_eq!(stream.seek(io::SeekFrom::Start(0)).await.unwrap(), 0);
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"ab\x02\x03");
+}
+
+// One endpoint of an in-memory duplex, reads the preset data, and records the writes
+struct MockDuplex {
+    incoming: io::Cursor<Vec<u8>>,
+    outgoing: Arc<Mutex<Vec<u8>>>,
+}
+
+impl MockDuplex {
+    fn new(incoming: &[u8]) -> Self {
+        Self { incoming: io::Cursor::new(incoming.to_vec()), outgoing: Default::default() }
+    }
+}
+
+impl AsyncRead for MockDuplex {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        std::io::Read::read(&mut self.incoming, buf)
+    }
+}
+
+impl AsyncWrite for MockDuplex {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.outgoing.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+#[tokio::test]
+async fn test_buf_stream_replace_inner() {
+    let old = MockDuplex::new(b"old data");
+    let old_outgoing = old.outgoing.clone();
+    let mut stream = AsyncBufStream::new(old, 32);
+    let mut buf = [0u8; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"old ");
+    stream.write_all(b"request").await.unwrap();
+    stream.flush().await.unwrap();
+
+    let new = MockDuplex::new(b"new data");
+    let new_outgoing = new.outgoing.clone();
+    let old = stream.replace_inner(new);
+    assert!(Arc::ptr_eq(&old.outgoing, &old_outgoing));
+    // The unread "data" of the old stream is discarded
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"new ");
+    stream.write_all(b"retry").await.unwrap();
+    stream.flush().await.unwrap();
+    assert_eq!(*old_outgoing.lock().unwrap(), b"request");
+    assert_eq!(*new_outgoing.lock().unwrap(), b"retry");
+}
@@ -0,0 +1,34 @@
+use orb::utils::{Cancellable, CancellableWith};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_cancellable_with_main() {
+    let r = CancellableWith::new(async { 1u32 }, async {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        "cancel"
+    })
+    .await;
+    assert_eq!(r, Ok(1));
+}
+
+#[tokio::test]
+async fn test_cancellable_with_cancel() {
+    let r = CancellableWith::new(
+        async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            1u32
+        },
+        async { "cancel" },
+    )
+    .await;
+    assert_eq!(r, Err("cancel"));
+}
+
+#[tokio::test]
+async fn test_cancellable_poll_main_first() {
+    // Both ready, the main future wins
+    let r = CancellableWith::new(async { 1u32 }, async { 2u32 }).await;
+    assert_eq!(r, Ok(1));
+    let r = Cancellable::new(async { 1u32 }, async { 2u32 }).await;
+    assert_eq!(r, Ok(1));
+}
@@ -0,0 +1,47 @@
+use orb::io::chain;
+use orb::prelude::*;
+use std::io;
+
+struct SliceReader(Vec<u8>);
+
+impl AsyncRead for SliceReader {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.0.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0.drain(..n);
+        Ok(n)
+    }
+}
+
+#[tokio::test]
+async fn test_chain_across_boundary() {
+    for size in [1, 3, 5, 7, 16] {
+        let mut reader = chain(SliceReader(b"HEAD:".to_vec()), SliceReader(b"body".to_vec()));
+        let mut out = Vec::new();
+        let mut buf = vec![0u8; size];
+        loop {
+            let n = reader.read(&mut buf).await.expect("read");
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(out, b"HEAD:body", "buf size {}", size);
+        // Stays EOF
+        assert_eq!(reader.read(&mut buf).await.expect("read"), 0);
+    }
+}
+
+#[tokio::test]
+async fn test_chain_empty_parts() {
+    let mut reader = chain(SliceReader(Vec::new()), SliceReader(b"abc".to_vec()));
+    let mut buf = [0u8; 8];
+    assert_eq!(reader.read(&mut buf).await.expect("read"), 3);
+    assert_eq!(&buf[..3], b"abc");
+
+    let mut reader = chain(SliceReader(b"abc".to_vec()), SliceReader(Vec::new()));
+    let mut out = [0u8; 3];
+    reader.read_exact(&mut out).await.expect("read");
+    assert_eq!(&out, b"abc");
+    assert_eq!(reader.read(&mut buf).await.expect("read"), 0);
+}
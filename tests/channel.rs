@@ -0,0 +1,92 @@
+use orb::utils::channel::{self, RecvError, SendError, TryRecvError, TrySendError};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_channel_backpressure() {
+    let (tx, mut rx) = channel::bounded::<u32>(2);
+    assert_eq!(tx.capacity(), 2);
+    tx.send(1).await.unwrap();
+    tx.send(2).await.unwrap();
+    assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+    // Blocked on full
+    let r = tokio::time::timeout(Duration::from_millis(20), tx.send(3)).await;
+    assert!(r.is_err());
+    assert_eq!(rx.len(), 2);
+
+    let _tx = tx.clone();
+    let sender = tokio::spawn(async move {
+        for i in 3..10 {
+            _tx.send(i).await.unwrap();
+        }
+    });
+    let mut received = Vec::new();
+    for _ in 0..9 {
+        received.push(rx.recv().await.unwrap());
+        assert!(rx.len() <= 2);
+    }
+    sender.await.unwrap();
+    assert_eq!(received, (1..10).collect::<Vec<_>>());
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[tokio::test]
+async fn test_channel_senders_dropped() {
+    let (tx, mut rx) = channel::bounded::<u32>(4);
+    let tx2 = tx.clone();
+    tx.send(1).await.unwrap();
+    drop(tx);
+    let receiver = tokio::spawn(async move {
+        let mut received = Vec::new();
+        loop {
+            match rx.recv().await {
+                Ok(v) => received.push(v),
+                Err(RecvError) => return received,
+            }
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    tx2.send(2).await.unwrap();
+    drop(tx2);
+    let received =
+        tokio::time::timeout(Duration::from_secs(1), receiver).await.expect("timeout").unwrap();
+    assert_eq!(received, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn test_channel_receiver_dropped() {
+    let (tx, rx) = channel::bounded::<u32>(1);
+    tx.send(1).await.unwrap();
+    let _tx = tx.clone();
+    // Blocked on full, then woken up by the drop of the receiver
+    let sender = tokio::spawn(async move { _tx.send(2).await });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    drop(rx);
+    let r = tokio::time::timeout(Duration::from_secs(1), sender).await.expect("timeout").unwrap();
+    assert_eq!(r, Err(SendError(2)));
+    assert!(tx.is_closed());
+    assert_eq!(tx.try_send(3), Err(TrySendError::Closed(3)));
+}
+
+#[tokio::test]
+async fn test_channel_close() {
+    let (tx, mut rx) = channel::bounded::<u32>(4);
+    tx.send(1).await.unwrap();
+    rx.close();
+    assert_eq!(tx.send(2).await, Err(SendError(2)));
+    // The buffered messages can still be received
+    assert_eq!(rx.recv().await, Ok(1));
+    assert_eq!(rx.recv().await, Err(RecvError));
+}
+
+#[tokio::test]
+async fn test_channel_send_cancel() {
+    let (tx, mut rx) = channel::bounded::<u32>(1);
+    tx.send(1).await.unwrap();
+    let r = tokio::time::timeout(Duration::from_millis(10), tx.send(2)).await;
+    assert!(r.is_err());
+    assert_eq!(rx.recv().await, Ok(1));
+    // The cancelled message is not sent
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    tx.send(3).await.unwrap();
+    assert_eq!(rx.recv().await, Ok(3));
+}
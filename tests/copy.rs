@@ -0,0 +1,60 @@
+use orb::io::copy;
+use orb::prelude::*;
+use std::io;
+
+/// Returns the data in small chunks, with Interrupted in between
+struct ChunkReader {
+    data: Vec<u8>,
+    chunk: usize,
+    interrupt: bool,
+}
+
+impl AsyncRead for ChunkReader {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        let n = buf.len().min(self.chunk).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data.drain(..n);
+        Ok(n)
+    }
+}
+
+/// Accepts at most 1000 bytes per write, the data is visible after flush
+#[derive(Default)]
+struct ShortWriter {
+    pending: Vec<u8>,
+    flushed: Vec<u8>,
+}
+
+impl AsyncWrite for ShortWriter {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(1000);
+        self.pending.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        self.flushed.append(&mut self.pending);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_copy() {
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let mut reader = ChunkReader { data: data.clone(), chunk: 30_000, interrupt: false };
+    let mut writer = ShortWriter::default();
+    let n = copy(&mut reader, &mut writer).await.expect("copy");
+    assert_eq!(n, data.len() as u64);
+    assert!(writer.pending.is_empty());
+    assert_eq!(writer.flushed, data);
+
+    // Empty reader
+    let mut reader = ChunkReader { data: Vec::new(), chunk: 1, interrupt: false };
+    let mut writer = ShortWriter::default();
+    assert_eq!(copy(&mut reader, &mut writer).await.expect("copy"), 0);
+    assert!(writer.flushed.is_empty());
+}
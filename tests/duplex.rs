@@ -0,0 +1,77 @@
+use futures_lite::future::zip;
+use orb::io::duplex;
+use orb::net::{StreamStats, StreamStatsSnapshot};
+use orb::prelude::*;
+use std::io;
+
+#[tokio::test]
+async fn test_duplex_read_write() {
+    let (mut a, mut b) = duplex(64);
+    a.write_all(b"ping").await.expect("write");
+    let mut buf = [0u8; 4];
+    b.read_exact(&mut buf).await.expect("read");
+    assert_eq!(&buf, b"ping");
+    b.write_all(b"pong").await.expect("write");
+    a.read_exact(&mut buf).await.expect("read");
+    assert_eq!(&buf, b"pong");
+
+    // Shutdown passes EOF after the data
+    a.write_all(b"bye").await.expect("write");
+    a.shutdown().await.expect("shutdown");
+    let mut buf = [0u8; 8];
+    assert_eq!(b.read(&mut buf).await.expect("read"), 3);
+    assert_eq!(b.read(&mut buf).await.expect("read"), 0);
+    // The other direction still works
+    b.write_all(b"ok").await.expect("write");
+    assert_eq!(a.read(&mut buf).await.expect("read"), 2);
+
+    drop(a);
+    let e = b.write(b"x").await.expect_err("peer dropped");
+    assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[tokio::test]
+async fn test_duplex_backpressure() {
+    let (mut a, mut b) = duplex(16);
+    // Only the room of the buffer is accepted
+    assert_eq!(a.write(&[1u8; 20]).await.expect("write"), 16);
+    let blocked = futures_lite::future::poll_once(a.write(b"more")).await;
+    assert!(blocked.is_none());
+
+    let mut buf = [0u8; 16];
+    b.read_exact(&mut buf).await.expect("read");
+
+    // The writer waits until the reader drains
+    let data: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    let writer = async {
+        a.write_all(&data).await.expect("write");
+        a.shutdown().await.expect("shutdown");
+    };
+    let reader = async {
+        let mut out = Vec::new();
+        let mut buf = [0u8; 7];
+        loop {
+            let n = b.read(&mut buf).await.expect("read");
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+        out
+    };
+    let ((), out) = zip(writer, reader).await;
+    assert_eq!(out, data);
+}
+
+#[tokio::test]
+async fn test_duplex_stats() {
+    let (mut a, b) = duplex(64);
+    a.write_all(b"hello").await.expect("write");
+    // Only the buffer fill, the socket metrics are None
+    let expected =
+        StreamStatsSnapshot { recv_queued: Some(0), send_queued: Some(5), ..Default::default() };
+    assert_eq!(a.stats().expect("stats"), expected);
+    let expected =
+        StreamStatsSnapshot { recv_queued: Some(5), send_queued: Some(0), ..Default::default() };
+    assert_eq!(b.stats().expect("stats"), expected);
+}
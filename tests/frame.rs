@@ -0,0 +1,62 @@
+use orb::io::LengthDelimited;
+use orb::prelude::*;
+use std::io;
+
+struct SliceReader(Vec<u8>);
+
+impl AsyncRead for SliceReader {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.0.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0.drain(..n);
+        Ok(n)
+    }
+}
+
+struct VecWriter(Vec<u8>);
+
+impl AsyncWrite for VecWriter {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+#[tokio::test]
+async fn test_length_delimited_round_trip() {
+    let codec = LengthDelimited::new(16);
+    let mut writer = VecWriter(Vec::new());
+    codec.write_frame(&mut writer, b"hello").await.expect("write");
+    codec.write_frame(&mut writer, b"").await.expect("write");
+    codec.write_frame(&mut writer, b"0123456789abcdef").await.expect("write");
+    assert_eq!(&writer.0[..9], b"\0\0\0\x05hello");
+
+    let mut reader = SliceReader(writer.0);
+    assert_eq!(codec.read_frame(&mut reader).await.expect("read"), b"hello");
+    let mut buf = Vec::with_capacity(16);
+    codec.read_frame_into(&mut reader, &mut buf).await.expect("read");
+    assert!(buf.is_empty());
+    codec.read_frame_into(&mut reader, &mut buf).await.expect("read");
+    assert_eq!(buf, b"0123456789abcdef");
+    let e = codec.read_frame(&mut reader).await.expect_err("eof");
+    assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn test_length_delimited_oversized() {
+    let codec = LengthDelimited::new(4);
+    let mut writer = VecWriter(Vec::new());
+    let e = codec.write_frame(&mut writer, b"hello").await.expect_err("oversized");
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    assert!(writer.0.is_empty());
+
+    // A malicious length is rejected before reading the body
+    let mut reader = SliceReader(b"\xff\xff\xff\xffabc".to_vec());
+    let e = codec.read_frame(&mut reader).await.expect_err("oversized");
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+
+    // Truncated body
+    let mut reader = SliceReader(b"\0\0\0\x04ab".to_vec());
+    let e = codec.read_frame(&mut reader).await.expect_err("truncated");
+    assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,35 @@
+use orb::runtime::{Metrics, MetricsSnapshot};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_metrics_track() {
+    let metrics = Metrics::new();
+    let mut handles = Vec::new();
+    for i in 0..10 {
+        handles.push(tokio::spawn(metrics.track(async move { i })));
+    }
+    for (i, h) in handles.into_iter().enumerate() {
+        assert_eq!(h.await.unwrap(), i);
+    }
+    assert_eq!(
+        metrics.snapshot(),
+        MetricsSnapshot { spawned: 10, completed: 10, panicked: 0, active: 0 }
+    );
+
+    // Panic captured by tokio
+    let h = tokio::spawn(metrics.track(async { panic!("task panic") }));
+    assert!(h.await.is_err());
+    let hung = tokio::spawn(metrics.track(tokio::time::sleep(Duration::from_secs(3600))));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(
+        metrics.snapshot(),
+        MetricsSnapshot { spawned: 12, completed: 10, panicked: 1, active: 1 }
+    );
+    // Aborted is neither completed nor panicked
+    hung.abort();
+    assert!(hung.await.unwrap_err().is_cancelled());
+    assert_eq!(
+        metrics.snapshot(),
+        MetricsSnapshot { spawned: 12, completed: 10, panicked: 1, active: 0 }
+    );
+}
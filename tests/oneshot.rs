@@ -0,0 +1,43 @@
+use orb::utils::oneshot::{self, Canceled};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_oneshot_deliver() {
+    let (tx, rx) = oneshot::oneshot::<String>();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        tx.send("done".to_string()).unwrap();
+    });
+    let r = tokio::time::timeout(Duration::from_secs(1), rx).await.expect("timeout");
+    assert_eq!(r.as_deref(), Ok("done"));
+
+    // Sent before polled
+    let (tx, mut rx) = orb::utils::oneshot::<u32>();
+    assert_eq!(rx.try_recv(), Ok(None));
+    tx.send(1).unwrap();
+    assert_eq!(rx.await, Ok(1));
+}
+
+#[tokio::test]
+async fn test_oneshot_sender_dropped() {
+    let (tx, rx) = oneshot::oneshot::<u32>();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(tx);
+    });
+    let r = tokio::time::timeout(Duration::from_secs(1), rx).await.expect("timeout");
+    assert_eq!(r, Err(Canceled));
+
+    let (tx, mut rx) = oneshot::oneshot::<u32>();
+    drop(tx);
+    assert_eq!(rx.try_recv(), Err(Canceled));
+}
+
+#[tokio::test]
+async fn test_oneshot_receiver_dropped() {
+    let (tx, rx) = oneshot::oneshot::<u32>();
+    assert!(!tx.is_closed());
+    drop(rx);
+    assert!(tx.is_closed());
+    assert_eq!(tx.send(1), Err(1));
+}
@@ -0,0 +1,64 @@
+use orb::io::ReadBuf;
+use orb::prelude::*;
+use std::io;
+use std::mem::MaybeUninit;
+
+struct SliceReader(Vec<u8>);
+
+impl AsyncRead for SliceReader {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.0.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0.drain(..n);
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_read_buf_regions() {
+    let mut storage = [MaybeUninit::<u8>::uninit(); 16];
+    let mut buf = ReadBuf::uninit(&mut storage);
+    assert_eq!(buf.capacity(), 16);
+    assert_eq!(buf.remaining(), 16);
+    assert!(buf.initialized().is_empty());
+
+    buf.put_slice(b"abc");
+    assert_eq!(buf.filled(), b"abc");
+    assert_eq!(buf.initialized().len(), 3);
+    assert_eq!(buf.remaining(), 13);
+
+    buf.clear();
+    assert!(buf.filled().is_empty());
+    assert_eq!(buf.initialized(), b"abc");
+    buf.advance(2);
+    assert_eq!(buf.filled(), b"ab");
+
+    let unfilled = buf.initialize_unfilled();
+    assert_eq!(unfilled.len(), 14);
+    assert_eq!(unfilled[0], b'c');
+    assert!(unfilled[1..].iter().all(|b| *b == 0));
+    assert_eq!(buf.initialized().len(), 16);
+}
+
+#[test]
+#[should_panic]
+fn test_read_buf_advance_uninit() {
+    let mut storage = [MaybeUninit::<u8>::uninit(); 4];
+    let mut buf = ReadBuf::uninit(&mut storage);
+    buf.advance(1);
+}
+
+#[test]
+fn test_read_buf_default() {
+    futures_lite::future::block_on(async {
+        let mut reader = SliceReader(b"hello world".to_vec());
+        let mut storage = [MaybeUninit::<u8>::uninit(); 8];
+        let mut buf = ReadBuf::uninit(&mut storage);
+        assert_eq!(reader.read_buf(&mut buf).await.unwrap(), 8);
+        assert_eq!(buf.filled(), b"hello wo");
+        assert_eq!(reader.read_buf(&mut buf).await.unwrap(), 0);
+        buf.clear();
+        assert_eq!(reader.read_buf(&mut buf).await.unwrap(), 3);
+        assert_eq!(buf.filled(), b"rld");
+    });
+}
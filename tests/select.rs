@@ -0,0 +1,37 @@
+use orb::utils::{Either, ShutdownToken, select2};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_select2_left() {
+    let r = select2(
+        async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            1u32
+        },
+        async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            "right"
+        },
+    )
+    .await;
+    assert_eq!(r, Either::Left(1));
+}
+
+#[tokio::test]
+async fn test_select2_right() {
+    let (trigger, token) = ShutdownToken::new();
+    let r = select2(tokio::time::sleep(Duration::from_secs(10)), async {
+        trigger.shutdown();
+        token.cancelled().await;
+        2u32
+    })
+    .await;
+    assert!(r.is_right());
+    assert_eq!(r, Either::Right(2));
+}
+
+#[tokio::test]
+async fn test_select2_both_ready() {
+    let r = select2(async { 1 }, async { 2 }).await;
+    assert_eq!(r.into_inner(), 1);
+}
@@ -0,0 +1,37 @@
+use orb::utils::{Cancellable, ShutdownToken};
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_shutdown_wake_all() {
+    let (trigger, token) = ShutdownToken::new();
+    let mut handles = Vec::new();
+    for _ in 0..8 {
+        let token = token.clone();
+        handles.push(tokio::spawn(async move {
+            token.cancelled().await;
+            assert!(token.is_cancelled());
+        }));
+    }
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!token.is_cancelled());
+    trigger.shutdown();
+    for h in handles {
+        tokio::time::timeout(Duration::from_secs(1), h).await.expect("timeout").unwrap();
+    }
+    // Already cancelled, return immediately
+    trigger.token().cancelled().await;
+    assert!(trigger.is_shutdown());
+}
+
+#[tokio::test]
+async fn test_shutdown_cancellable() {
+    let (trigger, token) = ShutdownToken::new();
+    let _token = token.clone();
+    let handle = tokio::spawn(async move {
+        Cancellable::new(tokio::time::sleep(Duration::from_secs(10)), _token.cancelled()).await
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    trigger.shutdown();
+    let r = tokio::time::timeout(Duration::from_secs(1), handle).await.expect("timeout").unwrap();
+    assert!(r.is_err());
+}
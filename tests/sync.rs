@@ -0,0 +1,252 @@
+use orb::utils::Condvar;
+use orb::utils::sync::{Mutex, Notify, RwLock, Semaphore};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_condvar_wait_while() {
+    let shared = Arc::new((Mutex::new(VecDeque::new()), Condvar::new()));
+    let _shared = shared.clone();
+    let consumer = tokio::spawn(async move {
+        let (queue, cond) = &*_shared;
+        let mut received = Vec::new();
+        while received.len() < 10 {
+            let mut guard = cond.wait_while(queue.lock().await, |q| q.is_empty()).await;
+            while let Some(i) = guard.pop_front() {
+                received.push(i);
+            }
+        }
+        received
+    });
+    let (queue, cond) = &*shared;
+    for i in 0..10 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        queue.lock().await.push_back(i);
+        cond.notify_one();
+    }
+    let received = consumer.await.unwrap();
+    assert_eq!(received, (0..10).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_condvar_notify_all() {
+    let shared = Arc::new((Mutex::new(false), Condvar::new()));
+    let mut handles = Vec::new();
+    for _ in 0..4 {
+        let _shared = shared.clone();
+        handles.push(tokio::spawn(async move {
+            let (ready, cond) = &*_shared;
+            let guard = cond.wait_while(ready.lock().await, |ready| !*ready).await;
+            assert!(*guard);
+        }));
+    }
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let (ready, cond) = &*shared;
+    *ready.lock().await = true;
+    cond.notify_all();
+    for h in handles {
+        tokio::time::timeout(Duration::from_secs(1), h).await.expect("timeout").unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_condvar_wait_cancel() {
+    let (mutex, cond) = (Mutex::new(0), Condvar::new());
+    let guard = mutex.lock().await;
+    let r = tokio::time::timeout(Duration::from_millis(10), cond.wait(guard)).await;
+    assert!(r.is_err());
+    // The lock is released on cancellation
+    assert!(mutex.try_lock().is_some());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_mutex_exclusion() {
+    let shared = Arc::new((Mutex::new(0usize), AtomicUsize::new(0)));
+    let mut handles = Vec::new();
+    for _ in 0..8 {
+        let _shared = shared.clone();
+        handles.push(tokio::spawn(async move {
+            let (mutex, holders) = &*_shared;
+            for _ in 0..100 {
+                let mut guard = mutex.lock().await;
+                assert_eq!(holders.fetch_add(1, Ordering::SeqCst), 0);
+                let v = *guard;
+                tokio::task::yield_now().await;
+                *guard = v + 1;
+                holders.fetch_sub(1, Ordering::SeqCst);
+            }
+        }));
+    }
+    for h in handles {
+        h.await.unwrap();
+    }
+    assert_eq!(*shared.0.lock().await, 800);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_rwlock_exclusion() {
+    let shared = Arc::new((RwLock::new(0usize), AtomicUsize::new(0), AtomicUsize::new(0)));
+    let mut handles = Vec::new();
+    for i in 0..8 {
+        let _shared = shared.clone();
+        handles.push(tokio::spawn(async move {
+            let (lock, readers, writers) = &*_shared;
+            for _ in 0..100 {
+                if i % 2 == 0 {
+                    let mut guard = lock.write().await;
+                    assert_eq!(writers.fetch_add(1, Ordering::SeqCst), 0);
+                    assert_eq!(readers.load(Ordering::SeqCst), 0);
+                    let v = *guard;
+                    tokio::task::yield_now().await;
+                    *guard = v + 1;
+                    writers.fetch_sub(1, Ordering::SeqCst);
+                } else {
+                    let guard = lock.read().await;
+                    readers.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(writers.load(Ordering::SeqCst), 0);
+                    let v = *guard;
+                    tokio::task::yield_now().await;
+                    assert_eq!(*guard, v);
+                    readers.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        }));
+    }
+    for h in handles {
+        h.await.unwrap();
+    }
+    assert_eq!(*shared.0.read().await, 400);
+}
+
+#[tokio::test]
+async fn test_rwlock_shared_and_fair() {
+    let lock = RwLock::new(1);
+    let r1 = lock.read().await;
+    let r2 = lock.try_read().expect("shared read");
+    assert_eq!(*r1 + *r2, 2);
+    assert!(lock.try_write().is_none());
+    {
+        // A waiting writer blocks the new readers
+        let write = lock.write();
+        futures_lite::pin!(write);
+        assert!(futures_lite::future::poll_once(&mut write).await.is_none());
+        assert!(lock.try_read().is_none());
+        drop(r1);
+        drop(r2);
+        let mut guard = write.await;
+        *guard = 2;
+    }
+    assert_eq!(*lock.read().await, 2);
+    // The cancelled writer gives up its turn
+    let r = lock.read().await;
+    let write = tokio::time::timeout(Duration::from_millis(10), lock.write()).await;
+    assert!(write.is_err());
+    assert!(lock.try_read().is_some());
+    drop(r);
+    assert!(lock.try_write().is_some());
+}
+
+#[tokio::test]
+async fn test_semaphore_blocks() {
+    let sema = Semaphore::new(2);
+    let p1 = sema.acquire().await;
+    let _p2 = sema.acquire().await;
+    assert_eq!(sema.available_permits(), 0);
+    assert!(sema.try_acquire().is_none());
+    // The 3rd blocks until a permit is released
+    let third = sema.acquire();
+    futures_lite::pin!(third);
+    assert!(futures_lite::future::poll_once(&mut third).await.is_none());
+    drop(p1);
+    let _p3 = tokio::time::timeout(Duration::from_secs(1), third).await.expect("timeout");
+    assert_eq!(sema.available_permits(), 0);
+
+    sema.add_permits(1);
+    assert!(sema.try_acquire().is_some());
+    sema.try_acquire().unwrap().forget();
+    assert_eq!(sema.available_permits(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_semaphore_owned() {
+    let sema = Arc::new(Semaphore::new(3));
+    let running = Arc::new(AtomicUsize::new(0));
+    let max = Arc::new(AtomicUsize::new(0));
+    let mut handles = Vec::new();
+    for _ in 0..20 {
+        let permit = sema.clone().acquire_owned().await;
+        let (running, max) = (running.clone(), max.clone());
+        handles.push(tokio::spawn(async move {
+            let n = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max.fetch_max(n, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            drop(permit);
+        }));
+    }
+    for h in handles {
+        h.await.unwrap();
+    }
+    assert!(max.load(Ordering::SeqCst) <= 3);
+    assert_eq!(sema.available_permits(), 3);
+    sema.clone().try_acquire_owned().unwrap().forget();
+    assert_eq!(sema.available_permits(), 2);
+}
+
+#[tokio::test]
+async fn test_notify_permit() {
+    let notify = Notify::new();
+    // Stored as a permit, only one
+    notify.notify_one();
+    notify.notify_one();
+    tokio::time::timeout(Duration::from_secs(1), notify.notified()).await.expect("timeout");
+    let r = tokio::time::timeout(Duration::from_millis(10), notify.notified()).await;
+    assert!(r.is_err());
+
+    let notify = Arc::new(Notify::new());
+    let _notify = notify.clone();
+    let waiter = tokio::spawn(async move { _notify.notified().await });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    notify.notify_one();
+    tokio::time::timeout(Duration::from_secs(1), waiter).await.expect("timeout").unwrap();
+    // Consumed by the waiter
+    let r = tokio::time::timeout(Duration::from_millis(10), notify.notified()).await;
+    assert!(r.is_err());
+}
+
+#[tokio::test]
+async fn test_notify_waiters() {
+    let notify = Arc::new(Notify::new());
+    let mut handles = Vec::new();
+    for _ in 0..4 {
+        let _notify = notify.clone();
+        handles.push(tokio::spawn(async move { _notify.notified().await }));
+    }
+    // Created but not yet polled
+    let pending = notify.notified();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    notify.notify_waiters();
+    for h in handles {
+        tokio::time::timeout(Duration::from_secs(1), h).await.expect("timeout").unwrap();
+    }
+    tokio::time::timeout(Duration::from_secs(1), pending).await.expect("timeout");
+    // Not for the future ones, and no permit stored
+    let r = tokio::time::timeout(Duration::from_millis(10), notify.notified()).await;
+    assert!(r.is_err());
+}
+
+#[tokio::test]
+async fn test_notify_one_cancelled() {
+    let notify = Notify::new();
+    let mut first = Box::pin(notify.notified());
+    assert!(futures_lite::future::poll_once(&mut first).await.is_none());
+    let second = notify.notified();
+    futures_lite::pin!(second);
+    assert!(futures_lite::future::poll_once(&mut second).await.is_none());
+    notify.notify_one();
+    // The notification passes to the second on cancellation of the first
+    drop(first);
+    tokio::time::timeout(Duration::from_secs(1), second).await.expect("timeout");
+}
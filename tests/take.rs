@@ -0,0 +1,54 @@
+use orb::io::take;
+use orb::prelude::*;
+use std::io;
+
+struct SliceReader(Vec<u8>);
+
+impl AsyncRead for SliceReader {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.0.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0.drain(..n);
+        Ok(n)
+    }
+}
+
+#[tokio::test]
+async fn test_take_limit() {
+    let mut reader = take(SliceReader(b"0123456789".to_vec()), 5);
+    let mut buf = [0u8; 16];
+    assert_eq!(reader.read(&mut buf).await.expect("read"), 5);
+    assert_eq!(&buf[..5], b"01234");
+    assert_eq!(reader.limit(), 0);
+    assert_eq!(reader.read(&mut buf).await.expect("read"), 0);
+
+    // The rest is still in the inner reader
+    reader.set_limit(2);
+    assert_eq!(reader.read(&mut buf).await.expect("read"), 2);
+    assert_eq!(&buf[..2], b"56");
+    let mut inner = reader.into_inner();
+    assert_eq!(inner.read(&mut buf).await.expect("read"), 3);
+    assert_eq!(&buf[..3], b"789");
+}
+
+#[tokio::test]
+async fn test_take_small_buf() {
+    let mut reader = take(SliceReader(b"0123456789".to_vec()), 5);
+    let mut out = Vec::new();
+    let mut buf = [0u8; 2];
+    loop {
+        let n = reader.read(&mut buf).await.expect("read");
+        if n == 0 {
+            break;
+        }
+        out.extend_from_slice(&buf[..n]);
+    }
+    assert_eq!(out, b"01234");
+
+    // Shorter source than the limit
+    let mut reader = take(SliceReader(b"abc".to_vec()), 5);
+    let mut buf = [0u8; 16];
+    assert_eq!(reader.read(&mut buf).await.expect("read"), 3);
+    assert_eq!(reader.read(&mut buf).await.expect("read"), 0);
+    assert_eq!(reader.limit(), 2);
+}
@@ -163,6 +163,47 @@ fn test_unify_addr_parse() {
     }
 }
 
+#[test]
+fn test_unify_addr_parse_scheme() {
+    let addr = UnifyAddr::parse("tcp://127.0.0.1:8080").expect("tcp scheme");
+    assert_eq!(addr, UnifyAddr::Socket(SocketAddr::from(([127, 0, 0, 1], 8080))));
+    let addr = UnifyAddr::parse("tcp://[::1]:80").expect("tcp scheme v6");
+    assert_eq!(addr, UnifyAddr::Socket(SocketAddr::from((Ipv6Addr::LOCALHOST, 80))));
+    // tcp:// never falls back to the path
+    assert!(UnifyAddr::parse("tcp:///tmp/test.sock").is_err());
+    assert!(UnifyAddr::parse("tcp://localhost:80").is_err());
+
+    let addr = UnifyAddr::parse("unix:/var/run/app.sock").expect("unix scheme");
+    assert_eq!(addr, UnifyAddr::Path(PathBuf::from("/var/run/app.sock")));
+    // Relative path is forced with unix:
+    let addr = UnifyAddr::parse("unix:app.sock").expect("unix scheme relative");
+    assert_eq!(addr, UnifyAddr::Path(PathBuf::from("app.sock")));
+    let addr = UnifyAddr::parse("unix:127.0.0.1:80").expect("unix scheme looks like ip");
+    assert_eq!(addr, UnifyAddr::Path(PathBuf::from("127.0.0.1:80")));
+    assert!(UnifyAddr::parse("unix:").is_err());
+
+    // The bare forms without scheme
+    let addr = UnifyAddr::parse("[::1]:80").expect("bracketed v6");
+    assert_eq!(addr, UnifyAddr::Socket(SocketAddr::from((Ipv6Addr::LOCALHOST, 80))));
+    let addr = UnifyAddr::parse("/tmp/test.sock").expect("path");
+    assert_eq!(addr, UnifyAddr::Path(PathBuf::from("/tmp/test.sock")));
+    assert!(UnifyAddr::parse("app.sock").is_err());
+    assert!(UnifyAddr::parse("").is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_unify_addr_parse_abstract() {
+    let addr = UnifyAddr::parse("@myservice").expect("Failed to parse abstract address");
+    match addr {
+        UnifyAddr::Abstract(ref name) => assert_eq!(name, "myservice"),
+        _ => panic!("Expected Abstract address"),
+    }
+    assert_eq!(addr.to_string(), "@myservice");
+    assert!(addr == *"@myservice");
+    assert!(addr.to_socket_addrs().is_err());
+}
+
 #[test]
 fn test_unify_addr_to_socket_addrs() {
     // Test SocketAddr conversion
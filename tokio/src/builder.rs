@@ -0,0 +1,92 @@
+use crate::TokioRT;
+use std::io;
+use tokio::runtime::Builder;
+
+/// Builder of [TokioRT], returned by [TokioRT::builder()].
+///
+/// Multi-thread runtime by default, with all the drivers (io, time) enabled.
+#[derive(Debug, Clone, Default)]
+pub struct TokioRTBuilder {
+    current_thread: bool,
+    worker_threads: Option<usize>,
+    thread_name: Option<String>,
+    thread_stack_size: Option<usize>,
+    max_blocking_threads: Option<usize>,
+    #[cfg(feature = "test-util")]
+    start_paused: bool,
+}
+
+impl TokioRTBuilder {
+    /// Run the tasks on the thread calling `block_on()`, instead of a pool of worker threads.
+    #[inline]
+    pub fn current_thread(mut self) -> Self {
+        self.current_thread = true;
+        self
+    }
+
+    /// The number of worker threads, 0 for the default (the number of CPU cores).
+    /// Ignored by current_thread runtime.
+    #[inline]
+    pub fn worker_threads(mut self, n: usize) -> Self {
+        self.worker_threads = Some(n);
+        self
+    }
+
+    /// The name of the threads spawned by the runtime (both worker and blocking threads)
+    #[inline]
+    pub fn thread_name(mut self, name: impl Into<String>) -> Self {
+        self.thread_name = Some(name.into());
+        self
+    }
+
+    /// The stack size in bytes of the threads spawned by the runtime, for deep recursive tasks.
+    #[inline]
+    pub fn thread_stack_size(mut self, size: usize) -> Self {
+        self.thread_stack_size = Some(size);
+        self
+    }
+
+    /// The limit of threads for `spawn_blocking()`, tokio defaults to 512.
+    #[inline]
+    pub fn max_blocking_threads(mut self, n: usize) -> Self {
+        self.max_blocking_threads = Some(n);
+        self
+    }
+
+    /// Start the runtime with the clock paused, refer to
+    /// [AsyncTimeControl](orb::time::AsyncTimeControl). Requires the current_thread runtime.
+    ///
+    /// NOTE: tokio auto-advances the paused clock to the next timer when the runtime is idle.
+    #[cfg(feature = "test-util")]
+    #[inline]
+    pub fn start_paused(mut self, paused: bool) -> Self {
+        self.start_paused = paused;
+        self
+    }
+
+    pub fn build(self) -> io::Result<TokioRT> {
+        let mut builder = if self.current_thread {
+            Builder::new_current_thread()
+        } else {
+            Builder::new_multi_thread()
+        };
+        if let Some(n) = self.worker_threads
+            && n > 0
+            && !self.current_thread
+        {
+            builder.worker_threads(n);
+        }
+        if let Some(name) = self.thread_name {
+            builder.thread_name(name);
+        }
+        if let Some(size) = self.thread_stack_size {
+            builder.thread_stack_size(size);
+        }
+        if let Some(n) = self.max_blocking_threads {
+            builder.max_blocking_threads(n);
+        }
+        #[cfg(feature = "test-util")]
+        builder.start_paused(self.start_paused);
+        Ok(TokioRT::Runtime(builder.enable_all().build()?))
+    }
+}
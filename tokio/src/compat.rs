@@ -0,0 +1,64 @@
+use orb::io::{AsyncRead, AsyncWrite, FuturesCompat};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tokio::io::ReadBuf;
+
+/// An adapter which implements [tokio::io::AsyncRead] and [tokio::io::AsyncWrite] for orb
+/// streams, to work with the libraries of tokio ecology (hyper, tokio-rustls etc).
+///
+/// It's generic over the orb streams supporting I/O by reference (TcpStream, UnixStream,
+/// UnifyStream), built on [FuturesCompat], refer to it for the limitations.
+pub struct TokioCompat<S: Sync + 'static>(FuturesCompat<S>);
+
+impl<S: Sync + 'static> TokioCompat<S>
+where
+    for<'a> &'a S: AsyncRead + AsyncWrite,
+{
+    #[inline]
+    pub fn new(inner: S) -> Self {
+        Self(FuturesCompat::new(inner))
+    }
+
+    /// Cancel the in-flight operation, and return the inner stream.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.0.into_inner()
+    }
+}
+
+impl<S: Sync + 'static> tokio::io::AsyncRead for TokioCompat<S>
+where
+    for<'a> &'a S: AsyncRead + AsyncWrite,
+{
+    fn poll_read(
+        self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let inner = Pin::new(&mut self.get_mut().0);
+        let n = ready!(futures_io::AsyncRead::poll_read(inner, cx, buf.initialize_unfilled()))?;
+        buf.advance(n);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: Sync + 'static> tokio::io::AsyncWrite for TokioCompat<S>
+where
+    for<'a> &'a S: AsyncRead + AsyncWrite,
+{
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        futures_io::AsyncWrite::poll_write(Pin::new(&mut self.get_mut().0), cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        futures_io::AsyncWrite::poll_flush(Pin::new(&mut self.get_mut().0), cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        futures_io::AsyncWrite::poll_close(Pin::new(&mut self.get_mut().0), cx)
+    }
+}
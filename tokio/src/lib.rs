@@ -5,6 +5,17 @@
 //!
 //! The main type provided is [`TokioRT`], which implements the core runtime functionality.
 //!
+//! ## Features
+//!
+//! - `tokio-compat`: Provides `TokioCompat` adapter, which implements `tokio::io::AsyncRead` /
+//!   `AsyncWrite` for orb streams. (by default not enabled)
+//! - `tracing`: Name the tasks of `spawn_named()` / `spawn_blocking_named()` with
+//!   `tokio::task::Builder`, which also requires `RUSTFLAGS="--cfg tokio_unstable"`. Otherwise the
+//!   name is ignored. (by default not enabled)
+//! - `test-util`: Implements [AsyncTimeControl](orb::time::AsyncTimeControl) for [`TokioRT`], to
+//!   pause and advance the clock in tests, with `tokio::time::pause()` / `advance()`. Only the
+//!   current_thread runtime can be paused. (by default not enabled)
+//!
 //! See the [main Orb documentation](https://github.com/NaturalIO/orb) for more information.
 //!
 //! ## Usage
@@ -16,8 +27,8 @@
 //! ```
 
 use orb::io::{AsyncFd, AsyncIO};
-pub use orb::runtime::{AsyncExec, AsyncHandle, ThreadHandle};
-use orb::time::{AsyncTime, TimeInterval};
+pub use orb::runtime::{AsyncExec, AsyncHandle, BlockingError, RuntimeFlavor, ThreadHandle};
+use orb::time::{AsyncTime, TimeDelay, TimeInterval, count_missed_ticks};
 use std::fmt;
 use std::future::Future;
 use std::io;
@@ -30,7 +41,14 @@ use std::path::PathBuf;
 use std::pin::Pin;
 use std::task::*;
 use std::time::{Duration, Instant};
-use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::runtime::{Handle, Runtime};
+
+mod builder;
+pub use builder::TokioRTBuilder;
+#[cfg(feature = "tokio-compat")]
+mod compat;
+#[cfg(feature = "tokio-compat")]
+pub use compat::TokioCompat;
 
 /// The main struct for tokio runtime IO, assign this type to AsyncIO trait when used.
 pub enum TokioRT {
@@ -55,19 +73,32 @@ impl TokioRT {
         Self::Runtime(rt)
     }
 
+    /// Customize the runtime, refer to [TokioRTBuilder]
+    #[inline]
+    pub fn builder() -> TokioRTBuilder {
+        TokioRTBuilder::default()
+    }
+
+    /// Multi-thread runtime with `workers` threads (0 for the default).
+    ///
+    /// Panics if failed to build, use [builder()](Self::builder) to handle the error.
     #[inline]
     pub fn new_multi_thread(workers: usize) -> Self {
-        let mut builder = Builder::new_multi_thread();
-        if workers > 0 {
-            builder.worker_threads(workers);
-        }
-        Self::Runtime(builder.enable_all().build().unwrap())
+        Self::builder().worker_threads(workers).build().unwrap()
     }
 
+    /// Panics if failed to build, use [builder()](Self::builder) to handle the error.
     #[inline]
     pub fn new_current_thread() -> Self {
-        let mut builder = Builder::new_current_thread();
-        Self::Runtime(builder.enable_all().build().unwrap())
+        Self::builder().current_thread().build().unwrap()
+    }
+
+    #[inline]
+    fn handle(&self) -> &Handle {
+        match self {
+            Self::Runtime(s) => s.handle(),
+            Self::Handle(s) => s,
+        }
     }
 
     /// Only capture a runtime handle. Should acquire with
@@ -119,32 +150,82 @@ impl AsyncIO for TokioRT {
     fn to_async_fd_rd<T: AsRawFd + AsFd + Send + Sync + 'static>(
         fd: T,
     ) -> io::Result<Self::AsyncFd<T>> {
-        use tokio::io;
-        Ok(TokioFD(io::unix::AsyncFd::with_interest(fd, io::Interest::READABLE)?))
+        Self::to_async_fd(fd, orb::io::Interest::READ)
     }
 
     #[inline(always)]
     fn to_async_fd_rw<T: AsRawFd + AsFd + Send + Sync + 'static>(
         fd: T,
     ) -> io::Result<Self::AsyncFd<T>> {
-        use tokio::io;
+        Self::to_async_fd(fd, orb::io::Interest::READ_WRITE)
+    }
+
+    #[inline(always)]
+    fn to_async_fd<T: AsRawFd + AsFd + Send + Sync + 'static>(
+        fd: T, interest: orb::io::Interest,
+    ) -> io::Result<Self::AsyncFd<T>> {
         use tokio::io::Interest;
-        Ok(TokioFD(io::unix::AsyncFd::with_interest(fd, Interest::READABLE | Interest::WRITABLE)?))
+        let tokio_interest = match (interest.is_readable(), interest.is_writable()) {
+            (true, true) => Interest::READABLE | Interest::WRITABLE,
+            (false, true) => Interest::WRITABLE,
+            _ => Interest::READABLE,
+        };
+        Ok(TokioFD(tokio::io::unix::AsyncFd::with_interest(fd, tokio_interest)?))
     }
 }
 
 impl AsyncTime for TokioRT {
     type Interval = TokioInterval;
 
+    type Delay = TokioDelay;
+
     #[inline(always)]
     fn sleep(d: Duration) -> impl Future + Send {
         tokio::time::sleep(d)
     }
 
+    #[inline(always)]
+    fn delay(d: Duration) -> Self::Delay {
+        TokioDelay { sleep: Box::pin(tokio::time::sleep(d)), cancelled: false }
+    }
+
+    /// Follows the paused time of tokio in tests
+    #[inline(always)]
+    fn now() -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
     #[inline(always)]
     fn tick(d: Duration) -> Self::Interval {
         let later = tokio::time::Instant::now() + d;
-        TokioInterval(tokio::time::interval_at(later, d))
+        TokioInterval { inner: tokio::time::interval_at(later, d), missed: 0 }
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl orb::time::AsyncTimeControl for TokioRT {
+    /// Panics if it's a multi-thread runtime, or the clock is already paused.
+    ///
+    /// NOTE: tokio rounds the deadlines up to milliseconds since the runtime started, so a timer
+    /// may need advancing slightly more than its duration. Start with
+    /// [TokioRTBuilder::start_paused()] instead to avoid it.
+    #[inline]
+    fn pause(&self) {
+        let _guard = self.handle().enter();
+        tokio::time::pause();
+    }
+
+    /// Panics if the clock is not paused.
+    #[inline]
+    fn resume(&self) {
+        let _guard = self.handle().enter();
+        tokio::time::resume();
+    }
+
+    /// Should be awaited inside the runtime, panics if the clock is not paused.
+    #[inline(always)]
+    fn advance(&self, d: Duration) -> impl Future<Output = ()> + Send {
+        tokio::time::advance(d)
     }
 }
 
@@ -172,6 +253,28 @@ impl AsyncExec for TokioRT {
         }
     }
 
+    /// Spawn a task with name, visible to tokio-console (with `tracing` feature and
+    /// `tokio_unstable` cfg), otherwise same as `spawn()`.
+    #[inline]
+    fn spawn_named<F, R>(&self, name: &str, f: F) -> Self::AsyncHandle<R>
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: Send + 'static,
+    {
+        #[cfg(all(tokio_unstable, feature = "tracing"))]
+        {
+            // Only fails when the runtime is shutting down, same as panic in spawn()
+            let h =
+                tokio::task::Builder::new().name(name).spawn_on(f, self.handle()).expect("spawn");
+            TokioJoinHandle(h)
+        }
+        #[cfg(not(all(tokio_unstable, feature = "tracing")))]
+        {
+            let _ = name;
+            self.spawn(f)
+        }
+    }
+
     /// Spawn a task and detach it (no handle returned)
     #[inline]
     fn spawn_detach<F, R>(&self, f: F)
@@ -198,12 +301,39 @@ impl AsyncExec for TokioRT {
         TokioThreadHandle(tokio::task::spawn_blocking(f))
     }
 
+    #[inline]
+    fn spawn_blocking_named<F, R>(name: &str, f: F) -> Self::ThreadHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        #[cfg(all(tokio_unstable, feature = "tracing"))]
+        {
+            let h = tokio::task::Builder::new().name(name).spawn_blocking(f).expect("spawn");
+            TokioThreadHandle(h)
+        }
+        #[cfg(not(all(tokio_unstable, feature = "tracing")))]
+        {
+            let _ = name;
+            TokioThreadHandle(tokio::task::spawn_blocking(f))
+        }
+    }
+
     /// Run a future to completion on the runtime
     #[inline]
     fn block_on<F, R>(&self, f: F) -> R
     where
         F: Future<Output = R>,
         R: 'static,
+    {
+        self.block_on_local(f)
+    }
+
+    /// Run a `!Send` future to completion on the current thread
+    #[inline]
+    fn block_on_local<F, R>(&self, f: F) -> R
+    where
+        F: Future<Output = R>,
     {
         match self {
             Self::Runtime(s) => {
@@ -216,21 +346,77 @@ impl AsyncExec for TokioRT {
             }
         }
     }
+
+    #[inline]
+    fn worker_count(&self) -> Option<usize> {
+        Some(self.handle().metrics().num_workers())
+    }
+
+    #[inline]
+    fn flavor(&self) -> RuntimeFlavor {
+        match self.handle().runtime_flavor() {
+            tokio::runtime::RuntimeFlavor::MultiThread => RuntimeFlavor::MultiThread,
+            tokio::runtime::RuntimeFlavor::CurrentThread => RuntimeFlavor::CurrentThread,
+            _ => RuntimeFlavor::Unknown,
+        }
+    }
 }
 
 /// Associate type for TokioRT
-pub struct TokioInterval(tokio::time::Interval);
+pub struct TokioInterval {
+    inner: tokio::time::Interval,
+    missed: u32,
+}
 
 impl TimeInterval for TokioInterval {
     #[inline]
     fn poll_tick(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Instant> {
         let _self = self.get_mut();
-        if let Poll::Ready(i) = _self.0.poll_tick(ctx) {
-            Poll::Ready(i.into_std())
+        if let Poll::Ready(i) = _self.inner.poll_tick(ctx) {
+            let i = i.into_std();
+            _self.missed = count_missed_ticks(i, TokioRT::now(), _self.inner.period());
+            Poll::Ready(i)
         } else {
             Poll::Pending
         }
     }
+
+    #[inline]
+    fn missed_ticks(&self) -> u32 {
+        self.missed
+    }
+}
+
+/// Associate type for TokioRT
+pub struct TokioDelay {
+    sleep: Pin<Box<tokio::time::Sleep>>,
+    cancelled: bool,
+}
+
+impl Future for TokioDelay {
+    type Output = ();
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<()> {
+        let _self = self.get_mut();
+        if _self.cancelled {
+            return Poll::Pending;
+        }
+        _self.sleep.as_mut().poll(ctx)
+    }
+}
+
+impl TimeDelay for TokioDelay {
+    #[inline]
+    fn reset(&mut self, d: Duration) {
+        self.cancelled = false;
+        self.sleep.as_mut().reset(tokio::time::Instant::now() + d);
+    }
+
+    #[inline]
+    fn cancel(&mut self) {
+        self.cancelled = true;
+    }
 }
 
 /// Associate type for TokioRT
@@ -302,13 +488,19 @@ impl<T> ThreadHandle<T> for TokioThreadHandle<T> {
 }
 
 impl<T> Future for TokioThreadHandle<T> {
-    type Output = Result<T, ()>;
+    type Output = Result<T, BlockingError>;
 
     #[inline]
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let _self = unsafe { self.get_unchecked_mut() };
         if let Poll::Ready(r) = Pin::new(&mut _self.0).poll(cx) {
-            return Poll::Ready(r.map_err(|_e| ()));
+            return Poll::Ready(r.map_err(|e| {
+                if e.is_panic() {
+                    BlockingError::Panic(e.into_panic())
+                } else {
+                    BlockingError::Cancelled
+                }
+            }));
         }
         Poll::Pending
     }
@@ -1,5 +1,6 @@
 use orb::prelude::*;
-use orb_test_utils::{runtime::*, time::*, *};
+use orb::runtime::RuntimeFlavor;
+use orb_test_utils::*;
 use orb_tokio::TokioRT;
 use rstest::*;
 use std::time::Duration;
@@ -14,11 +15,9 @@ fn setup() {
 #[case(TokioRT::new_current_thread())]
 fn test_tokio_rt(setup: (), #[case] rt: TokioRT) {
     let _ = setup; // Explicitly ignore the fixture value
-    test_spawn_async(&rt);
-    test_spawn_blocking::<TokioRT>(&rt);
-    test_sleep(&rt);
-    test_tick(&rt);
-    test_tick_stream(&rt);
+    conformance::run_exec(&rt);
+    conformance::run_time(&rt);
+    conformance::run_io(&rt);
 }
 
 #[rstest]
@@ -36,3 +35,107 @@ fn test_tokio_rt_panic(setup: (), #[case] rt: TokioRT) {
         assert!(handle.await.is_err());
     });
 }
+
+#[rstest]
+fn test_tokio_worker_count(setup: ()) {
+    let _ = setup;
+    let rt = TokioRT::new_multi_thread(4);
+    assert_eq!(rt.worker_count(), Some(4));
+    assert_eq!(rt.flavor(), RuntimeFlavor::MultiThread);
+    // Same on the cloned handle
+    let handle = rt.clone();
+    assert_eq!(handle.worker_count(), Some(4));
+    let rt = TokioRT::new_current_thread();
+    assert_eq!(rt.worker_count(), Some(1));
+    assert_eq!(rt.flavor(), RuntimeFlavor::CurrentThread);
+}
+
+#[rstest]
+fn test_tokio_builder(setup: ()) {
+    let _ = setup;
+    let rt = TokioRT::builder()
+        .worker_threads(2)
+        .thread_name("orb-builder")
+        .thread_stack_size(4 * 1024 * 1024)
+        .max_blocking_threads(4)
+        .build()
+        .expect("build");
+    assert_eq!(rt.worker_count(), Some(2));
+    rt.block_on(async {
+        let name = TokioRT::spawn_blocking(|| std::thread::current().name().map(String::from));
+        assert_eq!(name.await.unwrap().as_deref(), Some("orb-builder"));
+    });
+    let _rt = rt.clone();
+    rt.block_on(async move {
+        let name = _rt.spawn(async { std::thread::current().name().map(String::from) });
+        assert_eq!(name.await.unwrap().as_deref(), Some("orb-builder"));
+    });
+    let rt = TokioRT::builder().current_thread().build().expect("build");
+    assert_eq!(rt.flavor(), RuntimeFlavor::CurrentThread);
+}
+
+#[rstest]
+fn test_tokio_write_only_interest(setup: ()) {
+    use orb::io::{AsyncFd, Interest};
+    use std::io::{Read, Write};
+    let _ = setup;
+    let rt = TokioRT::new_current_thread();
+    rt.block_on(async {
+        let (tx, rx) = std::os::unix::net::UnixStream::pair().expect("pair");
+        tx.set_nonblocking(true).expect("nonblocking");
+        rx.set_nonblocking(true).expect("nonblocking");
+        let tx = TokioRT::to_async_fd(tx, Interest::WRITE).expect("register");
+        let rx = TokioRT::to_async_fd(rx, Interest::READ).expect("register");
+        let n = tx.async_write(|mut w| w.write(b"hello")).await.expect("write");
+        assert_eq!(n, 5);
+        let mut buf = [0u8; 16];
+        let n = rx.async_read(|mut r| r.read(&mut buf)).await.expect("read");
+        assert_eq!(&buf[..n], b"hello");
+    });
+}
+
+#[cfg(feature = "test-util")]
+#[rstest]
+fn test_tokio_time_control(setup: ()) {
+    use orb::time::AsyncTimeControl;
+    use std::future::Future;
+    use std::task::Poll;
+    let _ = setup;
+    let rt = TokioRT::builder().current_thread().start_paused(true).build().unwrap();
+    rt.block_on(async {
+        let start = std::time::Instant::now();
+        let clock_start = TokioRT::now();
+        let mut fut =
+            std::pin::pin!(TokioRT::timeout(Duration::from_secs(3), std::future::pending::<()>()));
+        let r = std::future::poll_fn(|cx| Poll::Ready(fut.as_mut().poll(cx))).await;
+        assert!(r.is_pending());
+        rt.advance(Duration::from_secs(3)).await;
+        let r = std::future::poll_fn(|cx| Poll::Ready(fut.as_mut().poll(cx))).await;
+        assert_eq!(r, Poll::Ready(Err(())));
+        assert!(TokioRT::now() - clock_start >= Duration::from_secs(3));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        rt.resume();
+        rt.pause();
+    });
+}
+
+#[cfg(feature = "test-util")]
+#[rstest]
+fn test_tokio_throttle_paused(setup: ()) {
+    use orb::io::{Throttle, duplex};
+    let _ = setup;
+    let rt = TokioRT::builder().current_thread().start_paused(true).build().unwrap();
+    rt.block_on(async {
+        let (local, mut remote) = duplex(1024 * 1024);
+        let mut stream = Throttle::<_, TokioRT>::new(local, 100_000);
+        let data = vec![1u8; 50_000];
+        let start = std::time::Instant::now();
+        let clock_start = TokioRT::now();
+        stream.write_all(&data).await.expect("write");
+        // The tokens are refilled by the paused clock, which auto-advances on sleep
+        assert!(TokioRT::now() - clock_start >= Duration::from_millis(400));
+        assert!(start.elapsed() < Duration::from_millis(200), "{:?}", start.elapsed());
+        let mut buf = vec![0u8; data.len()];
+        remote.read_exact(&mut buf).await.expect("read");
+    });
+}
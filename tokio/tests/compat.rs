@@ -0,0 +1,40 @@
+#![cfg(feature = "tokio-compat")]
+
+use orb::net::{TcpListener, TcpStream};
+use orb::prelude::*;
+use orb_test_utils::*;
+use orb_tokio::{TokioCompat, TokioRT};
+use rstest::*;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+#[fixture]
+fn setup() {
+    init_logger();
+}
+
+#[rstest]
+#[case(TokioRT::new_multi_thread(2))]
+#[case(TokioRT::new_current_thread())]
+fn test_tokio_compat(setup: (), #[case] rt: TokioRT) {
+    let _ = setup; // Explicitly ignore the fixture value
+    rt.block_on(async {
+        let mut listener = TcpListener::<TokioRT>::bind("127.0.0.1:0").await.expect("bind");
+        let server_addr = listener.local_addr().expect("local_addr");
+        let server = rt.spawn(async move {
+            let stream = listener.accept().await.expect("accept");
+            let mut stream = BufReader::new(TokioCompat::new(stream));
+            let mut line = String::new();
+            stream.read_line(&mut line).await.expect("read_line");
+            assert_eq!(line, "GET / HTTP/1.1\r\n");
+            stream.write_all(b"HTTP/1.1 200 OK\r\n").await.expect("write");
+            stream.flush().await.expect("flush");
+        });
+        let client = TcpStream::<TokioRT>::connect(&server_addr).await.expect("connect");
+        let mut client = BufReader::new(TokioCompat::new(client));
+        client.write_all(b"GET / HTTP/1.1\r\n").await.expect("write");
+        let mut line = String::new();
+        client.read_line(&mut line).await.expect("read_line");
+        assert_eq!(line, "HTTP/1.1 200 OK\r\n");
+        server.await.expect("server");
+    });
+}